@@ -11,6 +11,7 @@ fn main() {
         flop: flop_from_str("Td9d6h").unwrap(),
         turn: card_from_str("Qc").unwrap(),
         river: NOT_DEALT,
+        variant: GameVariant::Holdem,
     };
 
     // bet sizes -> 60% of the pot, geometric size, and all-in
@@ -22,8 +23,11 @@ fn main() {
         initial_state: BoardState::Turn, // must match `card_config`
         starting_pot: 200,
         effective_stack: 900,
+        starting_stacks: None,
+        pot_contributions: None,
         rake_rate: 0.0,
         rake_cap: 0.0,
+        rake_model: None,
         flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()], // [OOP, IP]
         turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
         river_bet_sizes: [bet_sizes.clone(), bet_sizes],
@@ -32,6 +36,7 @@ fn main() {
         add_allin_threshold: 1.5, // add all-in if (maximum bet size) <= 1.5x pot
         force_allin_threshold: 0.15, // force all-in if (SPR after the opponent's call) <= 0.15
         merging_threshold: 0.1,
+        fixed_limit: None,
     };
 
     // build the game tree
@@ -49,6 +54,7 @@ fn main() {
 
     // check memory usage
     let (mem_usage, mem_usage_compressed) = game.memory_usage();
+    let mem_usage_quantized = game.memory_usage_quantized();
     println!(
         "Memory usage without compression (32-bit float): {:.2}GB",
         mem_usage as f64 / (1024.0 * 1024.0 * 1024.0)
@@ -57,6 +63,10 @@ fn main() {
         "Memory usage with compression (16-bit integer): {:.2}GB",
         mem_usage_compressed as f64 / (1024.0 * 1024.0 * 1024.0)
     );
+    println!(
+        "Memory usage with 8-bit quantization: {:.2}GB",
+        mem_usage_quantized as f64 / (1024.0 * 1024.0 * 1024.0)
+    );
 
     // allocate memory without compression (use 32-bit float)
     game.allocate_memory(false);
@@ -64,6 +74,9 @@ fn main() {
     // allocate memory with compression (use 16-bit integer)
     // game.allocate_memory(true);
 
+    // allocate memory with 8-bit quantization (for coarse solves)
+    // game.allocate_memory(CompressionMode::Int8);
+
     // solve the game
     let max_num_iterations = 1000;
     let target_exploitability = game.tree_config().starting_pot as f32 * 0.005; // 0.5% of the pot
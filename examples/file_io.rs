@@ -11,6 +11,7 @@ fn main() {
         flop: flop_from_str("Td9d6h").unwrap(),
         turn: card_from_str("Qc").unwrap(),
         river: NOT_DEALT,
+        variant: GameVariant::Holdem,
     };
 
     let bet_sizes = BetSizeOptions::try_from(("60%, e, a", "2.5x")).unwrap();
@@ -19,8 +20,11 @@ fn main() {
         initial_state: BoardState::Turn,
         starting_pot: 200,
         effective_stack: 900,
+        starting_stacks: None,
+        pot_contributions: None,
         rake_rate: 0.0,
         rake_cap: 0.0,
+        rake_model: None,
         flop_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
         turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
         river_bet_sizes: [bet_sizes.clone(), bet_sizes],
@@ -29,6 +33,7 @@ fn main() {
         add_allin_threshold: 1.5,
         force_allin_threshold: 0.15,
         merging_threshold: 0.1,
+        fixed_limit: None,
     };
 
     let action_tree = ActionTree::new(tree_config).unwrap();
@@ -40,7 +45,8 @@ fn main() {
     solve(&mut game, max_num_iterations, target_exploitability, true);
 
     // save the solved game tree to a file
-    // 4th argument is zstd compression level (1-22); requires `zstd` feature to use
+    // 4th argument is the compression: `Some(level)` is zstd compression level (1-22), which
+    // requires `zstd` feature to use, and `Compression::Lz4` requires `lz4` feature to use
     save_data_to_file(&game, "memo string", "filename.bin", None).unwrap();
 
     // load the solved game tree from a file
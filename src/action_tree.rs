@@ -1,6 +1,7 @@
 use crate::bet_size::*;
 use crate::card::*;
 use crate::mutex_like::*;
+use crate::rake::*;
 
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
@@ -67,8 +68,11 @@ pub enum BoardState {
 ///     initial_state: BoardState::Turn,
 ///     starting_pot: 200,
 ///     effective_stack: 900,
+///     starting_stacks: None,
+///     pot_contributions: None,
 ///     rake_rate: 0.05,
 ///     rake_cap: 30.0,
+///     rake_model: None,
 ///     flop_bet_sizes: Default::default(),
 ///     turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
 ///     river_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
@@ -77,6 +81,7 @@ pub enum BoardState {
 ///     add_allin_threshold: 1.5,
 ///     force_allin_threshold: 0.15,
 ///     merging_threshold: 0.1,
+///     fixed_limit: None,
 /// };
 /// ```
 #[derive(Debug, Clone, Default)]
@@ -91,12 +96,33 @@ pub struct TreeConfig {
     /// Initial effective stack. Must be greater than `0`.
     pub effective_stack: i32,
 
+    /// Starting stack of each player (OOP, IP) (set `None` to use `effective_stack` for both).
+    ///
+    /// If this is set, `effective_stack` must be the smaller stack because the chips exceeding it
+    /// can never be wagered. Leave `effective_stack` as `0` to derive it when the [`ActionTree`]
+    /// is built. The starting stacks are only used to report the actual remaining stacks.
+    pub starting_stacks: Option<[i32; 2]>,
+
+    /// Contribution of each player (OOP, IP) to `starting_pot` (set `None` to split it equally).
+    ///
+    /// The remainder of `starting_pot` is treated as dead money (e.g., antes and a folded blind).
+    /// The contributions do not change the equilibrium because they only shift each player's
+    /// payoffs by a constant, and the rake is always computed on the full pot. If this is set,
+    /// the expected values are reported as the net profit of the hand from each player's
+    /// perspective.
+    pub pot_contributions: Option<[i32; 2]>,
+
     /// Rake rate. Must be between `0.0` and `1.0`, inclusive.
     pub rake_rate: f64,
 
     /// Rake cap. Must be non-negative.
     pub rake_cap: f64,
 
+    /// Rake structure (set `None` to use `rake_rate` and `rake_cap`).
+    ///
+    /// If this is set, both `rake_rate` and `rake_cap` must be `0.0`.
+    pub rake_model: Option<RakeModel>,
+
     /// Bet size options of each player for the flop.
     pub flop_bet_sizes: [BetSizeOptions; 2],
 
@@ -131,6 +157,59 @@ pub struct TreeConfig {
     ///
     /// Personal recommendation: around `0.1`
     pub merging_threshold: f64,
+
+    /// Fixed-limit betting structure (set `None` for no-limit).
+    ///
+    /// If this is set, the bet size options must be empty, the donk size options must be `None`,
+    /// and `add_allin_threshold`, `force_allin_threshold`, and `merging_threshold` are ignored.
+    pub fixed_limit: Option<FixedLimitConfig>,
+}
+
+impl TreeConfig {
+    /// Returns the starting stack of the given player.
+    #[inline]
+    pub fn starting_stack(&self, player: usize) -> i32 {
+        self.starting_stacks
+            .map_or(self.effective_stack, |stacks| stacks[player])
+    }
+
+    /// Returns the amount of dead money in the starting pot.
+    #[inline]
+    pub fn dead_money(&self) -> i32 {
+        self.pot_contributions
+            .map_or(0, |c| self.starting_pot - c[0] - c[1])
+    }
+}
+
+/// A struct representing the betting structure of fixed-limit hold'em.
+///
+/// The bet unit is `small_bet` on the flop and `big_bet` on the turn and river. Every bet and raise
+/// increases the amount by exactly one bet unit, unless the player does not have enough chips, in
+/// which case the player can only go all-in.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// // $2/$4 limit: bet, raise, 3-bet, and cap
+/// let fixed_limit = FixedLimitConfig {
+///     small_bet: 2,
+///     big_bet: 4,
+///     max_bets: 4,
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct FixedLimitConfig {
+    /// Bet unit on the flop. Must be greater than `0`.
+    pub small_bet: i32,
+
+    /// Bet unit on the turn and river. Must be greater than `0`.
+    pub big_bet: i32,
+
+    /// Maximum number of bets per street, counting the initial bet (e.g., `4` allows a bet, a
+    /// raise, a 3-bet, and a 4-bet). Must be greater than `0`.
+    pub max_bets: i32,
 }
 
 /// A struct representing an abstract game tree.
@@ -175,7 +254,12 @@ type EjectedActionTree = (
 impl ActionTree {
     /// Creates a new [`ActionTree`] with the specified configuration.
     #[inline]
-    pub fn new(config: TreeConfig) -> Result<Self, String> {
+    pub fn new(mut config: TreeConfig) -> Result<Self, String> {
+        if let Some(stacks) = config.starting_stacks {
+            if config.effective_stack == 0 {
+                config.effective_stack = stacks[0].min(stacks[1]);
+            }
+        }
         Self::check_config(&config)?;
         let mut ret = Self {
             config,
@@ -377,6 +461,19 @@ impl ActionTree {
         self.total_bet_amount_recursive(&self.root.lock(), &self.history, info)
     }
 
+    /// Returns the remaining stack of each player (OOP, IP) based on the starting stacks.
+    #[inline]
+    pub fn remaining_stacks(&self) -> [i32; 2] {
+        let total_bet_amount = self.total_bet_amount();
+        [0, 1].map(|player| self.config.starting_stack(player) - total_bet_amount[player])
+    }
+
+    /// Returns the root node.
+    #[inline]
+    pub(crate) fn root(&self) -> MutexGuardLike<'_, ActionTreeNode> {
+        self.root.lock()
+    }
+
     /// Ejects the fields.
     #[inline]
     pub(crate) fn eject(self) -> EjectedActionTree {
@@ -390,10 +487,10 @@ impl ActionTree {
             let mut node = &*self.root.lock() as *const ActionTreeNode;
             for action in &self.history {
                 while (*node).is_chance() {
-                    node = &*(*node).children[0].lock();
+                    node = &*(&(*node).children)[0].lock();
                 }
                 let index = (*node).actions.iter().position(|x| x == action).unwrap();
-                node = &*(*node).children[index].lock();
+                node = &*(&(*node).children)[index].lock();
             }
             &*node
         }
@@ -405,7 +502,7 @@ impl ActionTree {
         unsafe {
             let mut node = self.current_node() as *const ActionTreeNode;
             while (*node).is_chance() {
-                node = &*(*node).children[0].lock();
+                node = &*(&(*node).children)[0].lock();
             }
             &*node
         }
@@ -421,6 +518,19 @@ impl ActionTree {
             ));
         }
 
+        if let Some(stacks) = config.starting_stacks {
+            if stacks[0] <= 0 || stacks[1] <= 0 {
+                return Err(format!("Starting stacks must be positive: {stacks:?}"));
+            }
+
+            if config.effective_stack != stacks[0].min(stacks[1]) {
+                return Err(format!(
+                    "Effective stack must be the smaller starting stack: {}",
+                    config.effective_stack
+                ));
+            }
+        }
+
         if config.effective_stack <= 0 {
             return Err(format!(
                 "Effective stack must be positive: {}",
@@ -428,6 +538,20 @@ impl ActionTree {
             ));
         }
 
+        if let Some(contributions) = config.pot_contributions {
+            if contributions[0] < 0 || contributions[1] < 0 {
+                return Err(format!(
+                    "Pot contributions must be non-negative: {contributions:?}"
+                ));
+            }
+
+            if contributions[0] + contributions[1] > config.starting_pot {
+                return Err(format!(
+                    "Pot contributions must not exceed the starting pot: {contributions:?}"
+                ));
+            }
+        }
+
         if config.rake_rate < 0.0 {
             return Err(format!(
                 "Rake rate must be non-negative: {}",
@@ -449,6 +573,16 @@ impl ActionTree {
             ));
         }
 
+        if let Some(rake_model) = &config.rake_model {
+            if config.rake_rate != 0.0 || config.rake_cap != 0.0 {
+                return Err(
+                    "Rake rate and rake cap must be 0.0 when rake model is specified".to_string(),
+                );
+            }
+
+            rake_model.check()?;
+        }
+
         if config.add_allin_threshold < 0.0 {
             return Err(format!(
                 "Add all-in threshold must be non-negative: {}",
@@ -470,6 +604,54 @@ impl ActionTree {
             ));
         }
 
+        if let Some(limit) = &config.fixed_limit {
+            Self::check_fixed_limit_config(config, limit)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks the configuration of the fixed-limit mode.
+    #[inline]
+    fn check_fixed_limit_config(
+        config: &TreeConfig,
+        limit: &FixedLimitConfig,
+    ) -> Result<(), String> {
+        if limit.small_bet <= 0 {
+            return Err(format!("Small bet must be positive: {}", limit.small_bet));
+        }
+
+        if limit.big_bet <= 0 {
+            return Err(format!("Big bet must be positive: {}", limit.big_bet));
+        }
+
+        if limit.max_bets <= 0 {
+            return Err(format!(
+                "Maximum number of bets must be positive: {}",
+                limit.max_bets
+            ));
+        }
+
+        let streets = [
+            ("flop", &config.flop_bet_sizes),
+            ("turn", &config.turn_bet_sizes),
+            ("river", &config.river_bet_sizes),
+        ];
+
+        for (street, options) in streets {
+            for (player, option) in ["OOP", "IP"].iter().zip(options.iter()) {
+                if let Some(size) = option.bet.iter().chain(option.raise.iter()).next() {
+                    return Err(format!(
+                        "Bet size cannot be specified in fixed-limit mode: {street} {player} {size:?}"
+                    ));
+                }
+            }
+        }
+
+        if config.turn_donk_sizes.is_some() || config.river_donk_sizes.is_some() {
+            return Err("Donk size cannot be specified in fixed-limit mode".to_string());
+        }
+
         Ok(())
     }
 
@@ -554,7 +736,42 @@ impl ActionTree {
 
         let mut actions = Vec::new();
 
-        if donk_options.is_some()
+        if let Some(limit) = &self.config.fixed_limit {
+            let unit = match node.board_state {
+                BoardState::Flop => limit.small_bet,
+                _ => limit.big_bet,
+            };
+
+            if matches!(
+                info.prev_action,
+                Action::None | Action::Check | Action::Chance(_)
+            ) {
+                // check
+                actions.push(Action::Check);
+
+                // bet
+                if unit < max_amount {
+                    actions.push(Action::Bet(unit));
+                } else {
+                    actions.push(Action::AllIn(max_amount));
+                }
+            } else {
+                // fold
+                actions.push(Action::Fold);
+
+                // call
+                actions.push(Action::Call);
+
+                // raise
+                if !info.allin_flag && info.num_bets < limit.max_bets {
+                    if prev_amount + unit < max_amount {
+                        actions.push(Action::Raise(prev_amount + unit));
+                    } else {
+                        actions.push(Action::AllIn(max_amount));
+                    }
+                }
+            }
+        } else if donk_options.is_some()
             && matches!(info.prev_action, Action::Chance(_))
             && info.oop_call_flag
         {
@@ -662,10 +879,19 @@ impl ActionTree {
             }
         }
 
+        // thresholds are not applied in fixed-limit mode
+        let (force_allin_threshold, merging_threshold) = match self.config.fixed_limit {
+            Some(_) => (0.0, 0.0),
+            None => (
+                self.config.force_allin_threshold,
+                self.config.merging_threshold,
+            ),
+        };
+
         let is_above_threshold = |amount: i32| {
             let new_amount_diff = amount - prev_amount;
             let new_pot = pot + 2 * new_amount_diff;
-            let threshold = (new_pot as f64 * self.config.force_allin_threshold).round() as i32;
+            let threshold = (new_pot as f64 * force_allin_threshold).round() as i32;
             max_amount <= amount + threshold
         };
 
@@ -697,7 +923,7 @@ impl ActionTree {
         actions.dedup();
 
         // merge bet actions with close amounts
-        actions = merge_bet_actions(actions, pot, prev_amount, self.config.merging_threshold);
+        actions = merge_bet_actions(actions, pot, prev_amount, merging_threshold);
 
         let player_after_call = match node.board_state {
             BoardState::River => PLAYER_TERMINAL_FLAG,
@@ -977,12 +1203,12 @@ impl ActionTree {
 
 impl ActionTreeNode {
     #[inline]
-    fn is_terminal(&self) -> bool {
+    pub(crate) fn is_terminal(&self) -> bool {
         self.player & PLAYER_TERMINAL_FLAG != 0
     }
 
     #[inline]
-    fn is_chance(&self) -> bool {
+    pub(crate) fn is_chance(&self) -> bool {
         self.player & PLAYER_CHANCE_FLAG != 0
     }
 }
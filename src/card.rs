@@ -16,6 +16,186 @@ pub type Card = u8;
 /// Constant representing that the card is not yet dealt.
 pub const NOT_DEALT: Card = Card::MAX;
 
+/// An enum representing the game variant, which determines the deck and the hand rankings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(u8)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub enum GameVariant {
+    /// Texas hold'em with the standard 52-card deck.
+    #[default]
+    Holdem = 0,
+
+    /// Short-deck (6+) hold'em with a 36-card deck (2s-5s are removed).
+    ///
+    /// A flush ranks above a full house, and A-6-7-8-9 is treated as the lowest straight.
+    ShortDeck = 1,
+}
+
+impl GameVariant {
+    /// Returns the bit mask of cards that are removed from the deck.
+    #[inline]
+    pub fn removed_cards_mask(self) -> u64 {
+        match self {
+            GameVariant::Holdem => 0,
+            GameVariant::ShortDeck => (1 << 16) - 1,
+        }
+    }
+
+    /// Returns the number of cards in the deck.
+    #[inline]
+    pub fn num_cards(self) -> usize {
+        52 - self.removed_cards_mask().count_ones() as usize
+    }
+}
+
+/// Category of a hand relative to the board, in ascending order of strength in Texas hold'em.
+///
+/// The category is determined by the best five cards. A hand whose hole cards do not improve the
+/// best hand of the board alone, ignoring the kickers (i.e., the board plays), is [`HighCard`]; a
+/// hand that makes a better hand of the same category, such as a higher flush on a four-flush
+/// board, keeps the category. Pairs are classified by the rank of the pair relative to the board
+/// cards.
+///
+/// [`HighCard`]: HandCategory::HighCard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandCategory {
+    /// No pair or better is made with the hole cards.
+    HighCard,
+
+    /// A pair below the second highest board card, including underpairs.
+    WeakPair,
+
+    /// A pair of the second highest board card, or a pocket pair between the highest and the
+    /// second highest board cards.
+    SecondPair,
+
+    /// A pair of the highest board card.
+    TopPair,
+
+    /// A pocket pair above all the board cards.
+    Overpair,
+
+    /// Two pair.
+    TwoPair,
+
+    /// Three of a kind (a set or trips).
+    ThreeOfAKind,
+
+    /// Straight.
+    Straight,
+
+    /// Flush.
+    Flush,
+
+    /// Full house.
+    FullHouse,
+
+    /// Four of a kind.
+    FourOfAKind,
+
+    /// Straight flush.
+    StraightFlush,
+}
+
+impl HandCategory {
+    /// Classifies `hand` on `board`, which consists of 3 to 5 cards.
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let board = flop_from_str("Td9d6h").unwrap();
+    /// let hand = (card_from_str("Ts").unwrap(), card_from_str("2c").unwrap());
+    /// let category = HandCategory::classify(hand, &board, GameVariant::Holdem);
+    /// assert_eq!(category, HandCategory::TopPair);
+    /// ```
+    pub fn classify(hand: (Card, Card), board: &[Card], variant: GameVariant) -> Self {
+        let short_deck = variant == GameVariant::ShortDeck;
+        let made = Self::made_category(board.iter().chain([&hand.0, &hand.1]), short_deck);
+        if made == Self::made_category(board.iter(), short_deck) {
+            return Self::HighCard;
+        }
+
+        let category = made.0;
+
+        if category != Self::WeakPair {
+            return category;
+        }
+
+        // the board is not paired here, so the pair is made with a hole card
+        let rank = |card: Card| card >> 2;
+        let pair_rank =
+            if rank(hand.0) == rank(hand.1) || board.iter().any(|&c| rank(c) == rank(hand.0)) {
+                rank(hand.0)
+            } else {
+                rank(hand.1)
+            };
+
+        let mut board_ranks = board.iter().map(|&c| rank(c)).collect::<Vec<_>>();
+        board_ranks.sort_unstable_by(|a, b| b.cmp(a));
+
+        if pair_rank > board_ranks[0] {
+            Self::Overpair
+        } else if pair_rank == board_ranks[0] {
+            Self::TopPair
+        } else if pair_rank >= board_ranks[1] {
+            Self::SecondPair
+        } else {
+            Self::WeakPair
+        }
+    }
+
+    /// Returns the category of the best five cards, where any pair is reported as `WeakPair`, and
+    /// the key that orders the hands of the category without their kickers.
+    fn made_category<'a>(cards: impl Iterator<Item = &'a Card>, short_deck: bool) -> (Self, i32) {
+        let mut rankset = 0;
+        let mut rankset_suit = [0; 4];
+        let mut rank_count = [0; 13];
+
+        for &card in cards {
+            let rank = card as usize >> 2;
+            rankset |= 1 << rank;
+            rankset_suit[card as usize & 3] |= 1 << rank;
+            rank_count[rank] += 1;
+        }
+
+        let flush_suit = rankset_suit.iter().position(|s: &i32| s.count_ones() >= 5);
+        if let Some(suit) = flush_suit {
+            let straight = find_straight(rankset_suit[suit], short_deck);
+            if straight != 0 {
+                return (Self::StraightFlush, straight);
+            }
+        }
+
+        // ranks appearing at least `n` times, in descending order
+        let ranks = |n| (0..13).rev().filter(move |&r| rank_count[r as usize] >= n);
+        let trips = ranks(3).next();
+        let full_house_pair = ranks(2).find(|&r| Some(r) != trips);
+        let straight = find_straight(rankset, short_deck);
+
+        if let Some(quads) = ranks(4).next() {
+            (Self::FourOfAKind, quads)
+        } else if let (Some(trips), Some(pair), false) =
+            (trips, full_house_pair, short_deck && flush_suit.is_some())
+        {
+            (Self::FullHouse, trips << 4 | pair)
+        } else if let Some(suit) = flush_suit {
+            (Self::Flush, keep_n_msb(rankset_suit[suit], 5))
+        } else if straight != 0 {
+            (Self::Straight, straight)
+        } else if let Some(trips) = trips {
+            (Self::ThreeOfAKind, trips)
+        } else {
+            let mut pairs = ranks(2);
+            match (pairs.next(), pairs.next()) {
+                (Some(high), Some(low)) => (Self::TwoPair, high << 4 | low),
+                (Some(pair), None) => (Self::WeakPair, pair),
+                _ => (Self::HighCard, 0),
+            }
+        }
+    }
+}
+
 /// A struct containing the card configuration.
 ///
 /// # Examples
@@ -30,6 +210,7 @@ pub const NOT_DEALT: Card = Card::MAX;
 ///     flop: flop_from_str("Td9d6h").unwrap(),
 ///     turn: card_from_str("Qc").unwrap(),
 ///     river: NOT_DEALT,
+///     variant: GameVariant::Holdem,
 /// };
 /// ```
 #[derive(Debug, Clone)]
@@ -46,6 +227,9 @@ pub struct CardConfig {
 
     /// River card: must be in range [`0`, `52`) or `NOT_DEALT`.
     pub river: Card,
+
+    /// Game variant. The board cards must not be removed from the deck of this variant.
+    pub variant: GameVariant,
 }
 
 impl Default for CardConfig {
@@ -56,6 +240,7 @@ impl Default for CardConfig {
             flop: [NOT_DEALT; 3],
             turn: NOT_DEALT,
             river: NOT_DEALT,
+            variant: GameVariant::Holdem,
         }
     }
 }
@@ -116,9 +301,12 @@ impl CardConfig {
             Indices::default()
         };
 
+        let removed_mask = self.variant.removed_cards_mask();
+
         let mut ret_turn = vec![Indices::default(); 52];
         for board in 0..52 {
-            if !self.flop.contains(&board)
+            if (1 << board) & removed_mask == 0
+                && !self.flop.contains(&board)
                 && (self.turn == NOT_DEALT || self.turn == board)
                 && self.river == NOT_DEALT
             {
@@ -130,7 +318,8 @@ impl CardConfig {
         let mut ret_river = vec![Indices::default(); 52 * 51 / 2];
         for board1 in 0..52 {
             for board2 in board1 + 1..52 {
-                if !self.flop.contains(&board1)
+                if ((1 << board1) | (1 << board2)) & removed_mask == 0
+                    && !self.flop.contains(&board1)
                     && !self.flop.contains(&board2)
                     && (self.turn == NOT_DEALT || board1 == self.turn || board2 == self.turn)
                     && (self.river == NOT_DEALT || board1 == self.river || board2 == self.river)
@@ -191,9 +380,16 @@ impl CardConfig {
             board = board.add_card(card as usize);
         }
 
+        let removed_mask = self.variant.removed_cards_mask();
+        let evaluate = match self.variant {
+            GameVariant::Holdem => Hand::evaluate,
+            GameVariant::ShortDeck => Hand::evaluate_short_deck,
+        };
+
         for board1 in 0..52 {
             for board2 in board1 + 1..52 {
-                if !board.contains(board1 as usize)
+                if ((1u64 << board1) | (1u64 << board2)) & removed_mask == 0
+                    && !board.contains(board1 as usize)
                     && !board.contains(board2 as usize)
                     && (self.turn == NOT_DEALT || board1 == self.turn || board2 == self.turn)
                     && (self.river == NOT_DEALT || board1 == self.river || board2 == self.river)
@@ -224,7 +420,7 @@ impl CardConfig {
                                     } else {
                                         let hand = board.add_card(c1).add_card(c2);
                                         Some(StrengthItem {
-                                            strength: hand.evaluate() + 1, // +1 to avoid 0
+                                            strength: evaluate(&hand) + 1, // +1 to avoid 0
                                             index: index as u16,
                                         })
                                     }
@@ -260,7 +456,11 @@ impl CardConfig {
             next_index += 1;
         }
 
-        let flop_mask: u64 = (1 << self.flop[0]) | (1 << self.flop[1]) | (1 << self.flop[2]);
+        // removed cards are treated as if they were already dealt
+        let flop_mask: u64 = (1 << self.flop[0])
+            | (1 << self.flop[1])
+            | (1 << self.flop[2])
+            | self.variant.removed_cards_mask();
         let mut flop_rankset = [0; 4];
 
         for &card in &self.flop {
@@ -448,4 +648,49 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_hand_category() {
+        let check = |hand: &str, board: &str, variant, expected| {
+            let hand = (
+                card_from_str(&hand[0..2]).unwrap(),
+                card_from_str(&hand[2..4]).unwrap(),
+            );
+            let board = (0..board.len() / 2)
+                .map(|i| card_from_str(&board[2 * i..2 * i + 2]).unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(HandCategory::classify(hand, &board, variant), expected);
+        };
+
+        let holdem = GameVariant::Holdem;
+        check("AcKc", "Td9d6h", holdem, HandCategory::HighCard);
+        check("2c2d", "Td9d6h", holdem, HandCategory::WeakPair);
+        check("6c5c", "Td9d6h", holdem, HandCategory::WeakPair);
+        check("Jc9c", "Td9d6h", holdem, HandCategory::SecondPair);
+        check("JcJh", "Qd9d6h", holdem, HandCategory::SecondPair);
+        check("AcTc", "Td9d6h", holdem, HandCategory::TopPair);
+        check("QcQh", "Td9d6h", holdem, HandCategory::Overpair);
+        check("Tc9c", "Td9d6h", holdem, HandCategory::TwoPair);
+        check("AcKc", "Td9d6h9s6s", holdem, HandCategory::HighCard);
+        check("6c6d", "Td9d6h", holdem, HandCategory::ThreeOfAKind);
+        check("8c7c", "Td9d6h", holdem, HandCategory::Straight);
+        check("AdKd", "Td9d6h2d", holdem, HandCategory::Flush);
+        check("AcKc", "TdTcTh6d6h", holdem, HandCategory::HighCard);
+        check("9c9h", "Td9d6hTc", holdem, HandCategory::FullHouse);
+        check("ThTs", "Td9d6hTc", holdem, HandCategory::FourOfAKind);
+        check("8d7d", "Td9d6d2c", holdem, HandCategory::StraightFlush);
+
+        // same category as the board, but the hole cards make a better hand
+        check("Jh3c", "AhKhQh5h2h", holdem, HandCategory::Flush);
+        check("3h2c", "AhKhQh5h4h", holdem, HandCategory::HighCard);
+        check("JcTc", "9s8s7d6c2h", holdem, HandCategory::Straight);
+        check("AcKc", "9s8s7d6c5h", holdem, HandCategory::HighCard);
+        check("AcAd", "KhKs7d7c2h", holdem, HandCategory::TwoPair);
+        check("AcAd", "KhKs7d7c", holdem, HandCategory::TwoPair);
+        check("9c9d", "ThTsTd6c6h", holdem, HandCategory::FullHouse);
+
+        let short_deck = GameVariant::ShortDeck;
+        check("Ac6c", "7d8d9h", short_deck, HandCategory::Straight);
+        check("AdKd", "TdTc9dTh6d", short_deck, HandCategory::Flush);
+    }
 }
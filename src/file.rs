@@ -1,29 +1,186 @@
 // [File format]
 // The file consists of a header and a body. The header is as follows:
 //  - Magic number (4 bytes): 90 57 f1 09
-//  - Version number (1 byte): 1
-//  - Compression type (1 byte): 0 (none), 1 (zstd)
-//  - Data type (1 byte): 0 (game), 1 (bunching)
+//  - Version number (1 byte): 2
+//  - Compression type (1 byte): 0 (none), 1 (zstd), 2 (lz4), 3 (zstd with dictionary)
+//  - Data type (1 byte): 0 (game), 1 (bunching), 2 (street-indexed game), 3 (lock set)
 //  - Estimated memory usage (`VarIntEncoding`)
 //  - Memo string
 //
+// Since version 2, the encoded body is followed by its CRC-32 checksum (4 bytes, little endian),
+// which is compressed together with the body. For a street-indexed game, the checksum covers
+// everything between the header and the checksum.
+//
+// The body of a street-indexed game is not compressed and is as follows:
+//  - Section of the initial street: the game encoded up to the initial street
+//  - For each later street up to the deepest stored street:
+//    - Street section: the locking data and the nodes of the street without their storages
+//    - Subtree sections: the storages of the nodes under each chance node child on the street,
+//      in the order of the chance nodes in the game tree
+//  - Index: file offsets of the street sections (`[u64; 3]`, 0 if not stored) and of the subtree
+//    sections of each street (`[Vec<u64>; 3]`)
+//  - File offset of the index (8 bytes, little endian)
+//  - CRC-32 checksum (4 bytes, little endian)
+//
+// The version number covers the layout of the header and the file container. The body of a game
+// has its own version string, and the games encoded with older layouts are also decoded (see
+// `VERSIONS` in `game/serialization.rs`). Use `migrate_file` to rewrite them with the current
+// layout.
+//
 // `VarIntEncoding`: https://github.com/bincode-org/bincode/blob/trunk/docs/spec.md#varintencoding
 
+use crate::action_tree::*;
 use crate::bunching::*;
+use crate::card::*;
 use crate::game::*;
 use crate::interface::*;
-use bincode::{Decode, Encode};
+use bincode::{error::DecodeError, Decode, Encode};
 use std::fs::File;
-use std::io::{BufReader, BufWriter, Read, Write};
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 
 const MAGIC: u32 = 0x09f15790;
-const VERSION: u8 = 1;
+const VERSION: u8 = 2;
+const CORRUPTED_TRUNCATED: &str = "Data is corrupted: unexpected end of data";
+const CORRUPTED_CHECKSUM: &str = "Data is corrupted: checksum mismatch";
+const STREETS: [BoardState; 3] = [BoardState::Flop, BoardState::Turn, BoardState::River];
 
-#[doc(hidden)]
+/// The prefix of the error messages returned when the data is truncated or its checksum does not
+/// match.
+///
+/// Use [`is_corruption_error`] to check for it.
+pub const CORRUPTED_ERROR_PREFIX: &str = "Data is corrupted";
+
+/// Returns whether the `error` returned by the load and verify functions indicates corrupted data.
+///
+/// Corrupted data is truncated or fails the checksum. Other errors, such as a missing file, an
+/// invalid data type, or data saved by an unsupported version, are not treated as corruption.
+///
+/// # Examples
+/// ```no_run
+/// use postflop_solver::*;
+///
+/// if let Err(e) = verify_file("library/Td9d6h.flop") {
+///     if is_corruption_error(&e) {
+///         std::fs::remove_file("library/Td9d6h.flop").unwrap();
+///     }
+/// }
+/// ```
+#[inline]
+pub fn is_corruption_error(error: &str) -> bool {
+    error.starts_with(CORRUPTED_ERROR_PREFIX)
+}
+
+/// The type of data saved in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DataType {
+    /// [`PostFlopGame`] saved by [`save_data_to_file`].
     Game = 0,
+
+    /// [`BunchingData`] saved by [`save_data_to_file`].
     Bunching = 1,
+
+    /// [`PostFlopGame`] saved by [`save_indexed_game_to_file`].
+    IndexedGame = 2,
+
+    /// [`LockSet`] saved by [`save_data_to_file`].
+    LockSet = 3,
+}
+
+/// The compression type of a file body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileCompression {
+    /// No compression.
+    None = 0,
+
+    /// zstd compression.
+    Zstd = 1,
+
+    /// LZ4 compression.
+    Lz4 = 2,
+
+    /// zstd compression with a dictionary.
+    ZstdWithDictionary = 3,
+}
+
+/// Compression of the body of a saved file, specified in [`save_data_to_file`].
+///
+/// `Option<i32>` can also be used to specify the compression: `None` means [`None`] and
+/// `Some(level)` means [`Zstd`] with the given level.
+///
+/// [`None`]: Compression::None
+/// [`Zstd`]: Compression::Zstd
+#[derive(Debug, Clone, Copy, Default)]
+pub enum Compression<'a> {
+    /// No compression.
+    #[default]
+    None,
+
+    /// zstd compression with the given level (1-22; 0 means the default level).
+    /// Requires the `zstd` feature.
+    Zstd(i32),
+
+    /// zstd compression with the given level and dictionary. The same dictionary is required to
+    /// load the data. A dictionary can be trained with [`train_zstd_dictionary`].
+    /// Requires the `zstd` feature.
+    ZstdWithDictionary(i32, &'a [u8]),
+
+    /// LZ4 compression, which compresses less than zstd but decompresses much faster.
+    /// Requires the `lz4` feature.
+    Lz4,
+}
+
+impl From<Option<i32>> for Compression<'_> {
+    #[inline]
+    fn from(compression_level: Option<i32>) -> Self {
+        match compression_level {
+            None => Self::None,
+            Some(level) => Self::Zstd(level),
+        }
+    }
+}
+
+impl FileCompression {
+    /// Returns whether the compression is enabled by the crate features.
+    #[inline]
+    fn is_supported(self) -> bool {
+        match self {
+            Self::None => true,
+            Self::Zstd | Self::ZstdWithDictionary => cfg!(feature = "zstd"),
+            Self::Lz4 => cfg!(feature = "lz4"),
+        }
+    }
+}
+
+/// Metadata of a saved file, which can be read without decoding the whole body.
+#[derive(Debug, Clone)]
+pub struct FileMetadata {
+    /// The version number of the file format.
+    pub version: u8,
+
+    /// The compression type of the body.
+    pub compression: FileCompression,
+
+    /// The type of the saved data.
+    pub data_type: DataType,
+
+    /// The estimated memory usage of the data when loaded (in bytes).
+    pub estimated_memory_usage: u64,
+
+    /// The memo string saved with the data.
+    pub memo: String,
+
+    /// The version string of the encoded layout of the saved game. `None` if the data is not a
+    /// game. Games saved by older versions can be rewritten with [`migrate_file`].
+    pub game_version: Option<String>,
+
+    /// The card configuration of the saved game. `None` if the data is not a game or was saved by
+    /// an unsupported version.
+    pub card_config: Option<CardConfig>,
+
+    /// The tree configuration of the saved game. `None` if the data is not a game or was saved by
+    /// an unsupported version.
+    pub tree_config: Option<TreeConfig>,
 }
 
 /// A trait for data that can be saved into a file.
@@ -36,6 +193,15 @@ pub trait FileData: Decode + Encode {
     fn estimated_memory_usage(&self) -> u64;
 }
 
+struct Header {
+    version: u8,
+    compression: FileCompression,
+    data_type: u8,
+    estimated_memory_usage: u64,
+    memo: String,
+    has_checksum: bool,
+}
+
 fn encode_into_std_write<E: Encode, W: Write>(
     val: E,
     writer: &mut W,
@@ -45,6 +211,34 @@ fn encode_into_std_write<E: Encode, W: Write>(
         .map_err(|e| format!("{}: {}", err_msg, e))
 }
 
+fn write_header<W: Write>(header: &Header, writer: &mut W) -> Result<(), String> {
+    encode_into_std_write(MAGIC, writer, "Failed to write magic number")?;
+    encode_into_std_write(header.version, writer, "Failed to write version number")?;
+    encode_into_std_write(
+        header.compression as u8,
+        writer,
+        "Failed to write compression type",
+    )?;
+    encode_into_std_write(header.data_type, writer, "Failed to write data type")?;
+    encode_into_std_write(
+        header.estimated_memory_usage,
+        writer,
+        "Failed to write memory usage",
+    )?;
+    encode_into_std_write(header.memo.as_str(), writer, "Failed to write memo")?;
+    Ok(())
+}
+
+/// Encodes the body followed by its checksum.
+fn encode_body<E: Encode, W: Write>(val: E, writer: &mut W) -> Result<(), String> {
+    let mut checksum_writer = ChecksumWriter::new(&mut *writer);
+    encode_into_std_write(val, &mut checksum_writer, "Failed to write data")?;
+    let checksum = checksum_writer.checksum();
+    writer
+        .write_all(&checksum.to_le_bytes())
+        .map_err(|e| format!("Failed to write checksum: {}", e))
+}
+
 /// Saves data into a standard writer.
 ///
 /// This function serializes the `data` into the `writer`.
@@ -53,64 +247,90 @@ fn encode_into_std_write<E: Encode, W: Write>(
 ///
 /// # Arguments
 ///
-/// - `data`: The data to be saved, which is either a [`PostFlopGame`] or a [`BunchingData`].
+/// - `data`: The data to be saved, which is a [`PostFlopGame`], a [`BunchingData`], or a
+///   [`LockSet`].
 /// - `memo`: A memo string to be saved with the data.
 /// - `writer`: The writer to write the data into.
-/// - `compression_level`: The zstd compression level to use. If `None`, no compression is used.
-///   `Some(level)` can only be specified if the `zstd` feature is enabled.
-pub fn save_data_into_std_write<T: FileData, W: Write>(
+/// - `compression`: The compression of the data (see [`Compression`]). `None` means no compression
+///   and `Some(level)` means zstd compression with the given level, which can only be specified
+///   if the `zstd` feature is enabled.
+pub fn save_data_into_std_write<'a, T: FileData, W: Write>(
     data: &T,
     memo: &str,
     writer: &mut W,
-    compression_level: Option<i32>,
+    compression: impl Into<Compression<'a>>,
 ) -> Result<(), String> {
     if !data.is_ready_to_save() {
         return Err("Data is not ready to save".to_string());
     }
 
-    #[cfg(not(feature = "zstd"))]
-    if compression_level.is_some() {
+    let compression = compression.into();
+    let compression_type = match compression {
+        Compression::None => FileCompression::None,
+        Compression::Zstd(_) => FileCompression::Zstd,
+        Compression::ZstdWithDictionary(..) => FileCompression::ZstdWithDictionary,
+        Compression::Lz4 => FileCompression::Lz4,
+    };
+
+    if !compression_type.is_supported() {
         return Err("Compression is not supported".to_string());
     }
 
-    encode_into_std_write(MAGIC, writer, "Failed to write magic number")?;
-    encode_into_std_write(VERSION, writer, "Failed to write version number")?;
+    let header = Header {
+        version: VERSION,
+        compression: compression_type,
+        data_type: T::data_type() as u8,
+        estimated_memory_usage: data.estimated_memory_usage(),
+        memo: memo.to_string(),
+        has_checksum: true,
+    };
 
-    let compression_type = compression_level.is_some() as u8;
-    encode_into_std_write(compression_type, writer, "Failed to write compression type")?;
+    write_header(&header, writer)?;
 
-    encode_into_std_write(T::data_type() as u8, writer, "Failed to write data type")?;
-    encode_into_std_write(
-        data.estimated_memory_usage(),
-        writer,
-        "Failed to write memory usage",
-    )?;
+    let flush_err = |e| format!("Failed to flush writer: {}", e);
 
-    encode_into_std_write(memo, writer, "Failed to write memo")?;
-
-    if compression_level.is_none() {
-        encode_into_std_write(data, writer, "Failed to write data")?;
-        writer
-            .flush()
-            .map_err(|e| format!("Failed to flush writer: {}", e))?;
-    }
+    match compression {
+        Compression::None => {
+            encode_body(data, writer)?;
+            writer.flush().map_err(flush_err)?;
+        }
 
-    #[cfg(feature = "zstd")]
-    if let Some(compression_level) = compression_level {
-        let mut zstd_encoder = zstd::stream::Encoder::new(writer, compression_level)
+        #[cfg(feature = "zstd")]
+        Compression::Zstd(level) | Compression::ZstdWithDictionary(level, _) => {
+            let mut zstd_encoder = match compression {
+                Compression::ZstdWithDictionary(_, dictionary) => {
+                    zstd::stream::Encoder::with_dictionary(writer, level, dictionary)
+                }
+                _ => zstd::stream::Encoder::new(writer, level),
+            }
             .map_err(|e| format!("Failed to create zstd encoder: {}", e))?;
 
-        #[cfg(feature = "rayon")]
-        zstd_encoder
-            .multithread(rayon::current_num_threads() as u32)
-            .map_err(|e| format!("Failed to enable multithreaded zstd encoder: {}", e))?;
+            #[cfg(feature = "rayon")]
+            zstd_encoder
+                .multithread(rayon::current_num_threads() as u32)
+                .map_err(|e| format!("Failed to enable multithreaded zstd encoder: {}", e))?;
+
+            encode_body(data, &mut zstd_encoder)?;
+            zstd_encoder
+                .finish()
+                .map_err(|e| format!("Failed to finish zstd encoder: {}", e))?
+                .flush()
+                .map_err(flush_err)?;
+        }
 
-        encode_into_std_write(data, &mut zstd_encoder, "Failed to write data")?;
-        zstd_encoder
-            .finish()
-            .map_err(|e| format!("Failed to finish zstd encoder: {}", e))?
-            .flush()
-            .map_err(|e| format!("Failed to flush writer: {}", e))?;
+        #[cfg(feature = "lz4")]
+        Compression::Lz4 => {
+            let mut lz4_encoder = lz4_flex::frame::FrameEncoder::new(writer);
+            encode_body(data, &mut lz4_encoder)?;
+            lz4_encoder
+                .finish()
+                .map_err(|e| format!("Failed to finish lz4 encoder: {}", e))?
+                .flush()
+                .map_err(flush_err)?;
+        }
+
+        #[allow(unreachable_patterns)]
+        _ => unreachable!(),
     }
 
     Ok(())
@@ -123,20 +343,49 @@ pub fn save_data_into_std_write<T: FileData, W: Write>(
 ///
 /// # Arguments
 ///
-/// - `data`: The data to be saved, which is either a [`PostFlopGame`] or a [`BunchingData`].
+/// - `data`: The data to be saved, which is a [`PostFlopGame`], a [`BunchingData`], or a
+///   [`LockSet`].
 /// - `memo`: A memo string to be saved with the data.
 /// - `path`: The path to the file to save.
-/// - `compression_level`: The zstd compression level to use. If `None`, no compression is used.
-///   `Some(level)` can only be specified if the `zstd` feature is enabled.
-pub fn save_data_to_file<T: FileData, P: AsRef<Path>>(
+/// - `compression`: The compression of the data (see [`Compression`]). `None` means no compression
+///   and `Some(level)` means zstd compression with the given level, which can only be specified
+///   if the `zstd` feature is enabled.
+pub fn save_data_to_file<'a, T: FileData, P: AsRef<Path>>(
     data: &T,
     memo: &str,
     path: P,
-    compression_level: Option<i32>,
+    compression: impl Into<Compression<'a>>,
 ) -> Result<(), String> {
     let file = File::create(path).map_err(|e| format!("Failed to create file: {}", e))?;
     let mut writer = BufWriter::new(file);
-    save_data_into_std_write(data, memo, &mut writer, compression_level)
+    save_data_into_std_write(data, memo, &mut writer, compression)
+}
+
+/// Trains a zstd dictionary from sample data.
+///
+/// The dictionary improves the compression ratio of many similar files, such as the solutions of
+/// the same tree configuration on different boards. The samples are encoded in memory, so a few
+/// representative samples are usually enough. The trained dictionary can be used with
+/// [`Compression::ZstdWithDictionary`], and the same dictionary must be passed to
+/// [`load_data_from_file_with_dictionary`] to load the data.
+///
+/// # Arguments
+///
+/// - `samples`: The sample data, which are [`PostFlopGame`]s, [`BunchingData`]s, or [`LockSet`]s.
+/// - `max_size`: The maximum size of the dictionary in bytes (e.g., 110 KiB).
+#[cfg(feature = "zstd")]
+pub fn train_zstd_dictionary<T: FileData>(
+    samples: &[&T],
+    max_size: usize,
+) -> Result<Vec<u8>, String> {
+    let samples = samples
+        .iter()
+        .map(|&data| bincode::encode_to_vec(data, bincode::config::standard()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to encode sample: {}", e))?;
+
+    zstd::dict::from_samples(&samples, max_size)
+        .map_err(|e| format!("Failed to train dictionary: {}", e))
 }
 
 fn decode_from_std_read<D: Decode, R: Read>(reader: &mut R, err_msg: &str) -> Result<D, String> {
@@ -144,6 +393,142 @@ fn decode_from_std_read<D: Decode, R: Read>(reader: &mut R, err_msg: &str) -> Re
         .map_err(|e| format!("{}: {}", err_msg, e))
 }
 
+fn read_header<R: Read>(reader: &mut R) -> Result<Header, String> {
+    let magic: u32 = decode_from_std_read(reader, "Failed to read magic number")?;
+    if magic != MAGIC {
+        return Err("Magic number is invalid".to_string());
+    }
+
+    let version: u8 = decode_from_std_read(reader, "Failed to read version number")?;
+    if version == 0 || version > VERSION {
+        return Err("Version number is invalid".to_string());
+    }
+
+    let compression_type: u8 = decode_from_std_read(reader, "Failed to read compression type")?;
+    let compression = match compression_type {
+        0 => FileCompression::None,
+        1 => FileCompression::Zstd,
+        2 => FileCompression::Lz4,
+        3 => FileCompression::ZstdWithDictionary,
+        _ => return Err("Compression type is invalid".to_string()),
+    };
+
+    if !compression.is_supported() {
+        return Err("Compression is not supported".to_string());
+    }
+
+    let data_type: u8 = decode_from_std_read(reader, "Failed to read data type")?;
+    let estimated_memory_usage = decode_from_std_read(reader, "Failed to read memory usage")?;
+    let memo = decode_from_std_read(reader, "Failed to read memo")?;
+
+    Ok(Header {
+        version,
+        compression,
+        data_type,
+        estimated_memory_usage,
+        memo,
+        has_checksum: version >= 2,
+    })
+}
+
+/// Wraps the reader of a body with the decoder of the given compression.
+#[cfg_attr(not(feature = "zstd"), allow(unused_variables))]
+fn decompress<'a, R: Read>(
+    reader: &'a mut R,
+    compression: FileCompression,
+    dictionary: Option<&[u8]>,
+) -> Result<Box<dyn Read + 'a>, String> {
+    match compression {
+        FileCompression::None => Ok(Box::new(reader)),
+
+        #[cfg(feature = "zstd")]
+        FileCompression::Zstd => zstd::stream::Decoder::new(reader)
+            .map(|decoder| Box::new(decoder) as Box<dyn Read>)
+            .map_err(|e| format!("Failed to create zstd decoder: {}", e)),
+
+        #[cfg(feature = "zstd")]
+        FileCompression::ZstdWithDictionary => {
+            let Some(dictionary) = dictionary else {
+                return Err("Dictionary is required to load the data".to_string());
+            };
+            zstd::stream::Decoder::with_dictionary(BufReader::new(reader), dictionary)
+                .map(|decoder| Box::new(decoder) as Box<dyn Read>)
+                .map_err(|e| format!("Failed to create zstd decoder: {}", e))
+        }
+
+        #[cfg(feature = "lz4")]
+        FileCompression::Lz4 => Ok(Box::new(lz4_flex::frame::FrameDecoder::new(reader))),
+
+        // rejected in `read_header`
+        #[allow(unreachable_patterns)]
+        _ => Err("Compression is not supported".to_string()),
+    }
+}
+
+fn decode_body<D: Decode, R: Read>(reader: &mut R, has_checksum: bool) -> Result<D, String> {
+    let mut checksum_reader = ChecksumReader::new(reader);
+    let result = bincode::decode_from_std_read(&mut checksum_reader, bincode::config::standard());
+
+    let is_truncated = match &result {
+        Err(DecodeError::UnexpectedEnd { .. }) => true,
+        Err(DecodeError::Io { inner, .. }) => inner.kind() == ErrorKind::UnexpectedEof,
+        _ => false,
+    };
+
+    if is_truncated {
+        return Err(CORRUPTED_TRUNCATED.to_string());
+    }
+
+    if !has_checksum {
+        return result.map_err(|e| format!("Failed to read data: {}", e));
+    }
+
+    // corrupted data may also fail to decode, so the rest of the body is checked in that case
+    let ChecksumReader { reader, crc } = checksum_reader;
+    let (expected, actual) = if result.is_err() {
+        read_to_trailer(reader, crc).map_err(|_| CORRUPTED_TRUNCATED.to_string())?
+    } else {
+        let mut buf = [0; 4];
+        reader
+            .read_exact(&mut buf)
+            .map_err(|_| CORRUPTED_TRUNCATED.to_string())?;
+        (u32::from_le_bytes(buf), crc.value())
+    };
+
+    if expected != actual {
+        return Err(CORRUPTED_CHECKSUM.to_string());
+    }
+
+    result.map_err(|e| format!("Failed to read data: {}", e))
+}
+
+/// Reads the rest of a body followed by its checksum, and returns the stored checksum and the
+/// checksum of the body. `crc` is the checksum of the part of the body that is already read.
+fn read_to_trailer<R: Read + ?Sized>(reader: &mut R, mut crc: Crc32) -> io::Result<(u32, u32)> {
+    let mut buf = vec![0; 1 << 16];
+    let mut pending = Vec::with_capacity(buf.len() + 4);
+
+    loop {
+        let len = match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        // the last 4 bytes read so far may be the checksum
+        pending.extend_from_slice(&buf[..len]);
+        let body_len = pending.len().saturating_sub(4);
+        crc.update(&pending[..body_len]);
+        pending.drain(..body_len);
+    }
+
+    match <[u8; 4]>::try_from(pending.as_slice()) {
+        Ok(trailer) => Ok((u32::from_le_bytes(trailer), crc.value())),
+        Err(_) => Err(ErrorKind::UnexpectedEof.into()),
+    }
+}
+
 /// Loads data from a standard reader.
 ///
 /// This function deserializes the data from the `reader`.
@@ -158,81 +543,678 @@ fn decode_from_std_read<D: Decode, R: Read>(reader: &mut R, err_msg: &str) -> Re
 ///
 /// # Returns
 ///
-/// A tuple of the deserialized data (either a [`PostFlopGame`] or a [`BunchingData`]) and the memo
-/// string.
+/// A tuple of the deserialized data (a [`PostFlopGame`], a [`BunchingData`], or a [`LockSet`])
+/// and the memo string. If the data is truncated or its checksum does not match, `Err` starting with
+/// [`CORRUPTED_ERROR_PREFIX`] is returned, which can be detected by [`is_corruption_error`].
 pub fn load_data_from_std_read<T: FileData, R: Read>(
     reader: &mut R,
     max_memory_usage: Option<u64>,
 ) -> Result<(T, String), String> {
-    let magic: u32 = decode_from_std_read(reader, "Failed to read magic number")?;
-    if magic != MAGIC {
-        return Err("Magic number is invalid".to_string());
+    load_data_from_std_read_with_dictionary(reader, max_memory_usage, None)
+}
+
+/// Loads data from a standard reader with a zstd dictionary.
+///
+/// This function is the same as [`load_data_from_std_read`], except that the data saved with
+/// [`Compression::ZstdWithDictionary`] can be loaded with the same `dictionary`. The dictionary is
+/// ignored if the data is not compressed with a dictionary.
+pub fn load_data_from_std_read_with_dictionary<T: FileData, R: Read>(
+    reader: &mut R,
+    max_memory_usage: Option<u64>,
+    dictionary: Option<&[u8]>,
+) -> Result<(T, String), String> {
+    let header = read_header(reader)?;
+    if header.data_type != T::data_type() as u8 {
+        return Err("Data type is invalid".to_string());
+    }
+
+    if let Some(max_memory_usage) = max_memory_usage {
+        if header.estimated_memory_usage > max_memory_usage {
+            return Err("Estimated memory usage is too large".to_string());
+        }
+    }
+
+    let mut reader = decompress(reader, header.compression, dictionary)?;
+    let data: T = decode_body(&mut reader, header.has_checksum)?;
+
+    Ok((data, header.memo))
+}
+
+/// Loads data from a file.
+///
+/// This function deserializes the data from a file specified by `path`.
+///
+/// # Arguments
+///
+/// - `path`: The path to the file to load.
+/// - `max_memory_usage`: The maximum memory usage allowed for the data (in bytes). If `None`, no
+///   limit is set. If the estimated memory usage exceeds this value, `Err` is returned.
+///
+/// # Returns
+///
+/// A tuple of the deserialized data (a [`PostFlopGame`], a [`BunchingData`], or a [`LockSet`])
+/// and the memo string. If the file is truncated or its checksum does not match, `Err` starting with
+/// [`CORRUPTED_ERROR_PREFIX`] is returned, which can be detected by [`is_corruption_error`].
+pub fn load_data_from_file<T: FileData, P: AsRef<Path>>(
+    path: P,
+    max_memory_usage: Option<u64>,
+) -> Result<(T, String), String> {
+    load_data_from_file_with_dictionary(path, max_memory_usage, None)
+}
+
+/// Loads data from a file with a zstd dictionary.
+///
+/// This function is the same as [`load_data_from_file`], except that the data saved with
+/// [`Compression::ZstdWithDictionary`] can be loaded with the same `dictionary`. The dictionary is
+/// ignored if the data is not compressed with a dictionary.
+///
+/// # Examples
+/// ```no_run
+/// use postflop_solver::*;
+///
+/// let dictionary = std::fs::read("library/flop.dict").unwrap();
+/// let (game, _memo): (PostFlopGame, _) =
+///     load_data_from_file_with_dictionary("library/Td9d6h.flop", None, Some(&dictionary)).unwrap();
+/// ```
+pub fn load_data_from_file_with_dictionary<T: FileData, P: AsRef<Path>>(
+    path: P,
+    max_memory_usage: Option<u64>,
+    dictionary: Option<&[u8]>,
+) -> Result<(T, String), String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut reader = BufReader::new(file);
+    load_data_from_std_read_with_dictionary(&mut reader, max_memory_usage, dictionary)
+}
+
+/// Reads the metadata from a standard reader.
+///
+/// This function reads the header and the beginning of the body, so it is much cheaper than
+/// [`load_data_from_std_read`]. If you want to read the metadata from a file, use
+/// [`read_file_metadata`] instead.
+///
+/// # Arguments
+///
+/// - `reader`: The reader to read the metadata from.
+///
+/// # Returns
+///
+/// The metadata of the saved data. The card and tree configurations are also read if the data is
+/// a game that is not compressed with a dictionary.
+pub fn read_metadata_from_std_read<R: Read>(reader: &mut R) -> Result<FileMetadata, String> {
+    let header = read_header(reader)?;
+    let compression = header.compression;
+
+    let data_type = match header.data_type {
+        0 => DataType::Game,
+        1 => DataType::Bunching,
+        2 => DataType::IndexedGame,
+        3 => DataType::LockSet,
+        _ => return Err("Data type is invalid".to_string()),
+    };
+
+    // the body compressed with a dictionary cannot be read without the dictionary
+    let (game_version, configs) = match (data_type, compression) {
+        (DataType::Bunching | DataType::LockSet, _) | (_, FileCompression::ZstdWithDictionary) => {
+            (None, None)
+        }
+        _ => {
+            let mut reader = decompress(reader, compression, None)?;
+            let (version, configs) = PostFlopGame::read_configs(&mut reader)?;
+            (Some(version), configs)
+        }
+    };
+
+    let (card_config, tree_config) = configs.unzip();
+
+    Ok(FileMetadata {
+        version: header.version,
+        compression,
+        data_type,
+        estimated_memory_usage: header.estimated_memory_usage,
+        memo: header.memo,
+        game_version,
+        card_config,
+        tree_config,
+    })
+}
+
+/// Reads the metadata from a file.
+///
+/// This function reads the header and the beginning of the body of a file specified by `path`
+/// without loading the data. This is useful for listing the saved games with their boards, pots,
+/// and stacks.
+///
+/// # Arguments
+///
+/// - `path`: The path to the file to read.
+///
+/// # Returns
+///
+/// The metadata of the saved data. The card and tree configurations are also read if the data is
+/// a game.
+///
+/// # Examples
+/// ```no_run
+/// use postflop_solver::*;
+///
+/// let metadata = read_file_metadata("library/Td9d6h.flop").unwrap();
+/// let tree_config = metadata.tree_config.unwrap();
+/// println!("pot: {}, stack: {}", tree_config.starting_pot, tree_config.effective_stack);
+/// ```
+pub fn read_file_metadata<P: AsRef<Path>>(path: P) -> Result<FileMetadata, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut reader = BufReader::new(file);
+    read_metadata_from_std_read(&mut reader)
+}
+
+/// Migrates a saved file to the current format.
+///
+/// This function loads the data from `src`, which may have been saved by an older version of this
+/// library, and saves it into `dst` with the current format, including the checksum of the body.
+/// The memo, the data type, and the saved streets are preserved. Compressed data is recompressed
+/// with the same compression type (zstd with the default level). Data compressed with a dictionary
+/// is not supported. `src` and `dst` can be the same path.
+///
+/// # Examples
+/// ```no_run
+/// use postflop_solver::*;
+///
+/// migrate_file("archive/Td9d6h.flop", "archive/Td9d6h.flop").unwrap();
+/// ```
+pub fn migrate_file<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> Result<(), String> {
+    let metadata = read_file_metadata(&src)?;
+    let compression = match metadata.compression {
+        FileCompression::None => Compression::None,
+        FileCompression::Zstd => Compression::Zstd(0),
+        FileCompression::Lz4 => Compression::Lz4,
+        FileCompression::ZstdWithDictionary => {
+            return Err("Migration of data compressed with a dictionary is not supported".into());
+        }
+    };
+
+    match metadata.data_type {
+        DataType::Game => {
+            let (game, memo): (PostFlopGame, _) = load_data_from_file(src, None)?;
+            save_data_to_file(&game, &memo, dst, compression)
+        }
+        DataType::Bunching => {
+            let (bunching, memo): (BunchingData, _) = load_data_from_file(src, None)?;
+            save_data_to_file(&bunching, &memo, dst, compression)
+        }
+        DataType::LockSet => {
+            let (lock_set, memo): (LockSet, _) = load_data_from_file(src, None)?;
+            save_data_to_file(&lock_set, &memo, dst, compression)
+        }
+        DataType::IndexedGame => {
+            let mut file = IndexedGameFile::open(src)?;
+            let game = file.load(file.storage_mode())?;
+            let memo = file.memo().to_string();
+            drop(file);
+            save_indexed_game_to_file(&game, &memo, dst)
+        }
+    }
+}
+
+/// Verifies the checksum of the data in a standard reader without decoding it.
+///
+/// This function reads the whole body (decompressing it if needed) and compares its checksum with
+/// the stored one. If you want to verify a file, use [`verify_file`] instead.
+///
+/// # Returns
+///
+/// `Ok(())` if the checksum matches. If the data is truncated or its checksum does not match,
+/// `Err` detected by [`is_corruption_error`] is returned. Data saved by older versions of this
+/// library has no checksum and cannot be verified.
+pub fn verify_data_from_std_read<R: Read>(reader: &mut R) -> Result<(), String> {
+    verify_data_from_std_read_with_dictionary(reader, None)
+}
+
+/// Verifies the checksum of the data in a standard reader with a zstd dictionary.
+///
+/// This function is the same as [`verify_data_from_std_read`], except that the data saved with
+/// [`Compression::ZstdWithDictionary`] can be verified with the same `dictionary`.
+pub fn verify_data_from_std_read_with_dictionary<R: Read>(
+    reader: &mut R,
+    dictionary: Option<&[u8]>,
+) -> Result<(), String> {
+    let header = read_header(reader)?;
+    if !header.has_checksum {
+        return Err("Checksum is not available".to_string());
+    }
+
+    let hash_all = |reader: &mut dyn Read| read_to_trailer(reader, Crc32::default());
+
+    let err = |e: io::Error, msg: &str| match e.kind() {
+        ErrorKind::UnexpectedEof => CORRUPTED_TRUNCATED.to_string(),
+        _ => format!("{}: {}", msg, e),
+    };
+
+    let (expected, actual) = if header.compression == FileCompression::None {
+        hash_all(reader).map_err(|e| err(e, "Failed to read data"))?
+    } else {
+        let mut reader = decompress(reader, header.compression, dictionary)?;
+        hash_all(&mut reader).map_err(|e| err(e, CORRUPTED_ERROR_PREFIX))?
+    };
+
+    if actual != expected {
+        return Err(CORRUPTED_CHECKSUM.to_string());
+    }
+
+    Ok(())
+}
+
+/// Verifies the checksum of a saved file without decoding the data.
+///
+/// This function is much cheaper than loading the data, and it is useful for checking the files
+/// copied over a network. Street-indexed files saved by [`save_indexed_game_to_file`] are also
+/// supported.
+///
+/// # Returns
+///
+/// `Ok(())` if the checksum matches. If the file is truncated or its checksum does not match,
+/// `Err` detected by [`is_corruption_error`] is returned. Files saved by older versions of this
+/// library have no checksum and cannot be verified; use [`migrate_file`] to add one.
+///
+/// # Examples
+/// ```no_run
+/// use postflop_solver::*;
+///
+/// if let Err(e) = verify_file("library/Td9d6h.flop") {
+///     eprintln!("{}", e);
+/// }
+/// ```
+pub fn verify_file<P: AsRef<Path>>(path: P) -> Result<(), String> {
+    verify_file_with_dictionary(path, None)
+}
+
+/// Verifies the checksum of a saved file with a zstd dictionary.
+///
+/// This function is the same as [`verify_file`], except that the files saved with
+/// [`Compression::ZstdWithDictionary`] can be verified with the same `dictionary`.
+pub fn verify_file_with_dictionary<P: AsRef<Path>>(
+    path: P,
+    dictionary: Option<&[u8]>,
+) -> Result<(), String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut reader = BufReader::new(file);
+    verify_data_from_std_read_with_dictionary(&mut reader, dictionary)
+}
+
+/// Saves a game into a file that can be loaded subtree by subtree.
+///
+/// The file contains a section for each street and for each subtree dealt by a chance node, and an
+/// index of the section offsets, so that [`IndexedGameFile`] can load the flop part of the game
+/// without reading the rest of the file and page in the subtree of each turn and river card on
+/// demand. The sections are stored up to the [`storage_mode`] of the `game`.
+///
+/// Every [`CompressionMode`] of the `game` is supported. The file itself is not compressed so that
+/// the sections can be read directly; use [`save_data_to_file`] for a compressed file.
+///
+/// If the file already exists, it will be overwritten.
+///
+/// [`storage_mode`]: PostFlopGame::storage_mode
+pub fn save_indexed_game_to_file<P: AsRef<Path>>(
+    game: &PostFlopGame,
+    memo: &str,
+    path: P,
+) -> Result<(), String> {
+    if !game.is_ready_to_save() {
+        return Err("Data is not ready to save".to_string());
+    }
+
+    let file = File::create(path).map_err(|e| format!("Failed to create file: {}", e))?;
+    let mut writer = BufWriter::new(file);
+    let err = |e| format!("Failed to write file: {}", e);
+
+    let header = Header {
+        version: VERSION,
+        compression: FileCompression::None,
+        data_type: DataType::IndexedGame as u8,
+        estimated_memory_usage: game.memory_usage_until(game.storage_mode()),
+        memo: memo.to_string(),
+        has_checksum: true,
+    };
+
+    write_header(&header, &mut writer)?;
+    let header_len = writer.stream_position().map_err(err)?;
+
+    let mut body_writer = ChecksumWriter::new(&mut writer);
+    let mut offsets = [0u64; 3];
+    let mut subtree_offsets: [Vec<u64>; 3] = Default::default();
+    for street in STREETS {
+        if game.tree_config().initial_state <= street && street <= game.storage_mode() {
+            offsets[street as usize] = header_len + body_writer.len();
+            game.save_street_section(&mut body_writer, street)?;
+            for subtree in game.subtrees(street) {
+                subtree_offsets[street as usize].push(header_len + body_writer.len());
+                game.save_subtree_section(&mut body_writer, &subtree)?;
+            }
+        }
+    }
+
+    let index_offset = header_len + body_writer.len();
+    let index = (offsets, subtree_offsets);
+    encode_into_std_write(index, &mut body_writer, "Failed to write index")?;
+    body_writer
+        .write_all(&index_offset.to_le_bytes())
+        .map_err(err)?;
+
+    let checksum = body_writer.checksum();
+    writer.write_all(&checksum.to_le_bytes()).map_err(err)?;
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to flush writer: {}", e))?;
+
+    Ok(())
+}
+
+/// A file saved by [`save_indexed_game_to_file`], opened for partial loading.
+///
+/// Opening the file only reads the header and the index. The game is loaded up to the requested
+/// street with [`load`]. When a chance node is reached, the subtree of the dealt card can be paged
+/// in with [`load_subtree`], which reads the nodes of the next street (without their storages)
+/// and the storages of that subtree only; the whole street can also be paged in with
+/// [`load_street`]. The checksum is not verified when loading since only a part of the file is
+/// read; use [`verify_file`] to verify the whole file.
+///
+/// [`load`]: IndexedGameFile::load
+/// [`load_subtree`]: IndexedGameFile::load_subtree
+/// [`load_street`]: IndexedGameFile::load_street
+///
+/// # Examples
+/// ```no_run
+/// use postflop_solver::*;
+///
+/// let mut file = IndexedGameFile::open("library/Td9d6h.pfi").unwrap();
+/// let mut game = file.load(BoardState::Flop).unwrap();
+///
+/// // show the flop strategy, and then page in the turn card that is dealt after check-check
+/// game.apply_history(&[0, 0]);
+/// let turn = card_from_str("Qc").unwrap();
+/// file.load_subtree(&mut game, turn as usize).unwrap();
+/// game.play(turn as usize);
+/// ```
+pub struct IndexedGameFile {
+    reader: BufReader<File>,
+    memo: String,
+    estimated_memory_usage: u64,
+    offsets: [u64; 3],
+    subtree_offsets: [Vec<u64>; 3],
+}
+
+impl IndexedGameFile {
+    /// Opens the file and reads the header and the index.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+        let mut reader = BufReader::new(file);
+        let err = |e| format!("Failed to read file: {}", e);
+
+        let header = read_header(&mut reader)?;
+        if header.data_type != DataType::IndexedGame as u8 {
+            return Err("Data type is invalid".to_string());
+        }
+
+        // the index offset is followed by the checksum since version 2
+        let index_end = if header.has_checksum { -4 } else { 0 };
+
+        let mut buf = [0; 8];
+        reader.seek(SeekFrom::End(index_end - 8)).map_err(err)?;
+        reader.read_exact(&mut buf).map_err(err)?;
+        reader
+            .seek(SeekFrom::Start(u64::from_le_bytes(buf)))
+            .map_err(err)?;
+
+        let (offsets, subtree_offsets): ([u64; 3], [Vec<u64>; 3]) =
+            decode_from_std_read(&mut reader, "Failed to read index")?;
+        if offsets.iter().all(|&offset| offset == 0) {
+            return Err("Index is invalid".to_string());
+        }
+
+        Ok(Self {
+            reader,
+            memo: header.memo,
+            estimated_memory_usage: header.estimated_memory_usage,
+            offsets,
+            subtree_offsets,
+        })
+    }
+
+    /// Returns the memo string saved with the game.
+    #[inline]
+    pub fn memo(&self) -> &str {
+        &self.memo
+    }
+
+    /// Returns the estimated memory usage when all the stored streets are loaded (in bytes).
+    #[inline]
+    pub fn estimated_memory_usage(&self) -> u64 {
+        self.estimated_memory_usage
+    }
+
+    /// Returns the deepest street stored in the file.
+    #[inline]
+    pub fn storage_mode(&self) -> BoardState {
+        let index = self
+            .offsets
+            .iter()
+            .rposition(|&offset| offset != 0)
+            .unwrap();
+        STREETS[index]
+    }
+
+    /// Loads the game up to the given street.
+    ///
+    /// The [`storage_mode`] of the returned game is `street`, or the initial street of the game if
+    /// `street` is earlier than that.
+    ///
+    /// [`storage_mode`]: PostFlopGame::storage_mode
+    pub fn load(&mut self, street: BoardState) -> Result<PostFlopGame, String> {
+        let initial = self.offsets.iter().position(|&offset| offset != 0).unwrap();
+        self.seek_section(STREETS[initial])?;
+
+        let mut game: PostFlopGame = decode_from_std_read(&mut self.reader, "Failed to read data")?;
+        self.load_street(&mut game, street)?;
+
+        Ok(game)
+    }
+
+    /// Pages in the streets of `game` up to the given street.
+    ///
+    /// Each street is read as a whole, including the subtrees of all its cards that are not loaded
+    /// yet. `game` must be loaded from this file. Does nothing if the street is already loaded.
+    pub fn load_street(
+        &mut self,
+        game: &mut PostFlopGame,
+        street: BoardState,
+    ) -> Result<(), String> {
+        if street > self.storage_mode() {
+            return Err(format!("Street is not stored in the file: {street:?}"));
+        }
+
+        for next in STREETS {
+            if game.storage_mode() < next && next <= street {
+                self.load_street_nodes(game, next)?;
+
+                let roots = game.subtree_roots(next);
+                let offsets = self.subtree_offsets(next, roots.len())?;
+
+                let mut sections = Vec::new();
+                for (root, offset) in roots.into_iter().zip(offsets) {
+                    if !game.is_subtree_loaded(root) {
+                        self.seek(offset)?;
+                        let bytes = PostFlopGame::read_subtree_section(&mut self.reader)?;
+                        sections.push((root, bytes));
+                    }
+                }
+
+                game.load_subtrees(sections)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Pages in the subtree of `game` reached by dealing `card` at the current chance node.
+    ///
+    /// Only the nodes of the next street (without their storages) and the storages of the subtree
+    /// are read, so that the dealt card can be played and its street can be shown. The cards are
+    /// specified in the same way as [`PostFlopGame::play`], and the subtree of an isomorphic card
+    /// is shared with its representative card. `game` must be loaded from this file. Does nothing
+    /// if the subtree is already loaded.
+    pub fn load_subtree(&mut self, game: &mut PostFlopGame, card: usize) -> Result<(), String> {
+        if !game.is_chance_node() {
+            return Err("Current node is not a chance node".to_string());
+        }
+
+        let street = match game.current_board().len() {
+            3 => BoardState::Turn,
+            _ => BoardState::River,
+        };
+
+        if street > self.storage_mode() {
+            return Err(format!("Street is not stored in the file: {street:?}"));
+        }
+
+        self.load_street_nodes(game, street)?;
+        let Some(root) = game.chance_child_index(card) else {
+            return Err(format!("Card cannot be dealt: {card}"));
+        };
+
+        if game.is_subtree_loaded(root) {
+            return Ok(());
+        }
+
+        let roots = game.subtree_roots(street);
+        let offsets = self.subtree_offsets(street, roots.len())?;
+        let index = roots.iter().position(|&r| r == root).unwrap();
+
+        self.seek(offsets[index])?;
+        let bytes = PostFlopGame::read_subtree_section(&mut self.reader)?;
+        game.load_subtrees(vec![(root, bytes)])
+    }
+
+    /// Appends the nodes of the given street to `game` if they are not loaded yet.
+    fn load_street_nodes(
+        &mut self,
+        game: &mut PostFlopGame,
+        street: BoardState,
+    ) -> Result<(), String> {
+        if !game.has_street_nodes(street) {
+            self.seek_section(street)?;
+            game.load_street_section(&mut self.reader, street)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the offsets of the subtree sections of the given street, checking that the game has
+    /// the same number of subtrees.
+    fn subtree_offsets(&self, street: BoardState, num_subtrees: usize) -> Result<Vec<u64>, String> {
+        let offsets = &self.subtree_offsets[street as usize];
+        if offsets.len() != num_subtrees {
+            return Err("Index does not match the game".to_string());
+        }
+        Ok(offsets.clone())
+    }
+
+    /// Moves the reader to the section of the given street.
+    fn seek_section(&mut self, street: BoardState) -> Result<(), String> {
+        match self.offsets[street as usize] {
+            0 => Err(format!("Street is not stored in the file: {street:?}")),
+            offset => self.seek(offset),
+        }
+    }
+
+    /// Moves the reader to the given offset.
+    fn seek(&mut self, offset: u64) -> Result<(), String> {
+        self.reader
+            .seek(SeekFrom::Start(offset))
+            .map(|_| ())
+            .map_err(|e| format!("Failed to read file: {}", e))
+    }
+}
+
+/// Incremental CRC-32 (IEEE 802.3) checksum.
+#[derive(Default, Clone)]
+struct Crc32(crc32fast::Hasher);
+
+impl Crc32 {
+    #[inline]
+    fn update(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
     }
 
-    let version: u8 = decode_from_std_read(reader, "Failed to read version number")?;
-    if version != VERSION {
-        return Err("Version number is invalid".to_string());
+    #[inline]
+    fn value(&self) -> u32 {
+        self.0.clone().finalize()
     }
+}
 
-    let compression_type: u8 = decode_from_std_read(reader, "Failed to read compression type")?;
-    if compression_type > 1 {
-        return Err("Compression type is invalid".to_string());
+/// A writer that computes the checksum of the written bytes.
+struct ChecksumWriter<W> {
+    writer: W,
+    crc: Crc32,
+    len: u64,
+}
+
+impl<W: Write> ChecksumWriter<W> {
+    #[inline]
+    fn new(writer: W) -> Self {
+        Self {
+            writer,
+            crc: Crc32::default(),
+            len: 0,
+        }
     }
 
-    #[cfg(not(feature = "zstd"))]
-    if compression_type == 1 {
-        return Err("Compression is not supported".to_string());
+    #[inline]
+    fn checksum(&self) -> u32 {
+        self.crc.value()
     }
 
-    let data_type: u8 = decode_from_std_read(reader, "Failed to read data type")?;
-    if data_type != T::data_type() as u8 {
-        return Err("Data type is invalid".to_string());
+    #[inline]
+    fn len(&self) -> u64 {
+        self.len
     }
+}
 
-    let estimated_memory_usage: u64 = decode_from_std_read(reader, "Failed to read memory usage")?;
-    if let Some(max_memory_usage) = max_memory_usage {
-        if estimated_memory_usage > max_memory_usage {
-            return Err("Estimated memory usage is too large".to_string());
-        }
+impl<W: Write> Write for ChecksumWriter<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.writer.write(buf)?;
+        self.crc.update(&buf[..len]);
+        self.len += len as u64;
+        Ok(len)
     }
 
-    let memo: String = decode_from_std_read(reader, "Failed to read memo")?;
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
 
-    #[cfg(not(feature = "zstd"))]
-    let data: T = decode_from_std_read(reader, "Failed to read data")?;
-    #[cfg(feature = "zstd")]
-    let data: T = if compression_type == 0 {
-        decode_from_std_read(reader, "Failed to read data")?
-    } else {
-        let mut zstd_decoder = zstd::stream::Decoder::new(reader)
-            .map_err(|e| format!("Failed to create zstd decoder: {}", e))?;
-        decode_from_std_read(&mut zstd_decoder, "Failed to read data")?
-    };
+/// A reader that computes the checksum of the read bytes.
+struct ChecksumReader<R> {
+    reader: R,
+    crc: Crc32,
+}
 
-    Ok((data, memo))
+impl<R: Read> ChecksumReader<R> {
+    #[inline]
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            crc: Crc32::default(),
+        }
+    }
 }
 
-/// Loads data from a file.
-///
-/// This function deserializes the data from a file specified by `path`.
-///
-/// # Arguments
-///
-/// - `path`: The path to the file to load.
-/// - `max_memory_usage`: The maximum memory usage allowed for the data (in bytes). If `None`, no
-///   limit is set. If the estimated memory usage exceeds this value, `Err` is returned.
-///
-/// # Returns
-///
-/// A tuple of the deserialized data (either a [`PostFlopGame`] or a [`BunchingData`]) and the memo
-/// string.
-pub fn load_data_from_file<T: FileData, P: AsRef<Path>>(
-    path: P,
-    max_memory_usage: Option<u64>,
-) -> Result<(T, String), String> {
-    let file = File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
-    let mut reader = BufReader::new(file);
-    load_data_from_std_read(&mut reader, max_memory_usage)
+impl<R: Read> Read for ChecksumReader<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.reader.read(buf)?;
+        self.crc.update(&buf[..len]);
+        Ok(len)
+    }
 }
 
 impl FileData for PostFlopGame {
@@ -263,12 +1245,32 @@ impl FileData for BunchingData {
     }
 }
 
+impl FileData for LockSet {
+    fn data_type() -> DataType {
+        DataType::LockSet
+    }
+
+    fn is_ready_to_save(&self) -> bool {
+        true
+    }
+
+    fn estimated_memory_usage(&self) -> u64 {
+        let entry_size = |entry: &LockEntry| {
+            let num_actions = entry.actions.len();
+            let hand_size = 4 + num_actions * std::mem::size_of::<f32>();
+            (entry.line.len() + num_actions) * std::mem::size_of::<Action>()
+                + entry.strategy.len() * hand_size
+                + entry.frequencies.as_ref().map_or(0, |f| f.len() * 4)
+        };
+        self.entries.iter().map(entry_size).sum::<usize>() as u64
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::action_tree::*;
-    use crate::card::*;
     use crate::range::*;
+    use crate::solver::*;
     use crate::utility::*;
 
     #[test]
@@ -330,6 +1332,370 @@ mod tests {
         assert!((root_ev_ip - 15.0).abs() < 1e-4);
     }
 
+    #[test]
+    fn save_and_load_file_quantized() {
+        let card_config = CardConfig {
+            range: ["QQ+,AK".parse().unwrap(), "TT+,AQ+".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            starting_pot: 60,
+            effective_stack: 970,
+            flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+        game.allocate_memory(CompressionMode::Int8);
+        solve(&mut game, 10, 0.0, false);
+
+        game.cache_normalized_weights();
+        let weights_oop = game.normalized_weights(0);
+        let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
+
+        // save and load
+        save_data_to_file(&game, "", "tmpfile-int8.flop", None).unwrap();
+        let mut game2: PostFlopGame = load_data_from_file("tmpfile-int8.flop", None).unwrap().0;
+        assert_eq!(game2.memory_usage(), game.memory_usage());
+        assert_eq!(
+            game2.allocated_memory_usage(),
+            game.allocated_memory_usage()
+        );
+
+        // save and load (turn)
+        game2.set_target_storage_mode(BoardState::Turn).unwrap();
+        save_data_to_file(&game2, "", "tmpfile-int8.flop", None).unwrap();
+        let mut game3: PostFlopGame = load_data_from_file("tmpfile-int8.flop", None).unwrap().0;
+
+        // remove tmpfile
+        std::fs::remove_file("tmpfile-int8.flop").unwrap();
+
+        for game in [&mut game2, &mut game3] {
+            game.cache_normalized_weights();
+            let weights_oop = game.normalized_weights(0);
+            let ev_oop = compute_average(&game.expected_values(0), weights_oop);
+            assert!((ev_oop - root_ev_oop).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn save_and_load_file_indexed() {
+        let card_config = CardConfig {
+            range: ["QQ+,AK".parse().unwrap(), "TT+,AQ+".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            starting_pot: 60,
+            effective_stack: 970,
+            flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let history = [0, 0, usize::MAX, 0, 0, usize::MAX];
+        let ev_oop = |game: &mut PostFlopGame, history: &[usize]| {
+            game.apply_history(history);
+            game.cache_normalized_weights();
+            let weights_oop = game.normalized_weights(0);
+            compute_average(&game.expected_values(0), weights_oop)
+        };
+
+        let modes = [
+            CompressionMode::Disabled,
+            CompressionMode::Int16,
+            CompressionMode::Int8,
+        ];
+
+        for mode in modes {
+            let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+            let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+
+            game.allocate_memory(mode);
+            solve(&mut game, 10, 0.0, false);
+
+            let root_ev_oop = ev_oop(&mut game, &[]);
+            let turn_ev_oop = ev_oop(&mut game, &history[..3]);
+            let river_ev_oop = ev_oop(&mut game, &history);
+
+            // save
+            save_indexed_game_to_file(&game, "memo", "tmpfile-indexed.flop").unwrap();
+
+            // load (flop)
+            let mut file = IndexedGameFile::open("tmpfile-indexed.flop").unwrap();
+            assert_eq!(file.memo(), "memo");
+            assert_eq!(file.storage_mode(), BoardState::River);
+            assert_eq!(file.estimated_memory_usage(), game.target_memory_usage());
+
+            let mut game2 = file.load(BoardState::Flop).unwrap();
+            assert_eq!(game2.storage_mode(), BoardState::Flop);
+            assert!((ev_oop(&mut game2, &[]) - root_ev_oop).abs() < 1e-4);
+
+            // a truncated street section leaves the game unchanged
+            let len = (file.subtree_offsets[1][0] - file.offsets[1]) as usize;
+            let mut section = vec![0; len];
+            file.seek_section(BoardState::Turn).unwrap();
+            file.reader.read_exact(&mut section).unwrap();
+            for len in [section.len() / 2, section.len() - 1] {
+                let mut reader = &section[..len];
+                assert!(game2
+                    .load_street_section(&mut reader, BoardState::Turn)
+                    .is_err());
+                assert!(!game2.has_street_nodes(BoardState::Turn));
+            }
+
+            // page in the subtree of a single turn card
+            game2.apply_history(&history[..2]);
+            file.load_subtree(&mut game2, usize::MAX).unwrap();
+            assert!(game2.has_street_nodes(BoardState::Turn));
+            assert_eq!(game2.storage_mode(), BoardState::Flop);
+
+            let turn = game2.chance_child_index(usize::MAX).unwrap();
+            let roots = game2.subtree_roots(BoardState::Turn);
+            let loaded = roots.iter().filter(|&&root| game2.is_subtree_loaded(root));
+            assert_eq!(loaded.collect::<Vec<_>>(), vec![&turn]);
+
+            // the isomorphic card shares the subtree of its representative card
+            let ace_clubs = card_from_str("Ac").unwrap() as usize;
+            let ace_spades = card_from_str("As").unwrap() as usize;
+            let ace = game2.chance_child_index(ace_clubs).unwrap();
+            assert!(!game2.is_subtree_loaded(ace));
+            file.load_subtree(&mut game2, ace_spades).unwrap();
+            assert!(game2.is_subtree_loaded(ace));
+            assert_eq!(game2.chance_child_index(ace_spades), Some(ace));
+
+            // a broken subtree section leaves the game unchanged
+            let index = roots.iter().position(|&root| root == turn + 1).unwrap();
+            file.seek(file.subtree_offsets[1][index]).unwrap();
+            let mut bytes = PostFlopGame::read_subtree_section(&mut file.reader).unwrap();
+            bytes[0].pop();
+            assert!(game2.load_subtrees(vec![(turn + 1, bytes)]).is_err());
+            assert!(!game2.is_subtree_loaded(turn + 1));
+
+            // page in the subtree of a single river card
+            assert!((ev_oop(&mut game2, &history[..3]) - turn_ev_oop).abs() < 1e-4);
+            game2.apply_history(&history[..5]);
+            file.load_subtree(&mut game2, usize::MAX).unwrap();
+            assert!((ev_oop(&mut game2, &history) - river_ev_oop).abs() < 1e-4);
+            assert!((ev_oop(&mut game2, &[]) - root_ev_oop).abs() < 1e-4);
+            assert_eq!(game2.storage_mode(), BoardState::Flop);
+
+            // page in the rest of the turn and river
+            file.load_street(&mut game2, BoardState::Turn).unwrap();
+            assert_eq!(game2.storage_mode(), BoardState::Turn);
+            file.load_street(&mut game2, BoardState::River).unwrap();
+            assert_eq!(game2.storage_mode(), BoardState::River);
+            assert_eq!(game2.memory_usage(), game.memory_usage());
+            assert_eq!(game2.target_memory_usage(), game.target_memory_usage());
+            assert!((ev_oop(&mut game2, &[]) - root_ev_oop).abs() < 1e-4);
+            assert!((ev_oop(&mut game2, &history) - river_ev_oop).abs() < 1e-4);
+
+            // the paged-in game is saved in the same way as the original game
+            save_indexed_game_to_file(&game2, "memo", "tmpfile-indexed2.flop").unwrap();
+            let bytes = std::fs::read("tmpfile-indexed.flop").unwrap();
+            let bytes2 = std::fs::read("tmpfile-indexed2.flop").unwrap();
+            assert!(bytes == bytes2);
+
+            // load (river)
+            let mut game3 = file.load(BoardState::River).unwrap();
+            assert_eq!(game3.storage_mode(), BoardState::River);
+            assert!((ev_oop(&mut game3, &history) - river_ev_oop).abs() < 1e-4);
+        }
+
+        // not an indexed file
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        finalize(&mut game);
+        save_data_to_file(&game, "", "tmpfile-indexed.flop", None).unwrap();
+        assert!(IndexedGameFile::open("tmpfile-indexed.flop").is_err());
+
+        // remove tmpfile
+        std::fs::remove_file("tmpfile-indexed.flop").unwrap();
+        std::fs::remove_file("tmpfile-indexed2.flop").unwrap();
+    }
+
+    #[test]
+    fn read_metadata() {
+        let card_config = CardConfig {
+            range: [Range::ones(); 2],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("Qc").unwrap(),
+            river: card_from_str("2s").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 60,
+            effective_stack: 970,
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+        game.allocate_memory(false);
+        finalize(&mut game);
+
+        save_data_to_file(&game, "memo", "tmpfile-metadata.flop", None).unwrap();
+        let metadata = read_file_metadata("tmpfile-metadata.flop").unwrap();
+        assert_eq!(metadata.compression, FileCompression::None);
+        assert_eq!(metadata.data_type, DataType::Game);
+        assert_eq!(metadata.estimated_memory_usage, game.target_memory_usage());
+        assert_eq!(metadata.memo, "memo");
+        let card_config = metadata.card_config.unwrap();
+        let tree_config = metadata.tree_config.unwrap();
+        assert_eq!(card_config.flop, game.card_config().flop);
+        assert_eq!(card_config.river, game.card_config().river);
+        assert_eq!(tree_config.starting_pot, 60);
+        assert_eq!(tree_config.effective_stack, 970);
+
+        save_indexed_game_to_file(&game, "", "tmpfile-metadata.flop").unwrap();
+        let metadata = read_file_metadata("tmpfile-metadata.flop").unwrap();
+        assert_eq!(metadata.data_type, DataType::IndexedGame);
+        assert_eq!(metadata.tree_config.unwrap().starting_pot, 60);
+
+        #[cfg(feature = "zstd")]
+        {
+            save_data_to_file(&game, "", "tmpfile-metadata.flop", Some(3)).unwrap();
+            let metadata = read_file_metadata("tmpfile-metadata.flop").unwrap();
+            assert_eq!(metadata.compression, FileCompression::Zstd);
+            assert_eq!(metadata.tree_config.unwrap().starting_pot, 60);
+        }
+
+        // remove tmpfile
+        std::fs::remove_file("tmpfile-metadata.flop").unwrap();
+    }
+
+    #[test]
+    fn load_and_migrate_legacy_file() {
+        // solved with the 2023-03-19 layout: Td9d6hQc, turn and river bet 50% (OOP only on river)
+        let legacy_files = [
+            ("tests/data/legacy-2023-03-19-river.bin", BoardState::River),
+            ("tests/data/legacy-2023-03-19-turn.bin", BoardState::Turn),
+        ];
+
+        let ev_oop = |game: &mut PostFlopGame, history: &[usize]| {
+            game.apply_history(history);
+            game.cache_normalized_weights();
+            let weights_oop = game.normalized_weights(0);
+            compute_average(&game.expected_values(0), weights_oop)
+        };
+
+        for (path, storage_mode) in legacy_files {
+            let metadata = read_file_metadata(path).unwrap();
+            assert_eq!(metadata.memo, "legacy");
+            assert_eq!(metadata.game_version.unwrap(), "2023-03-19");
+            assert_eq!(
+                metadata.card_config.unwrap().turn,
+                card_from_str("Qc").unwrap()
+            );
+            assert_eq!(metadata.tree_config.unwrap().rake_cap, 30.0);
+
+            let (mut game, memo): (PostFlopGame, _) = load_data_from_file(path, None).unwrap();
+            assert_eq!(memo, "legacy");
+            assert_eq!(game.storage_mode(), storage_mode);
+            assert!((ev_oop(&mut game, &[]) - 31.651415).abs() < 1e-4);
+            if storage_mode == BoardState::River {
+                assert!((ev_oop(&mut game, &[0, 0, usize::MAX]) - 36.244717).abs() < 1e-4);
+            }
+
+            // migrate
+            migrate_file(path, "tmpfile-migrated.flop").unwrap();
+            let (mut game, memo): (PostFlopGame, _) =
+                load_data_from_file("tmpfile-migrated.flop", None).unwrap();
+            assert_eq!(memo, "legacy");
+            assert_eq!(game.storage_mode(), storage_mode);
+            assert!((ev_oop(&mut game, &[]) - 31.651415).abs() < 1e-4);
+
+            let metadata = read_file_metadata("tmpfile-migrated.flop").unwrap();
+            assert_eq!(metadata.game_version.unwrap(), "2026-10-18");
+            verify_file("tmpfile-migrated.flop").unwrap();
+        }
+
+        // remove tmpfile
+        std::fs::remove_file("tmpfile-migrated.flop").unwrap();
+    }
+
+    #[test]
+    fn detect_corrupted_data() {
+        let mut crc = Crc32::default();
+        crc.update(b"123456789");
+        assert_eq!(crc.value(), 0xcbf43926);
+
+        let card_config = CardConfig {
+            range: ["QQ+,AK".parse().unwrap(), "TT+,AQ+".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("Qc").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 60,
+            effective_stack: 970,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+        game.allocate_memory(false);
+        solve(&mut game, 5, 0.0, false);
+
+        let mut buf = Vec::new();
+        save_data_into_std_write(&game, "", &mut buf, None).unwrap();
+        verify_data_from_std_read(&mut buf.as_slice()).unwrap();
+        load_data_from_std_read::<PostFlopGame, _>(&mut buf.as_slice(), None).unwrap();
+
+        // flipped bits
+        for i in [buf.len() / 2, buf.len() - 1] {
+            let mut corrupted = buf.clone();
+            corrupted[i] ^= 0x10;
+            let err = verify_data_from_std_read(&mut corrupted.as_slice()).unwrap_err();
+            assert!(is_corruption_error(&err));
+            let result =
+                load_data_from_std_read::<PostFlopGame, _>(&mut corrupted.as_slice(), None);
+            assert!(is_corruption_error(&result.err().unwrap()));
+        }
+
+        // truncated
+        let truncated = &buf[..buf.len() - 100];
+        let err = verify_data_from_std_read(&mut &truncated[..]).unwrap_err();
+        assert!(is_corruption_error(&err));
+        let result = load_data_from_std_read::<PostFlopGame, _>(&mut &truncated[..], None);
+        assert!(is_corruption_error(&result.err().unwrap()));
+
+        // indexed file
+        save_indexed_game_to_file(&game, "", "tmpfile-checksum.flop").unwrap();
+        verify_file("tmpfile-checksum.flop").unwrap();
+        let mut indexed = std::fs::read("tmpfile-checksum.flop").unwrap();
+        let len = indexed.len();
+        indexed[len / 2] ^= 0x10;
+        std::fs::write("tmpfile-checksum.flop", indexed).unwrap();
+        let err = verify_file("tmpfile-checksum.flop").unwrap_err();
+        assert!(is_corruption_error(&err));
+
+        // remove tmpfile
+        std::fs::remove_file("tmpfile-checksum.flop").unwrap();
+
+        // no checksum in version 1
+        let err = verify_file("tests/data/legacy-2023-03-19-turn.bin").unwrap_err();
+        assert!(!is_corruption_error(&err));
+        let err = verify_file("tmpfile-nonexistent.flop").unwrap_err();
+        assert!(!is_corruption_error(&err));
+    }
+
     #[test]
     #[cfg(feature = "zstd")]
     fn save_and_load_file_compressed() {
@@ -375,4 +1741,143 @@ mod tests {
         assert!((root_ev_oop - 45.0).abs() < 1e-4);
         assert!((root_ev_ip - 15.0).abs() < 1e-4);
     }
+
+    #[test]
+    fn save_and_load_lock_set() {
+        let lock_set = LockSet {
+            entries: vec![LockEntry {
+                line: vec![Action::Check, Action::Chance(card_from_str("2c").unwrap())],
+                actions: vec![Action::Check, Action::Bet(50)],
+                strategy: vec![("AsKs".to_string(), vec![0.25, 0.75])],
+                frequencies: Some(vec![0.5, 0.5]),
+            }],
+        };
+
+        // save
+        save_data_to_file(&lock_set, "profile", "tmpfile-lock.flop", None).unwrap();
+        let metadata = read_file_metadata("tmpfile-lock.flop").unwrap();
+        assert_eq!(metadata.data_type, DataType::LockSet);
+        assert!(metadata.tree_config.is_none());
+
+        // load
+        let result = load_data_from_file::<LockSet, _>("tmpfile-lock.flop", None);
+
+        // remove tmpfile
+        std::fs::remove_file("tmpfile-lock.flop").unwrap();
+
+        assert_eq!(result.unwrap(), (lock_set, "profile".to_string()));
+    }
+
+    #[test]
+    #[cfg(feature = "lz4")]
+    fn save_and_load_file_lz4() {
+        let card_config = CardConfig {
+            range: [Range::ones(); 2],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            starting_pot: 60,
+            effective_stack: 970,
+            flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+        game.allocate_memory(false);
+        finalize(&mut game);
+
+        // save
+        save_data_to_file(&game, "", "tmpfile-lz4.flop", Compression::Lz4).unwrap();
+        let metadata = read_file_metadata("tmpfile-lz4.flop").unwrap();
+        assert_eq!(metadata.compression, FileCompression::Lz4);
+        assert!(metadata.tree_config.is_some());
+        verify_file("tmpfile-lz4.flop").unwrap();
+
+        // load
+        let mut game: PostFlopGame = load_data_from_file("tmpfile-lz4.flop", None).unwrap().0;
+
+        // remove tmpfile
+        std::fs::remove_file("tmpfile-lz4.flop").unwrap();
+
+        game.cache_normalized_weights();
+        let weights_oop = game.normalized_weights(0);
+        let weights_ip = game.normalized_weights(1);
+        let root_ev_oop = compute_average(&game.expected_values(0), weights_oop);
+        let root_ev_ip = compute_average(&game.expected_values(1), weights_ip);
+
+        assert!((root_ev_oop - 45.0).abs() < 1e-4);
+        assert!((root_ev_ip - 15.0).abs() < 1e-4);
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn save_and_load_file_zstd_dictionary() {
+        let build = |flop: &str| {
+            let card_config = CardConfig {
+                range: [
+                    "AA,KK,QQ,AKs".parse().unwrap(),
+                    "JJ,TT,AQs".parse().unwrap(),
+                ],
+                flop: flop_from_str(flop).unwrap(),
+                ..Default::default()
+            };
+
+            let tree_config = TreeConfig {
+                starting_pot: 60,
+                effective_stack: 970,
+                flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+                turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+                ..Default::default()
+            };
+
+            let action_tree = ActionTree::new(tree_config).unwrap();
+            let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+            game.allocate_memory(false);
+            finalize(&mut game);
+            game
+        };
+
+        let flops = [
+            "Td9d6h", "8c7c2d", "Ks5h3s", "Jh8d4c", "9s6s5d", "Ac4h2c", "7d5c3h", "Qd6c2s",
+        ];
+        let samples = flops.map(build);
+        let dictionary = train_zstd_dictionary(&samples.iter().collect::<Vec<_>>(), 16384).unwrap();
+
+        // save
+        let game = build("Th8h3c");
+        let compression = Compression::ZstdWithDictionary(3, &dictionary);
+        save_data_to_file(&game, "memo", "tmpfile-dict.flop", compression).unwrap();
+
+        // the dictionary is required to read the body
+        let metadata = read_file_metadata("tmpfile-dict.flop").unwrap();
+        assert_eq!(metadata.compression, FileCompression::ZstdWithDictionary);
+        assert_eq!(metadata.memo, "memo");
+        assert!(metadata.tree_config.is_none());
+        assert!(load_data_from_file::<PostFlopGame, _>("tmpfile-dict.flop", None).is_err());
+        assert!(verify_file("tmpfile-dict.flop").is_err());
+        verify_file_with_dictionary("tmpfile-dict.flop", Some(&dictionary)).unwrap();
+
+        // load
+        let (mut loaded, memo): (PostFlopGame, _) =
+            load_data_from_file_with_dictionary("tmpfile-dict.flop", None, Some(&dictionary))
+                .unwrap();
+
+        // remove tmpfile
+        std::fs::remove_file("tmpfile-dict.flop").unwrap();
+
+        assert_eq!(memo, "memo");
+        assert_eq!(loaded.card_config().flop, game.card_config().flop);
+
+        let mut game = game;
+        game.cache_normalized_weights();
+        loaded.cache_normalized_weights();
+        let ev = compute_average(&game.expected_values(0), game.normalized_weights(0));
+        let loaded_ev = compute_average(&loaded.expected_values(0), loaded.normalized_weights(0));
+        assert!((ev - loaded_ev).abs() < 1e-6);
+    }
 }
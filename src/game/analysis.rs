@@ -0,0 +1,1017 @@
+use super::memory_plan::rebuild_action_tree;
+use super::*;
+use crate::interface::*;
+use crate::range::*;
+use crate::solver::*;
+use crate::utility::*;
+
+/// A proposal for simplifying the bet sizes of a solved game.
+///
+/// See [`PostFlopGame::simplify_bet_sizes`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BetSizeAdvice {
+    /// EV of each player (OOP, IP) in the original game.
+    pub ev: [f32; 2],
+
+    /// Lines removed in the proposed tree, which can be passed to [`ActionTree::remove_line`].
+    pub removed_lines: Vec<Vec<Action>>,
+
+    /// EV of each player in the proposed tree.
+    pub simplified_ev: [f32; 2],
+
+    /// Candidates evaluated individually, in ascending order of `ev_loss`.
+    pub candidates: Vec<BetSizeCandidate>,
+}
+
+/// A candidate bet size to be removed, a part of [`BetSizeAdvice`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BetSizeCandidate {
+    /// Line ending with the bet, raise, or all-in action to be removed, in the format of
+    /// [`ActionTree::remove_line`] (i.e., chance actions are omitted).
+    pub line: Vec<Action>,
+
+    /// Player taking the action (0 for OOP, 1 for IP).
+    pub player: usize,
+
+    /// EV lost by `player` when only this line is removed from the original tree.
+    pub ev_loss: f32,
+}
+
+/// A report of the EV gained by each player in a game with locked nodes, relative to an unlocked
+/// equilibrium, returned by [`PostFlopGame::exploit_report`].
+///
+/// Index 0 of each array is OOP and index 1 is IP. The root EVs are the same as
+/// [`compute_current_ev`], i.e., (starting pot) / 2 is subtracted.
+///
+/// [`compute_current_ev`]: crate::compute_current_ev
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExploitReport {
+    /// EV of each player in the locked game.
+    pub ev_locked: [f32; 2],
+
+    /// EV of each player in the baseline (unlocked) game.
+    pub ev_baseline: [f32; 2],
+
+    /// EV gained by each player, i.e., `ev_locked - ev_baseline`.
+    pub gain: [f32; 2],
+
+    /// EV each player could gain by maximally exploiting the opponent's strategy in the locked
+    /// game, i.e., the MES EV (see [`compute_mes_ev`]) minus `ev_baseline`.
+    ///
+    /// [`compute_mes_ev`]: crate::compute_mes_ev
+    pub max_gain: [f32; 2],
+
+    /// Breakdown by decision node, in depth-first order from the root node.
+    pub nodes: Vec<NodeGain>,
+}
+
+/// The EV gained at a decision node, a part of [`ExploitReport`].
+///
+/// The gain of a player at a node is the difference of the EVs between the locked game and the
+/// baseline game at the node, weighted by the probability of reaching the node in the locked game.
+/// Therefore, the gain at the root node equals [`ExploitReport::gain`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeGain {
+    /// Actions from the root node, including the dealt cards ([`Action::Chance`]).
+    pub line: Vec<Action>,
+
+    /// Player to act (0 for OOP, 1 for IP).
+    pub player: usize,
+
+    /// Whether the node is locked in the locked game.
+    pub is_locked: bool,
+
+    /// Probability of reaching the node in the locked game and the baseline game, respectively.
+    pub reach: [f32; 2],
+
+    /// Average EV of each player at the node in the locked game (the same as the average of
+    /// [`PostFlopGame::expected_values`]).
+    pub ev_locked: [f32; 2],
+
+    /// Average EV of each player at the node in the baseline game.
+    pub ev_baseline: [f32; 2],
+
+    /// EV gained by each player at the node.
+    pub gain: [f32; 2],
+
+    /// Breakdown of `gain` by the hand categories of each player on the board of the node, in
+    /// ascending order of the categories. The categories that no hand reaches are omitted.
+    pub categories: [Vec<(HandCategory, f32)>; 2],
+}
+
+/// A breakdown of the exploitability by decision node, returned by
+/// [`PostFlopGame::exploitability_report`].
+///
+/// The exploitability is attributed to the decision nodes as follows: the contribution of a node
+/// is the EV that the player to act would gain by switching to the best response only at the node
+/// (while playing the best response in the subsequent nodes), weighted by the reach probability of
+/// the player and divided by 2. The contributions of all nodes sum up to the exploitability.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExploitabilityReport {
+    /// Exploitability of the current strategy (see [`compute_exploitability`]).
+    ///
+    /// [`compute_exploitability`]: crate::compute_exploitability
+    pub exploitability: f32,
+
+    /// Sum of the contributions of the nodes on each street (flop, turn, river).
+    pub streets: [f32; 3],
+
+    /// Nodes with the largest contributions, in descending order.
+    pub nodes: Vec<NodeExploitability>,
+}
+
+/// The contribution of a decision node to the exploitability, a part of
+/// [`ExploitabilityReport`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeExploitability {
+    /// Actions from the root node, including the dealt cards ([`Action::Chance`]).
+    pub line: Vec<Action>,
+
+    /// Player to act (0 for OOP, 1 for IP).
+    pub player: usize,
+
+    /// Street of the node.
+    pub street: BoardState,
+
+    /// Probability of reaching the node.
+    pub reach: f32,
+
+    /// Contribution of the node to the exploitability.
+    pub exploitability: f32,
+}
+
+/// The difference between two solutions at a decision node, returned by [`diff_solutions`].
+///
+/// Index 0 of each array is the first game and index 1 is the second game. The actions are the
+/// union of the available actions of both games; an action that is not available in a game has
+/// the probability of zero in the game.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeDiff {
+    /// Actions from the root node, including the dealt cards ([`Action::Chance`]).
+    pub line: Vec<Action>,
+
+    /// Player to act (0 for OOP, 1 for IP).
+    pub player: usize,
+
+    /// Union of the available actions of both games.
+    pub actions: Vec<Action>,
+
+    /// Aggregate frequencies of `actions` in each game.
+    pub frequencies: [Vec<f32>; 2],
+
+    /// Average EV of each player (OOP, IP) at the node in each game (the same as the average of
+    /// [`PostFlopGame::expected_values`]).
+    pub ev: [[f32; 2]; 2],
+
+    /// Hands with the largest strategy changes, in descending order of the changes.
+    pub combos: Vec<ComboDiff>,
+}
+
+/// The strategy change of a hand, a part of [`NodeDiff`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ComboDiff {
+    /// Hand string (e.g., `"AsKs"`).
+    pub hand: String,
+
+    /// Probabilities of the actions of [`NodeDiff`] in each game.
+    pub strategy: [Vec<f32>; 2],
+
+    /// Total variation distance between the strategies (in range [`0.0`, `1.0`]).
+    pub change: f32,
+}
+
+impl PostFlopGame {
+    /// Reports the EV gained by each player in this game with locked nodes, relative to
+    /// `baseline`, which must be the same game solved without the locks.
+    ///
+    /// The report breaks the gain down by the decision nodes up to `max_street` (including every
+    /// runout) and by the hand categories of each player at each node. Note that the number of the
+    /// reported nodes grows quickly with `max_street`.
+    ///
+    /// Both games must be solved. The current nodes of both games are preserved.
+    ///
+    /// Returns `Err` if the games do not share the same ranges, board, and game tree.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use postflop_solver::*;
+    ///
+    /// let (mut baseline, _): (PostFlopGame, _) = load_data_from_file("base.flop", None).unwrap();
+    /// let (mut game, _): (PostFlopGame, _) = load_data_from_file("locked.flop", None).unwrap();
+    ///
+    /// let report = game.exploit_report(&mut baseline, BoardState::River).unwrap();
+    /// println!("EV gain of OOP: {}", report.gain[0]);
+    /// ```
+    pub fn exploit_report(
+        &mut self,
+        baseline: &mut PostFlopGame,
+        max_street: BoardState,
+    ) -> Result<ExploitReport, String> {
+        if self.state != State::Solved || baseline.state != State::Solved {
+            panic!("Game is not solved");
+        }
+
+        if self.private_cards != baseline.private_cards
+            || self.initial_weights != baseline.initial_weights
+            || self.card_config.flop != baseline.card_config.flop
+            || self.card_config.turn != baseline.card_config.turn
+            || self.card_config.river != baseline.card_config.river
+            || self.node_arena.len() != baseline.node_arena.len()
+        {
+            return Err("Games do not match".to_string());
+        }
+
+        let ev_locked = compute_current_ev(self);
+        let ev_baseline = compute_current_ev(baseline);
+        let mes_ev = compute_mes_ev(self);
+
+        let mut report = ExploitReport {
+            ev_locked,
+            ev_baseline,
+            gain: [0, 1].map(|player| ev_locked[player] - ev_baseline[player]),
+            max_gain: [0, 1].map(|player| mes_ev[player] - ev_baseline[player]),
+            nodes: Vec::new(),
+        };
+
+        let history = self.action_history.clone();
+        let baseline_history = baseline.action_history.clone();
+        self.back_to_root();
+        baseline.back_to_root();
+        self.cache_normalized_weights();
+
+        let root_weight_sum = [0, 1].map(|player| {
+            let weights = self.normalized_weights(player);
+            weights.iter().fold(0.0, |sum, &w| sum + w as f64)
+        });
+
+        let max_board_len = match max_street {
+            BoardState::Flop => 3,
+            BoardState::Turn => 4,
+            BoardState::River => 5,
+        };
+
+        let result = self.exploit_report_recursive(
+            baseline,
+            &mut Vec::new(),
+            max_board_len,
+            root_weight_sum,
+            &mut report.nodes,
+        );
+
+        self.apply_history(&history);
+        baseline.apply_history(&baseline_history);
+
+        result.map(|_| report)
+    }
+
+    /// Proposes a reduced set of bet sizes whose EV loss is within `tolerance`.
+    ///
+    /// Every bet, raise, and all-in action in the tree is a candidate for removal. First, each
+    /// candidate is evaluated by removing only its line from the tree, re-solving the game, and
+    /// measuring the EV lost by the player taking the action (compared with the EV of this game).
+    /// Then, the candidates are removed greedily in ascending order of the EV loss, re-solving the
+    /// game after each removal; a candidate is kept if removing it makes the EV loss of either
+    /// player exceed `tolerance`.
+    ///
+    /// The games are re-solved with [`solve`] using `max_num_iterations` and
+    /// `target_exploitability`, which should match the settings used for this game. Lines added
+    /// to or removed from the action tree are reapplied, but the lines removed by the
+    /// [`remove_lines`] method are not.
+    ///
+    /// This method solves the game (#(candidates) + #(candidates within `tolerance`)) times, so it
+    /// is only feasible for small trees.
+    ///
+    /// Panics if the game is not solved.
+    ///
+    /// [`solve`]: crate::solve
+    /// [`remove_lines`]: #method.remove_lines
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use postflop_solver::*;
+    ///
+    /// let (game, _): (PostFlopGame, _) = load_data_from_file("river.flop", None).unwrap();
+    ///
+    /// // remove the bet sizes that cost less than 0.5 chips in total
+    /// let advice = game.simplify_bet_sizes(0.5, 1000, 0.01).unwrap();
+    /// println!("removed lines: {:?}", advice.removed_lines);
+    /// ```
+    pub fn simplify_bet_sizes(
+        &self,
+        tolerance: f32,
+        max_num_iterations: u32,
+        target_exploitability: f32,
+    ) -> Result<BetSizeAdvice, String> {
+        if self.state != State::Solved {
+            panic!("Game is not solved");
+        }
+
+        let ev = compute_current_ev(self);
+        let action_tree = rebuild_action_tree(
+            self.tree_config.clone(),
+            &self.added_lines,
+            &self.removed_lines,
+        )?;
+
+        let mut lines = Vec::new();
+        collect_bet_lines(&action_tree.root(), &mut Vec::new(), &mut lines);
+
+        // solves the game with `removed_lines` removed from the tree
+        let compression = self.compression_mode();
+        let solve_without = |removed_lines: &[Vec<Action>]| -> Result<[f32; 2], String> {
+            let mut removed = self.removed_lines.clone();
+            removed.extend_from_slice(removed_lines);
+            let tree = rebuild_action_tree(self.tree_config.clone(), &self.added_lines, &removed)?;
+            let mut game = PostFlopGame::with_config(self.card_config.clone(), tree)?;
+            game.allocate_memory(compression);
+            solve(&mut game, max_num_iterations, target_exploitability, false);
+            Ok(compute_current_ev(&game))
+        };
+
+        let mut candidates = Vec::with_capacity(lines.len());
+        for (line, player) in lines {
+            let candidate_ev = solve_without(std::slice::from_ref(&line))?;
+            candidates.push(BetSizeCandidate {
+                line,
+                player,
+                ev_loss: ev[player] - candidate_ev[player],
+            });
+        }
+
+        candidates.sort_by(|a, b| a.ev_loss.total_cmp(&b.ev_loss));
+
+        let mut removed_lines = Vec::<Vec<Action>>::new();
+        let mut simplified_ev = ev;
+
+        for candidate in &candidates {
+            if candidate.ev_loss > tolerance {
+                break;
+            }
+
+            if removed_lines.iter().any(|l| candidate.line.starts_with(l)) {
+                continue;
+            }
+
+            removed_lines.push(candidate.line.clone());
+            let candidate_ev = solve_without(&removed_lines)?;
+            if (0..2).all(|player| ev[player] - candidate_ev[player] <= tolerance) {
+                simplified_ev = candidate_ev;
+            } else {
+                removed_lines.pop();
+            }
+        }
+
+        Ok(BetSizeAdvice {
+            ev,
+            removed_lines,
+            simplified_ev,
+            candidates,
+        })
+    }
+
+    /// Appends the gains at the current node and its descendants to `nodes`.
+    ///
+    /// `weight_sum` is the sum of the normalized weights at the root node multiplied by the
+    /// numbers of the possible cards dealt so far.
+    fn exploit_report_recursive(
+        &mut self,
+        baseline: &mut PostFlopGame,
+        line: &mut Vec<Action>,
+        max_board_len: usize,
+        weight_sum: [f64; 2],
+        nodes: &mut Vec<NodeGain>,
+    ) -> Result<(), String> {
+        if self.is_terminal_node() {
+            return Ok(());
+        }
+
+        let board = self.current_board();
+        let actions = self.available_actions();
+        if actions != baseline.available_actions() {
+            return Err("Games do not match".to_string());
+        }
+
+        let history = self.action_history.clone();
+        let baseline_history = baseline.action_history.clone();
+
+        if self.is_chance_node() {
+            if board.len() >= max_board_len {
+                return Ok(());
+            }
+
+            let chance_factor = self.num_possible_deals(board.len());
+            let weight_sum = weight_sum.map(|w| w * chance_factor as f64);
+
+            for card in 0..52 {
+                if self.possible_cards() & (1 << card) != 0 {
+                    self.play(card);
+                    baseline.play(card);
+                    line.push(Action::Chance(card as Card));
+                    self.exploit_report_recursive(
+                        baseline,
+                        line,
+                        max_board_len,
+                        weight_sum,
+                        nodes,
+                    )?;
+                    line.pop();
+                    self.apply_history(&history);
+                    baseline.apply_history(&baseline_history);
+                }
+            }
+
+            return Ok(());
+        }
+
+        self.cache_normalized_weights();
+        baseline.cache_normalized_weights();
+
+        let index = self.node_history.last().cloned().unwrap_or(0);
+        let mut node_gain = NodeGain {
+            line: line.clone(),
+            player: self.current_player(),
+            is_locked: self.node_arena[index].lock().is_locked
+                || self.locking_frequencies.contains_key(&index),
+            ..Default::default()
+        };
+
+        for (player, &weight_sum) in weight_sum.iter().enumerate() {
+            let weights_locked = self.normalized_weights(player);
+            let weights_baseline = baseline.normalized_weights(player);
+            let ev_locked = self.expected_values(player);
+            let ev_baseline = baseline.expected_values(player);
+
+            let mut reach = [0.0; 2];
+            let mut gain = 0.0;
+            let mut categories = BTreeMap::new();
+
+            for (hand, &cards) in self.private_cards[player].iter().enumerate() {
+                let w_locked = weights_locked[hand] as f64;
+                reach[0] += w_locked;
+                reach[1] += weights_baseline[hand] as f64;
+
+                if w_locked > 0.0 {
+                    let diff = (ev_locked[hand] - ev_baseline[hand]) as f64;
+                    let category = HandCategory::classify(cards, &board, self.card_config.variant);
+                    *categories.entry(category).or_insert(0.0) += w_locked * diff;
+                    gain += w_locked * diff;
+                }
+            }
+
+            node_gain.reach = [0, 1].map(|i| (reach[i] / weight_sum) as f32);
+            node_gain.ev_locked[player] = average(&ev_locked, weights_locked);
+            node_gain.ev_baseline[player] = average(&ev_baseline, weights_baseline);
+            node_gain.gain[player] = (gain / weight_sum) as f32;
+            node_gain.categories[player] = categories
+                .into_iter()
+                .map(|(category, gain)| (category, (gain / weight_sum) as f32))
+                .collect();
+        }
+
+        nodes.push(node_gain);
+
+        for (action_index, &action) in actions.iter().enumerate() {
+            self.play(action_index);
+            baseline.play(action_index);
+            line.push(action);
+            self.exploit_report_recursive(baseline, line, max_board_len, weight_sum, nodes)?;
+            line.pop();
+            self.apply_history(&history);
+            baseline.apply_history(&baseline_history);
+        }
+
+        Ok(())
+    }
+
+    /// Computes the best-response strategy of `player` against the opponent's current strategy
+    /// at every decision node of `player`, and returns it as a [`LockSet`].
+    ///
+    /// Each entry contains the strategy returned by the [`current_best_response`] method at the
+    /// node. Only one of the isomorphic runouts is included, as in the [`export_lock_set`] method.
+    /// The returned lock set can be imported into a game with the same configuration with the
+    /// [`import_lock_set`] method, and the best response can be navigated there with the
+    /// [`current_locking_strategy`] method.
+    ///
+    /// Panics if `player` is invalid or the memory is not yet allocated. The current node is
+    /// preserved.
+    ///
+    /// [`current_best_response`]: #method.current_best_response
+    /// [`export_lock_set`]: #method.export_lock_set
+    /// [`import_lock_set`]: #method.import_lock_set
+    /// [`current_locking_strategy`]: #method.current_locking_strategy
+    pub fn best_response_lock_set(&mut self, player: usize) -> LockSet {
+        if player > 1 {
+            panic!("Invalid player");
+        }
+
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        let history = self.action_history.clone();
+        self.back_to_root();
+
+        let mut lock_set = LockSet::default();
+        self.best_response_recursive(player, &mut Vec::new(), &mut lock_set);
+
+        self.apply_history(&history);
+        lock_set
+    }
+
+    /// Appends the best-response strategies of `player` under the current node to `lock_set`.
+    fn best_response_recursive(
+        &mut self,
+        player: usize,
+        line: &mut Vec<Action>,
+        lock_set: &mut LockSet,
+    ) {
+        if self.is_terminal_node() {
+            return;
+        }
+
+        let actions = self.available_actions();
+        let history = self.action_history.clone();
+
+        if !self.is_chance_node() && self.current_player() == player {
+            let board_mask = self
+                .current_board()
+                .iter()
+                .fold(0u64, |mask, &card| mask | (1 << card));
+            let private_cards = &self.private_cards[player];
+            let num_hands = private_cards.len();
+            let best_response = self.current_best_response();
+
+            let strategy = private_cards
+                .iter()
+                .enumerate()
+                .filter(|&(_, &(c1, c2))| board_mask & ((1 << c1) | (1 << c2)) == 0)
+                .map(|(hand, &cards)| {
+                    let strategy = (0..actions.len())
+                        .map(|action| best_response[action * num_hands + hand])
+                        .collect();
+                    (hole_to_string(cards).unwrap(), strategy)
+                })
+                .collect();
+
+            lock_set.entries.push(LockEntry {
+                line: line.clone(),
+                actions: actions.clone(),
+                strategy,
+                frequencies: None,
+            });
+        }
+
+        for (action_index, &action) in actions.iter().enumerate() {
+            match action {
+                Action::Chance(card) => self.play(card as usize),
+                _ => self.play(action_index),
+            }
+            line.push(action);
+            self.best_response_recursive(player, line, lock_set);
+            line.pop();
+            self.apply_history(&history);
+        }
+    }
+
+    /// Attributes the exploitability of the current strategy to the decision nodes and returns the
+    /// `top_n` nodes with the largest contributions (see [`ExploitabilityReport`]).
+    ///
+    /// This method visits every decision node under every runout and computes the best response
+    /// of the subtree at each node, so it is considerably slower than [`compute_exploitability`].
+    ///
+    /// Panics if the memory is not yet allocated. The current node is preserved.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use postflop_solver::*;
+    ///
+    /// let (mut game, _): (PostFlopGame, _) = load_data_from_file("river.flop", None).unwrap();
+    ///
+    /// let report = game.exploitability_report(3);
+    /// for node in &report.nodes {
+    ///     println!("{:?}: {}", node.line, node.exploitability);
+    /// }
+    /// ```
+    ///
+    /// [`compute_exploitability`]: crate::compute_exploitability
+    pub fn exploitability_report(&mut self, top_n: usize) -> ExploitabilityReport {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        let exploitability = compute_exploitability(self);
+
+        let history = self.action_history.clone();
+        self.back_to_root();
+        self.cache_normalized_weights();
+
+        let weights = self.normalized_weights(0);
+        let weight_sum = weights.iter().fold(0.0, |sum, &w| sum + w as f64);
+
+        let mut streets = [0.0; 3];
+        let mut nodes = Vec::new();
+        self.exploitability_report_recursive(
+            &mut Vec::new(),
+            1.0,
+            weight_sum,
+            top_n,
+            &mut streets,
+            &mut nodes,
+        );
+
+        self.apply_history(&history);
+
+        sort_by_exploitability(&mut nodes);
+        nodes.truncate(top_n);
+
+        ExploitabilityReport {
+            exploitability,
+            streets: streets.map(|x| x as f32),
+            nodes,
+        }
+    }
+
+    /// Adds the contributions of the current node and its descendants to `streets` and `nodes`.
+    ///
+    /// `chance_factor` is the product of the numbers of the possible cards dealt so far, and
+    /// `weight_sum` is the sum of the normalized weights at the root node.
+    fn exploitability_report_recursive(
+        &mut self,
+        line: &mut Vec<Action>,
+        chance_factor: f64,
+        weight_sum: f64,
+        top_n: usize,
+        streets: &mut [f64; 3],
+        nodes: &mut Vec<NodeExploitability>,
+    ) {
+        if self.is_terminal_node() {
+            return;
+        }
+
+        let board = self.current_board();
+        let actions = self.available_actions();
+        let history = self.action_history.clone();
+
+        if self.is_chance_node() {
+            let num_possible_deals = self.num_possible_deals(board.len());
+            let chance_factor = chance_factor * num_possible_deals as f64;
+
+            for card in 0..52 {
+                if self.possible_cards() & (1 << card) != 0 {
+                    self.play(card);
+                    line.push(Action::Chance(card as Card));
+                    self.exploitability_report_recursive(
+                        line,
+                        chance_factor,
+                        weight_sum,
+                        top_n,
+                        streets,
+                        nodes,
+                    );
+                    line.pop();
+                    self.apply_history(&history);
+                }
+            }
+
+            return;
+        }
+
+        let index = self.node_history.last().cloned().unwrap_or(0);
+        if actions.len() > 1 && !self.locking_frequencies.contains_key(&index) {
+            let player = self.current_player();
+            let num_hands = self.num_private_hands(player);
+            let cfv_actions = self.best_response_cfvalues();
+            let strategy = self.strategy();
+
+            let node = self.node_arena[index].lock();
+            let mut locking = self.locking_strategy(&node).to_vec();
+            locking.chunks_exact_mut(num_hands).for_each(|chunk| {
+                self.apply_swap(chunk, player, false);
+            });
+
+            let mut gain = 0.0;
+            for (hand, &weight) in self.weights[player].iter().enumerate() {
+                if weight == 0.0 || (!locking.is_empty() && locking[hand].is_sign_positive()) {
+                    continue;
+                }
+                let mut max_value = f64::NEG_INFINITY;
+                let mut value = 0.0;
+                for action in 0..actions.len() {
+                    let cfv = cfv_actions[action * num_hands + hand] as f64;
+                    max_value = max_value.max(cfv);
+                    value += strategy[action * num_hands + hand] as f64 * cfv;
+                }
+                gain += weight as f64 * (max_value - value);
+            }
+
+            let contribution = gain / chance_factor * 0.5;
+            let street = match board.len() {
+                3 => BoardState::Flop,
+                4 => BoardState::Turn,
+                _ => BoardState::River,
+            };
+            streets[street as usize] += contribution;
+
+            self.cache_normalized_weights();
+            let weights = self.normalized_weights(player);
+            let reach = weights.iter().fold(0.0, |sum, &w| sum + w as f64);
+
+            nodes.push(NodeExploitability {
+                line: line.clone(),
+                player,
+                street,
+                reach: (reach / (weight_sum * chance_factor)) as f32,
+                exploitability: contribution as f32,
+            });
+
+            // keep only the candidates for the top nodes
+            if nodes.len() >= top_n.saturating_mul(2).max(1024) {
+                sort_by_exploitability(nodes);
+                nodes.truncate(top_n);
+            }
+        }
+
+        for (action_index, &action) in actions.iter().enumerate() {
+            self.play(action_index);
+            line.push(action);
+            self.exploitability_report_recursive(
+                line,
+                chance_factor,
+                weight_sum,
+                top_n,
+                streets,
+                nodes,
+            );
+            line.pop();
+            self.apply_history(&history);
+        }
+    }
+}
+
+/// Sorts `nodes` in descending order of the contributions to the exploitability.
+#[inline]
+fn sort_by_exploitability(nodes: &mut [NodeExploitability]) {
+    nodes.sort_unstable_by(|a, b| b.exploitability.total_cmp(&a.exploitability));
+}
+
+/// Computes the average with given weights, or zero if all weights are zero.
+#[inline]
+fn average(slice: &[f32], weights: &[f32]) -> f32 {
+    if weights.iter().all(|&w| w == 0.0) {
+        0.0
+    } else {
+        compute_average(slice, weights)
+    }
+}
+
+/// Compares two solutions of the same spot and returns the differences at the decision nodes up
+/// to `max_street`, in depth-first order from the root node.
+///
+/// The games must have the same board, but may differ in the ranges, the bet sizes, the rake, and
+/// so on. Only the lines shared by both games are visited (including every runout). At each node,
+/// up to `max_combos` hands with the largest strategy changes are reported; the hands must be in
+/// the ranges of both games and reach the node in both games.
+///
+/// Both games must be solved. The current nodes of both games are preserved.
+///
+/// Returns `Err` if the boards of the games are different.
+///
+/// # Examples
+/// ```no_run
+/// use postflop_solver::*;
+///
+/// // what changes if OOP can bet 33% in addition to 50%?
+/// let (mut game_a, _): (PostFlopGame, _) = load_data_from_file("bet50.flop", None).unwrap();
+/// let (mut game_b, _): (PostFlopGame, _) = load_data_from_file("bet33-50.flop", None).unwrap();
+///
+/// let diffs = diff_solutions(&mut game_a, &mut game_b, BoardState::River, 5).unwrap();
+/// for diff in &diffs {
+///     println!("{:?}: {:?} -> {:?}", diff.line, diff.frequencies[0], diff.frequencies[1]);
+/// }
+/// ```
+pub fn diff_solutions(
+    game_a: &mut PostFlopGame,
+    game_b: &mut PostFlopGame,
+    max_street: BoardState,
+    max_combos: usize,
+) -> Result<Vec<NodeDiff>, String> {
+    if game_a.state != State::Solved || game_b.state != State::Solved {
+        panic!("Game is not solved");
+    }
+
+    if game_a.card_config.flop != game_b.card_config.flop
+        || game_a.card_config.turn != game_b.card_config.turn
+        || game_a.card_config.river != game_b.card_config.river
+    {
+        return Err("Boards do not match".to_string());
+    }
+
+    let max_board_len = match max_street {
+        BoardState::Flop => 3,
+        BoardState::Turn => 4,
+        BoardState::River => 5,
+    };
+
+    let mut games = [game_a, game_b];
+    let histories = games.each_ref().map(|game| game.action_history.clone());
+    games.iter_mut().for_each(|game| game.back_to_root());
+
+    let mut diffs = Vec::new();
+    diff_solutions_recursive(
+        &mut games,
+        &mut Vec::new(),
+        max_board_len,
+        max_combos,
+        &mut diffs,
+    );
+
+    for (game, history) in games.iter_mut().zip(histories.iter()) {
+        game.apply_history(history);
+    }
+
+    Ok(diffs)
+}
+
+/// Appends the differences at the current nodes and their descendants to `diffs`.
+fn diff_solutions_recursive(
+    games: &mut [&mut PostFlopGame; 2],
+    line: &mut Vec<Action>,
+    max_board_len: usize,
+    max_combos: usize,
+    diffs: &mut Vec<NodeDiff>,
+) {
+    if games.iter().any(|game| game.is_terminal_node()) {
+        return;
+    }
+
+    let histories = games.each_ref().map(|game| game.action_history.clone());
+    let restore = |games: &mut [&mut PostFlopGame; 2]| {
+        for (game, history) in games.iter_mut().zip(histories.iter()) {
+            game.apply_history(history);
+        }
+    };
+
+    let is_chance = games.each_ref().map(|game| game.is_chance_node());
+    if is_chance[0] != is_chance[1] {
+        return;
+    }
+
+    if is_chance[0] {
+        if games[0].current_board().len() >= max_board_len {
+            return;
+        }
+
+        let possible_cards = games[0].possible_cards() & games[1].possible_cards();
+        for card in 0..52 {
+            if possible_cards & (1 << card) != 0 {
+                games.iter_mut().for_each(|game| game.play(card));
+                line.push(Action::Chance(card as Card));
+                diff_solutions_recursive(games, line, max_board_len, max_combos, diffs);
+                line.pop();
+                restore(games);
+            }
+        }
+
+        return;
+    }
+
+    let player = games[0].current_player();
+    if player != games[1].current_player() {
+        return;
+    }
+
+    let game_actions = games.each_ref().map(|game| game.available_actions());
+    let mut actions = game_actions[0].clone();
+    for &action in &game_actions[1] {
+        if !actions.contains(&action) {
+            actions.push(action);
+        }
+    }
+
+    // strategies of the hands of each game, aligned with `actions`
+    games
+        .iter_mut()
+        .for_each(|game| game.cache_normalized_weights());
+    let strategies = [0, 1].map(|i| {
+        let game = &games[i];
+        let strategy = game.strategy();
+        let num_hands = game.private_cards[player].len();
+        (0..num_hands)
+            .map(|hand| {
+                actions
+                    .iter()
+                    .map(
+                        |action| match game_actions[i].iter().position(|a| a == action) {
+                            Some(index) => strategy[index * num_hands + hand],
+                            None => 0.0,
+                        },
+                    )
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let mut diff = NodeDiff {
+        line: line.clone(),
+        player,
+        actions: actions.clone(),
+        ..Default::default()
+    };
+
+    for i in 0..2 {
+        let weights = games[i].normalized_weights(player);
+        let weight_sum = weights.iter().fold(0.0, |sum, &w| sum + w as f64);
+        diff.frequencies[i] = (0..actions.len())
+            .map(|action| {
+                let sum = strategies[i]
+                    .iter()
+                    .zip(weights)
+                    .fold(0.0, |sum, (s, &w)| sum + s[action] as f64 * w as f64);
+                match weight_sum {
+                    0.0 => 0.0,
+                    _ => (sum / weight_sum) as f32,
+                }
+            })
+            .collect();
+        for p in 0..2 {
+            let ev = games[i].expected_values(p);
+            diff.ev[i][p] = average(&ev, games[i].normalized_weights(p));
+        }
+    }
+
+    // hands reaching the node in both games
+    let hand_index_b = games[1].private_cards[player]
+        .iter()
+        .enumerate()
+        .map(|(index, &cards)| (cards, index))
+        .collect::<BTreeMap<_, _>>();
+    let mut combos = games[0].private_cards[player]
+        .iter()
+        .enumerate()
+        .filter_map(|(hand_a, cards)| {
+            let hand_b = *hand_index_b.get(cards)?;
+            let reaches =
+                games[0].weights[player][hand_a] > 0.0 && games[1].weights[player][hand_b] > 0.0;
+            reaches.then(|| {
+                let strategy = [strategies[0][hand_a].clone(), strategies[1][hand_b].clone()];
+                let change = strategy[0]
+                    .iter()
+                    .zip(&strategy[1])
+                    .map(|(a, b)| (a - b).abs())
+                    .sum::<f32>()
+                    * 0.5;
+                ComboDiff {
+                    hand: hole_to_string(*cards).unwrap(),
+                    strategy,
+                    change,
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    combos.sort_by(|a, b| b.change.total_cmp(&a.change));
+    combos.truncate(max_combos);
+    diff.combos = combos;
+
+    diffs.push(diff);
+
+    for &action in &game_actions[0] {
+        if !game_actions[1].contains(&action) {
+            continue;
+        }
+        for (game, actions) in games.iter_mut().zip(game_actions.iter()) {
+            game.play(actions.iter().position(|&a| a == action).unwrap());
+        }
+        line.push(action);
+        diff_solutions_recursive(games, line, max_board_len, max_combos, diffs);
+        line.pop();
+        restore(games);
+    }
+}
+
+/// Collects the lines ending with the bet, raise, and all-in actions under `node`, paired with the
+/// players taking the actions.
+fn collect_bet_lines(
+    node: &ActionTreeNode,
+    line: &mut Vec<Action>,
+    lines: &mut Vec<(Vec<Action>, usize)>,
+) {
+    if node.is_terminal() {
+        return;
+    }
+
+    if node.is_chance() {
+        collect_bet_lines(&node.children[0].lock(), line, lines);
+        return;
+    }
+
+    for (action, child) in node.actions.iter().zip(node.children.iter()) {
+        line.push(*action);
+        if node.actions.len() > 1
+            && matches!(action, Action::Bet(_) | Action::Raise(_) | Action::AllIn(_))
+        {
+            lines.push((line.clone(), node.player as usize));
+        }
+        collect_bet_lines(&child.lock(), line, lines);
+        line.pop();
+    }
+}
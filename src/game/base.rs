@@ -3,6 +3,7 @@ use crate::bunching::*;
 use crate::interface::*;
 use crate::utility::*;
 use std::mem::{self, MaybeUninit};
+use std::ops::Range;
 
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
@@ -21,7 +22,7 @@ impl Game for PostFlopGame {
     type Node = PostFlopNode;
 
     #[inline]
-    fn root(&self) -> MutexGuardLike<Self::Node> {
+    fn root(&self) -> MutexGuardLike<'_, Self::Node> {
         self.node_arena[0].lock()
     }
 
@@ -52,11 +53,8 @@ impl Game for PostFlopGame {
 
     #[inline]
     fn chance_factor(&self, node: &Self::Node) -> usize {
-        if node.turn == NOT_DEALT {
-            45 - self.bunching_num_dead_cards
-        } else {
-            44 - self.bunching_num_dead_cards
-        }
+        let board_len = if node.turn == NOT_DEALT { 3 } else { 4 };
+        self.num_possible_deals(board_len)
     }
 
     #[inline]
@@ -78,7 +76,10 @@ impl Game for PostFlopGame {
 
     #[inline]
     fn is_raked(&self) -> bool {
-        self.tree_config.rake_rate > 0.0 && self.tree_config.rake_cap > 0.0
+        match &self.tree_config.rake_model {
+            Some(rake_model) => !rake_model.is_zero(),
+            None => self.tree_config.rake_rate > 0.0 && self.tree_config.rake_cap > 0.0,
+        }
     }
 
     #[inline]
@@ -104,9 +105,24 @@ impl Game for PostFlopGame {
     fn locking_strategy(&self, node: &Self::Node) -> &[f32] {
         if !node.is_locked {
             &[]
+        } else {
+            // the node may be locked only by its aggregate frequencies
+            let index = self.node_index(node);
+            match self.locking_strategy.get(&index) {
+                Some(locking) => locking,
+                None => &[],
+            }
+        }
+    }
+
+    #[inline]
+    fn locking_frequencies(&self, node: &Self::Node) -> Option<(&[f32], &MutexLike<Vec<f32>>)> {
+        if self.locking_frequencies.is_empty() {
+            None
         } else {
             let index = self.node_index(node);
-            self.locking_strategy.get(&index).unwrap()
+            let (frequencies, multipliers) = self.locking_frequencies.get(&index)?;
+            Some((frequencies, multipliers))
         }
     }
 
@@ -114,6 +130,11 @@ impl Game for PostFlopGame {
     fn is_compression_enabled(&self) -> bool {
         self.is_compression_enabled
     }
+
+    #[inline]
+    fn is_quantization_enabled(&self) -> bool {
+        self.is_quantization_enabled
+    }
 }
 
 impl PostFlopGame {
@@ -187,6 +208,10 @@ impl PostFlopGame {
             return Err("Bunching configuration is not ready".to_string());
         }
 
+        if self.card_config.variant != GameVariant::Holdem {
+            return Err("Bunching effect is only supported for Texas hold'em".to_string());
+        }
+
         let mut flop_sorted = self.card_config.flop;
         flop_sorted.sort_unstable();
         if flop_sorted != bunching_data.flop() {
@@ -255,17 +280,33 @@ impl PostFlopGame {
     }
 
     /// Returns the estimated memory usage in bytes (uncompressed, compressed).
+    ///
+    /// Use [`memory_usage_quantized`] to get the memory usage with [`CompressionMode::Int8`], and
+    /// [`allocated_memory_usage`] to get the actual footprint after allocating the memory.
+    ///
+    /// [`memory_usage_quantized`]: #method.memory_usage_quantized
+    /// [`allocated_memory_usage`]: #method.allocated_memory_usage
     #[inline]
     pub fn memory_usage(&self) -> (u64, u64) {
         if self.state <= State::Uninitialized {
             panic!("Game is not successfully initialized");
         }
 
-        let num_elements = 2 * self.num_storage + self.num_storage_ip + self.num_storage_chance;
-        let uncompressed = 4 * num_elements + self.misc_memory_usage;
-        let compressed = 2 * num_elements + self.misc_memory_usage;
+        (
+            self.memory_usage_with(CompressionMode::Disabled),
+            self.memory_usage_with(CompressionMode::Int16),
+        )
+    }
 
-        (uncompressed, compressed)
+    /// Returns the estimated memory usage in bytes with 8-bit quantization
+    /// ([`CompressionMode::Int8`]).
+    #[inline]
+    pub fn memory_usage_quantized(&self) -> u64 {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
+        }
+
+        self.memory_usage_with(CompressionMode::Int8)
     }
 
     /// Returns the estimated additional memory usage in bytes when the bunching effect is enabled.
@@ -314,43 +355,61 @@ impl PostFlopGame {
     }
 
     /// Allocates the memory.
-    pub fn allocate_memory(&mut self, enable_compression: bool) {
-        if self.state <= State::Uninitialized {
-            panic!("Game is not successfully initialized");
+    ///
+    /// `compression` accepts either a [`CompressionMode`] or a `bool` (`true` means
+    /// [`CompressionMode::Int16`]).
+    pub fn allocate_memory(&mut self, compression: impl Into<CompressionMode>) {
+        if let Err(e) = self.allocate_memory_internal(compression.into(), None) {
+            panic!("{e}");
         }
+    }
 
-        if self.state == State::MemoryAllocated
-            && self.storage_mode == BoardState::River
-            && self.is_compression_enabled == enable_compression
-        {
-            return;
-        }
+    /// Allocates the memory backed by memory-mapped temporary files created in `dir`.
+    ///
+    /// The OS pages the storage in and out as needed, so trees larger than the physical memory can
+    /// be solved at the cost of slower iterations. The temporary files are removed when the
+    /// storage is released (e.g., when the game is dropped). Use [`allocated_memory_usage`] to
+    /// check the resident and on-disk footprint.
+    ///
+    /// [`allocated_memory_usage`]: #method.allocated_memory_usage
+    #[cfg(feature = "mmap")]
+    pub fn allocate_memory_on_disk(
+        &mut self,
+        compression: impl Into<CompressionMode>,
+        dir: impl AsRef<std::path::Path>,
+    ) -> Result<(), String> {
+        self.allocate_memory_internal(compression.into(), Some(dir.as_ref()))
+    }
 
-        let num_bytes = if enable_compression { 2 } else { 4 };
-        if num_bytes * self.num_storage > isize::MAX as u64
-            || num_bytes * self.num_storage_chance > isize::MAX as u64
-        {
-            panic!("Memory usage exceeds maximum size");
+    /// Returns the memory usage of the allocated storage in bytes (resident, on disk).
+    ///
+    /// The resident part includes the estimated memory usage other than the storage. If the memory
+    /// is allocated by [`allocate_memory_on_disk`], the storage is counted as on disk, although
+    /// the OS may keep some of its pages in memory.
+    ///
+    /// [`allocate_memory_on_disk`]: #method.allocate_memory_on_disk
+    pub fn allocated_memory_usage(&self) -> (u64, u64) {
+        if self.state <= State::Uninitialized {
+            panic!("Game is not successfully initialized");
         }
 
-        self.state = State::MemoryAllocated;
-        self.is_compression_enabled = enable_compression;
+        let mut resident = self.misc_memory_usage;
+        let mut disk = 0;
 
-        self.clear_storage();
-
-        let storage_bytes = (num_bytes * self.num_storage) as usize;
-        let storage_ip_bytes = (num_bytes * self.num_storage_ip) as usize;
-        let storage_chance_bytes = (num_bytes * self.num_storage_chance) as usize;
-
-        self.storage1 = vec![0; storage_bytes];
-        self.storage2 = vec![0; storage_bytes];
-        self.storage_ip = vec![0; storage_ip_bytes];
-        self.storage_chance = vec![0; storage_chance_bytes];
-
-        self.allocate_memory_nodes();
+        for storage in [
+            &self.storage1,
+            &self.storage2,
+            &self.storage_ip,
+            &self.storage_chance,
+        ] {
+            if storage.is_on_disk() {
+                disk += storage.len() as u64;
+            } else {
+                resident += storage.len() as u64;
+            }
+        }
 
-        self.storage_mode = BoardState::River;
-        self.target_storage_mode = BoardState::River;
+        (resident, disk)
     }
 
     /// Checks the card configuration.
@@ -371,6 +430,16 @@ impl PostFlopGame {
             return Err(format!("Flop cards must be unique: flop = {flop:?}"));
         }
 
+        let removed_mask = config.variant.removed_cards_mask();
+        for card in [flop[0], flop[1], flop[2], turn, river] {
+            if card != NOT_DEALT && card < 52 && (1 << card) & removed_mask != 0 {
+                return Err(format!(
+                    "Board card is not in the deck of {:?}: card = {card}",
+                    config.variant
+                ));
+            }
+        }
+
         if turn != NOT_DEALT {
             if 52 <= turn {
                 return Err(format!("Turn card must be in [0, 52): turn = {turn}"));
@@ -469,7 +538,9 @@ impl PostFlopGame {
         let (flop, turn, river) = (config.flop, config.turn, config.river);
         let range = &config.range;
 
+        // hands containing removed cards are excluded as well
         let mut board_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
+        board_mask |= config.variant.removed_cards_mask();
         if turn != NOT_DEALT {
             board_mask |= 1 << turn;
         }
@@ -575,48 +646,185 @@ impl PostFlopGame {
         self.cfvalues_cache = vecs;
     }
 
+    /// Allocates the memory, on disk if `dir` is specified.
+    fn allocate_memory_internal(
+        &mut self,
+        compression: CompressionMode,
+        dir: Option<&std::path::Path>,
+    ) -> Result<(), String> {
+        if self.state <= State::Uninitialized {
+            return Err("Game is not successfully initialized".to_string());
+        }
+
+        if self.state == State::MemoryAllocated
+            && self.storage_mode == BoardState::River
+            && self.compression_mode() == compression
+            && self.is_storage_on_disk() == dir.is_some()
+        {
+            return Ok(());
+        }
+
+        let num_bytes = compression.num_bytes();
+        if num_bytes[1] * self.num_storage > isize::MAX as u64
+            || num_bytes[3] * self.num_storage_chance > isize::MAX as u64
+        {
+            return Err("Memory usage exceeds maximum size".to_string());
+        }
+
+        // node pointers are invalidated until the new storage is allocated
+        self.state = State::TreeBuilt;
+        self.clear_storage();
+
+        let storage1_bytes = (num_bytes[0] * self.num_storage) as usize;
+        let storage2_bytes = (num_bytes[1] * self.num_storage) as usize;
+        let storage_ip_bytes = (num_bytes[2] * self.num_storage_ip) as usize;
+        let storage_chance_bytes = (num_bytes[3] * self.num_storage_chance) as usize;
+
+        match dir {
+            None => {
+                self.storage1 = Storage::zeroed(storage1_bytes);
+                self.storage2 = Storage::zeroed(storage2_bytes);
+                self.storage_ip = Storage::zeroed(storage_ip_bytes);
+                self.storage_chance = Storage::zeroed(storage_chance_bytes);
+            }
+
+            #[cfg(feature = "mmap")]
+            Some(dir) => {
+                let alloc = |len| {
+                    Storage::zeroed_on_disk(len, dir)
+                        .map_err(|e| format!("Failed to map storage file: {e}"))
+                };
+                self.storage1 = alloc(storage1_bytes)?;
+                self.storage2 = alloc(storage2_bytes)?;
+                self.storage_ip = alloc(storage_ip_bytes)?;
+                self.storage_chance = alloc(storage_chance_bytes)?;
+            }
+
+            #[cfg(not(feature = "mmap"))]
+            Some(_) => unreachable!(),
+        }
+
+        self.state = State::MemoryAllocated;
+        self.is_compression_enabled = compression != CompressionMode::Disabled;
+        self.is_quantization_enabled = compression == CompressionMode::Int8;
+
+        self.allocate_memory_nodes();
+
+        self.storage_mode = BoardState::River;
+        self.target_storage_mode = BoardState::River;
+
+        Ok(())
+    }
+
+    /// Returns the number of the cards that can be dealt next to the board of `board_len` cards,
+    /// excluding the hole cards of both players and the dead cards of the bunching effect.
+    #[inline]
+    pub(super) fn num_possible_deals(&self, board_len: usize) -> usize {
+        self.card_config.variant.num_cards() - board_len - 4 - self.bunching_num_dead_cards
+    }
+
+    /// Returns the compression mode of the storage.
+    #[inline]
+    pub(super) fn compression_mode(&self) -> CompressionMode {
+        match (self.is_compression_enabled, self.is_quantization_enabled) {
+            (false, _) => CompressionMode::Disabled,
+            (true, false) => CompressionMode::Int16,
+            (true, true) => CompressionMode::Int8,
+        }
+    }
+
+    /// Returns whether the storage is backed by memory-mapped files.
+    #[inline]
+    fn is_storage_on_disk(&self) -> bool {
+        self.storage1.is_on_disk() || self.storage_chance.is_on_disk()
+    }
+
     /// Clears the storage.
     #[inline]
     fn clear_storage(&mut self) {
-        self.storage1 = Vec::new();
-        self.storage2 = Vec::new();
-        self.storage_ip = Vec::new();
-        self.storage_chance = Vec::new();
+        self.storage1 = Storage::default();
+        self.storage2 = Storage::default();
+        self.storage_ip = Storage::default();
+        self.storage_chance = Storage::default();
+    }
+
+    /// Returns the estimated memory usage in bytes with the given compression mode.
+    pub(super) fn memory_usage_with(&self, mode: CompressionMode) -> u64 {
+        let num_bytes = mode.num_bytes();
+        let num_elements = [
+            self.num_storage,
+            self.num_storage,
+            self.num_storage_ip,
+            self.num_storage_chance,
+        ];
+
+        let storage = (0..4).map(|i| num_bytes[i] * num_elements[i]).sum::<u64>();
+        storage + self.misc_memory_usage
+    }
+
+    /// Returns the range of node indices on the given street.
+    pub(super) fn node_range(&self, street: BoardState) -> Range<usize> {
+        let num_nodes = self.num_nodes.map(|x| x as usize);
+        match street {
+            BoardState::Flop => 0..num_nodes[0],
+            BoardState::Turn => num_nodes[0]..num_nodes[0] + num_nodes[1],
+            BoardState::River => num_nodes[0] + num_nodes[1]..num_nodes.iter().sum(),
+        }
+    }
+
+    /// Returns whether the nodes of the given street are loaded.
+    #[inline]
+    pub(crate) fn has_street_nodes(&self, street: BoardState) -> bool {
+        self.node_arena.len() >= self.node_range(street).end
+    }
+
+    /// Returns whether the storage of the node is loaded. Every node is loaded unless the game is
+    /// being loaded subtree by subtree from an indexed file.
+    #[inline]
+    pub(crate) fn is_subtree_loaded(&self, index: usize) -> bool {
+        let node = self.node_arena[index].lock();
+        node.is_terminal() || !node.storage1.is_null()
     }
 
     /// Counts the number of nodes in the game tree.
     #[inline]
     fn count_num_nodes(&self) -> [u64; 3] {
+        let coef = self.street_coefficients();
+        let num_action_nodes = count_num_action_nodes(&self.action_root.lock());
+        [0, 1, 2].map(|street| num_action_nodes[street] * coef[street])
+    }
+
+    /// Returns the number of game nodes corresponding to one action tree node for each street.
+    pub(super) fn street_coefficients(&self) -> [u64; 3] {
+        let num_cards = self.card_config.variant.num_cards();
         let (turn_coef, river_coef) = match (self.card_config.turn, self.card_config.river) {
             (NOT_DEALT, _) => {
                 let mut river_coef = 0;
                 let flop = self.card_config.flop;
                 let skip_cards = &self.isomorphism_card_turn;
-                let flop_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
+                let mut flop_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
+                flop_mask |= self.card_config.variant.removed_cards_mask();
                 let skip_mask: u64 = skip_cards.iter().map(|&card| 1 << card).sum();
                 for turn in 0..52 {
                     if (1 << turn) & (flop_mask | skip_mask) == 0 {
-                        river_coef += 48 - self.isomorphism_card_river[turn & 3].len();
+                        river_coef += num_cards - 4 - self.isomorphism_card_river[turn & 3].len();
                     }
                 }
-                (49 - self.isomorphism_card_turn.len(), river_coef)
+                (num_cards - 3 - self.isomorphism_card_turn.len(), river_coef)
             }
-            (turn, NOT_DEALT) => (1, 48 - self.isomorphism_card_river[turn as usize & 3].len()),
+            (turn, NOT_DEALT) => (
+                1,
+                num_cards - 4 - self.isomorphism_card_river[turn as usize & 3].len(),
+            ),
             _ => (0, 1),
         };
 
-        let num_action_nodes = count_num_action_nodes(&self.action_root.lock());
-
-        [
-            num_action_nodes[0],
-            num_action_nodes[1] * turn_coef as u64,
-            num_action_nodes[2] * river_coef as u64,
-        ]
+        [1, turn_coef as u64, river_coef as u64]
     }
 
     /// Computes the memory usage of this struct.
     #[inline]
-    fn memory_usage_internal(&self) -> u64 {
+    pub(super) fn memory_usage_internal(&self) -> u64 {
         // untracked: tree_config, action_root
 
         let mut memory_usage = mem::size_of::<Self>() as u64;
@@ -712,7 +920,8 @@ impl PostFlopGame {
     fn push_chances(&self, node_index: usize, info: &mut BuildTreeInfo) {
         let mut node = self.node_arena[node_index].lock();
         let flop = self.card_config.flop;
-        let flop_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
+        let mut flop_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
+        flop_mask |= self.card_config.variant.removed_cards_mask();
 
         // deal turn
         if node.turn == NOT_DEALT {
@@ -1417,9 +1626,9 @@ impl PostFlopGame {
     }
 
     /// Allocates memory recursively.
-    fn allocate_memory_nodes(&mut self) {
-        let num_bytes = if self.is_compression_enabled { 2 } else { 4 };
-        let mut action_counter = 0;
+    pub(super) fn allocate_memory_nodes(&mut self) {
+        let num_bytes = self.compression_mode().num_bytes().map(|x| x as usize);
+        let mut action_counter = [0; 2];
         let mut ip_counter = 0;
         let mut chance_counter = 0;
 
@@ -1432,18 +1641,19 @@ impl PostFlopGame {
                     let ptr = self.storage_chance.as_mut_ptr();
                     node.storage1 = ptr.add(chance_counter);
                 }
-                chance_counter += num_bytes * node.num_elements as usize;
+                chance_counter += num_bytes[3] * node.num_elements as usize;
             } else {
                 unsafe {
                     let ptr1 = self.storage1.as_mut_ptr();
                     let ptr2 = self.storage2.as_mut_ptr();
                     let ptr3 = self.storage_ip.as_mut_ptr();
-                    node.storage1 = ptr1.add(action_counter);
-                    node.storage2 = ptr2.add(action_counter);
+                    node.storage1 = ptr1.add(action_counter[0]);
+                    node.storage2 = ptr2.add(action_counter[1]);
                     node.storage3 = ptr3.add(ip_counter);
                 }
-                action_counter += num_bytes * node.num_elements as usize;
-                ip_counter += num_bytes * node.num_elements_ip as usize;
+                action_counter[0] += num_bytes[0] * node.num_elements as usize;
+                action_counter[1] += num_bytes[1] * node.num_elements as usize;
+                ip_counter += num_bytes[2] * node.num_elements_ip as usize;
             }
         }
     }
@@ -1,5 +1,8 @@
 use super::*;
+use crate::hand::*;
+use crate::rake::*;
 use crate::sliceop::*;
+use std::cmp::Ordering;
 use std::mem::MaybeUninit;
 
 #[inline]
@@ -12,6 +15,84 @@ fn min(x: f64, y: f64) -> f64 {
 }
 
 impl PostFlopGame {
+    /// Computes the rake of the terminal `node`.
+    #[inline]
+    fn compute_rake(&self, node: &PostFlopNode, pot: f64) -> f64 {
+        let board_state = if node.river != NOT_DEALT {
+            BoardState::River
+        } else if node.turn != NOT_DEALT {
+            BoardState::Turn
+        } else {
+            BoardState::Flop
+        };
+        let is_showdown = node.player & PLAYER_FOLD_FLAG != PLAYER_FOLD_FLAG;
+        self.compute_rake_with(node.amount, pot, board_state, is_showdown)
+    }
+
+    /// Computes the rake of the pot that ends on `board_state`.
+    #[inline]
+    fn compute_rake_with(
+        &self,
+        amount: i32,
+        pot: f64,
+        board_state: BoardState,
+        is_showdown: bool,
+    ) -> f64 {
+        let config = &self.tree_config;
+        match &config.rake_model {
+            None => min(pot * config.rake_rate, config.rake_cap),
+            Some(rake_model) => rake_model.rake(&RakeContext {
+                pot,
+                starting_pot: config.starting_pot as f64,
+                called_amount: amount as f64,
+                board_state,
+                is_showdown,
+            }),
+        }
+    }
+
+    /// Computes the payoff of each player at the terminal `node` when the players hold `hands`.
+    ///
+    /// `board` must be complete (i.e., five cards) unless someone folded. The payoffs are on the
+    /// same scale as [`evaluate_internal`](Self::evaluate_internal) but not divided by the number
+    /// of combinations. `node` may be the chance node after the all-in call, in which case the
+    /// rake is computed as a river showdown.
+    pub(super) fn terminal_payoffs(
+        &self,
+        node: &PostFlopNode,
+        hands: [(Card, Card); 2],
+        board: &[Card],
+    ) -> [f64; 2] {
+        let pot = (self.tree_config.starting_pot + 2 * node.amount) as f64;
+        let half_pot = 0.5 * pot;
+
+        // someone folded
+        if node.player & PLAYER_FOLD_FLAG == PLAYER_FOLD_FLAG {
+            let rake = self.compute_rake(node, pot);
+            let mut ret = [half_pot - rake; 2];
+            ret[(node.player & PLAYER_MASK) as usize] = -half_pot;
+            return ret;
+        }
+
+        let rake = self.compute_rake_with(node.amount, pot, BoardState::River, true);
+        let evaluate = match self.card_config.variant {
+            GameVariant::Holdem => Hand::evaluate,
+            GameVariant::ShortDeck => Hand::evaluate_short_deck,
+        };
+
+        let board = board
+            .iter()
+            .fold(Hand::new(), |hand, &card| hand.add_card(card as usize));
+        let strength =
+            hands.map(|(c1, c2)| evaluate(&board.add_card(c1 as usize).add_card(c2 as usize)));
+
+        match strength[0].cmp(&strength[1]) {
+            Ordering::Greater => [half_pot - rake, -half_pot],
+            Ordering::Less => [-half_pot, half_pot - rake],
+            Ordering::Equal => [-0.5 * rake; 2],
+        }
+    }
+
     pub(super) fn evaluate_internal(
         &self,
         result: &mut [MaybeUninit<f32>],
@@ -21,7 +102,7 @@ impl PostFlopGame {
     ) {
         let pot = (self.tree_config.starting_pot + 2 * node.amount) as f64;
         let half_pot = 0.5 * pot;
-        let rake = min(pot * self.tree_config.rake_rate, self.tree_config.rake_cap);
+        let rake = self.compute_rake(node, pot);
         let amount_win = (half_pot - rake) / self.num_combinations;
         let amount_lose = -half_pot / self.num_combinations;
 
@@ -261,7 +342,7 @@ impl PostFlopGame {
     ) {
         let pot = (self.tree_config.starting_pot + 2 * node.amount) as f64;
         let half_pot = 0.5 * pot;
-        let rake = min(pot * self.tree_config.rake_rate, self.tree_config.rake_cap);
+        let rake = self.compute_rake(node, pot);
         let amount_win = ((half_pot - rake) / self.bunching_num_combinations) as f32;
         let amount_lose = (-half_pot / self.bunching_num_combinations) as f32;
         let amount_tie = (-0.5 * rake / self.bunching_num_combinations) as f32;
@@ -10,6 +10,13 @@ fn decode_signed_slice(slice: &[i16], scale: f32) -> Vec<f32> {
     slice.iter().map(|&x| x as f32 * decoder).collect()
 }
 
+/// Decodes the encoded `i8` slice to the `f32` slice.
+#[inline]
+fn decode_signed_slice_quantized(slice: &[i8], scale: f32) -> Vec<f32> {
+    let decoder = scale / i8::MAX as f32;
+    slice.iter().map(|&x| x as f32 * decoder).collect()
+}
+
 impl PostFlopGame {
     /// Moves the current node back to the root node.
     #[inline]
@@ -133,7 +140,7 @@ impl PostFlopGame {
 
         let flop = self.card_config.flop;
         let mut board_mask: u64 = (1 << flop[0]) | (1 << flop[1]) | (1 << flop[2]);
-        let mut dead_mask: u64 = 0;
+        let mut dead_mask: u64 = self.card_config.variant.removed_cards_mask();
 
         // no bunching
         if self.bunching_num_dead_cards == 0 {
@@ -266,7 +273,8 @@ impl PostFlopGame {
     ///   - If the current node is not a chance node, plays the `action`-th action of
     ///     [`available_actions`].
     ///
-    /// Panics if the memory is not yet allocated or the current node is a terminal node.
+    /// Panics if the memory is not yet allocated, the current node is a terminal node, or the dealt
+    /// card leads to a subtree that is not loaded yet (see `IndexedGameFile`).
     ///
     /// **Time complexity:** *O*(#(OOP private hands) + #(IP private hands))
     ///
@@ -283,9 +291,13 @@ impl PostFlopGame {
         // chance node
         if self.is_chance_node() {
             let is_turn = self.turn == NOT_DEALT;
-            if self.storage_mode == BoardState::Flop
-                || (!is_turn && self.storage_mode == BoardState::Turn)
-            {
+            let next_street = if is_turn {
+                BoardState::Turn
+            } else {
+                BoardState::River
+            };
+
+            if !self.has_street_nodes(next_street) {
                 panic!("Storage mode is not compatible");
             }
 
@@ -295,69 +307,39 @@ impl PostFlopGame {
                 action as Card
             };
 
-            // swap the suit if swapping was performed in turn
-            let action_card = if let Some((suit1, suit2)) = self.turn_swapped_suit {
-                if actual_card & 3 == suit1 {
-                    actual_card - suit1 + suit2
-                } else if actual_card & 3 == suit2 {
-                    actual_card + suit1 - suit2
-                } else {
-                    actual_card
-                }
-            } else {
-                actual_card
-            };
+            let action_card = self.chance_action_card(actual_card);
 
-            let actions = self.available_actions();
-            let mut action_index = usize::MAX;
+            // panic if the action is not found
+            let Some((action_index, isomorphic_index)) = self.find_chance_action(action_card)
+            else {
+                panic!("Invalid action");
+            };
 
-            // find the action index from available actions
-            for (i, &action) in actions.iter().enumerate() {
-                if action == Action::Chance(action_card) {
-                    action_index = i;
-                    break;
-                }
+            // panic if the subtree is not loaded yet (see `IndexedGameFile::load_subtree`)
+            let node_index = self.node_index(&self.node().play(action_index));
+            if !self.is_subtree_loaded(node_index) {
+                panic!("Subtree is not loaded");
             }
 
-            // find the action index from isomorphic chances
-            if action_index == usize::MAX {
-                let node = self.node();
-                let isomorphism = self.isomorphic_chances(&node);
-                let isomorphic_cards = if node.turn == NOT_DEALT {
-                    &self.isomorphism_card_turn
-                } else {
-                    &self.isomorphism_card_river[node.turn as usize & 3]
-                };
-                for (i, &repr_index) in isomorphism.iter().enumerate() {
-                    if action_card == isomorphic_cards[i] {
-                        action_index = repr_index as usize;
-                        if is_turn {
-                            if let Action::Chance(repr_card) = actions[repr_index as usize] {
-                                self.turn_swapped_suit = Some((action_card & 3, repr_card & 3));
-                            }
-                            self.turn_swap = Some(action_card & 3);
-                        } else {
-                            // `self.turn != self.node().turn` if `self.turn_swap.is_some()`.
-                            // This is possible only when the flop is monotone.
-                            // In this case, there is only one suit that can be swapped and the
-                            // following code works correctly.
-                            self.river_swap = Some((
-                                self.turn & 3,
-                                self.isomorphism_card_river[self.turn as usize & 3][i] & 3,
-                            ));
-                        }
-                        break;
+            if let Some(i) = isomorphic_index {
+                if is_turn {
+                    if let Action::Chance(repr_card) = self.available_actions()[action_index] {
+                        self.turn_swapped_suit = Some((action_card & 3, repr_card & 3));
                     }
+                    self.turn_swap = Some(action_card & 3);
+                } else {
+                    // `self.turn != self.node().turn` if `self.turn_swap.is_some()`.
+                    // This is possible only when the flop is monotone.
+                    // In this case, there is only one suit that can be swapped and the
+                    // following code works correctly.
+                    self.river_swap = Some((
+                        self.turn & 3,
+                        self.isomorphism_card_river[self.turn as usize & 3][i] & 3,
+                    ));
                 }
             }
 
-            // panic if the action is not found
-            if action_index == usize::MAX {
-                panic!("Invalid action");
-            }
-
             // update the state
-            let node_index = self.node_index(&self.node().play(action_index));
             self.node_history.push(node_index);
             if is_turn {
                 self.turn = actual_card;
@@ -612,18 +594,21 @@ impl PostFlopGame {
 
         let tmp = if self.bunching_num_dead_cards == 0 {
             let mut tmp = vec![0.0; num_hands];
+            let num_river = (self.card_config.variant.num_cards() - 8) as f64;
+            let num_turn = num_river + 1.0;
             if self.river != NOT_DEALT {
                 self.equity_internal(&mut tmp, player, self.turn, self.river, 0.5);
             } else if self.turn != NOT_DEALT {
                 for river in 0..52 {
                     if self.turn != river {
-                        self.equity_internal(&mut tmp, player, self.turn, river, 0.5 / 44.0);
+                        self.equity_internal(&mut tmp, player, self.turn, river, 0.5 / num_river);
                     }
                 }
             } else {
+                let amount = 1.0 / (num_turn * num_river);
                 for turn in 0..52 {
                     for river in turn + 1..52 {
-                        self.equity_internal(&mut tmp, player, turn, river, 1.0 / (45.0 * 44.0));
+                        self.equity_internal(&mut tmp, player, turn, river, amount);
                     }
                 }
             }
@@ -649,6 +634,11 @@ impl PostFlopGame {
 
     /// Returns the expected values of each private hand of the given player.
     ///
+    /// By default, the expected value is the expected share of the pot, from which the future bets
+    /// of the player are subtracted. If `pot_contributions` of [`TreeConfig`] is set, the expected
+    /// value is the net profit of the hand, i.e., the player's contribution to the starting pot and
+    /// all the bets of the player are subtracted.
+    ///
     /// Panics if the game is not solved.
     ///
     /// After mutating the current node, you must call the [`cache_normalized_weights`] method
@@ -724,10 +714,10 @@ impl PostFlopGame {
 
         let mut chance_factor = 1;
         if self.card_config.turn == NOT_DEALT && self.turn != NOT_DEALT {
-            chance_factor *= 45 - self.bunching_num_dead_cards;
+            chance_factor *= self.num_possible_deals(3);
         }
         if self.card_config.river == NOT_DEALT && self.river != NOT_DEALT {
-            chance_factor *= 44 - self.bunching_num_dead_cards;
+            chance_factor *= self.num_possible_deals(4);
         }
 
         let num_combinations = match self.bunching_num_dead_cards {
@@ -747,7 +737,11 @@ impl PostFlopGame {
             unsafe { ret.set_len(num_hands) };
             ret
         } else if node.is_chance() && node.cfvalue_storage_player() == Some(player) {
-            if self.is_compression_enabled {
+            if self.is_quantization_enabled {
+                let slice = node.cfvalues_chance_quantized();
+                let scale = node.cfvalue_chance_scale();
+                decode_signed_slice_quantized(slice, scale)
+            } else if self.is_compression_enabled {
                 let slice = node.cfvalues_chance_compressed();
                 let scale = node.cfvalue_chance_scale();
                 decode_signed_slice(slice, scale)
@@ -755,7 +749,11 @@ impl PostFlopGame {
                 node.cfvalues_chance().to_vec()
             }
         } else if node.has_cfvalues_ip() && player == PLAYER_IP as usize {
-            if self.is_compression_enabled {
+            if self.is_quantization_enabled {
+                let slice = node.cfvalues_ip_quantized();
+                let scale = node.cfvalue_ip_scale();
+                decode_signed_slice_quantized(slice, scale)
+            } else if self.is_compression_enabled {
                 let slice = node.cfvalues_ip_compressed();
                 let scale = node.cfvalue_ip_scale();
                 decode_signed_slice(slice, scale)
@@ -778,6 +776,17 @@ impl PostFlopGame {
         let starting_pot = self.tree_config.starting_pot;
         let total_bet_amount = self.total_bet_amount();
         let bias = (total_bet_amount[player] - total_bet_amount[player ^ 1]).max(0);
+        let invested = self.node().amount + bias;
+
+        // (offset, value of fold action)
+        let (offset, fold_value) = match self.tree_config.pot_contributions {
+            None => (starting_pot as f32 * 0.5 + invested as f32, 0.0),
+            Some(contributions) => {
+                let contribution = contributions[player];
+                let offset = starting_pot as f32 * 0.5 - contribution as f32;
+                (offset, -(contribution + invested) as f32)
+            }
+        };
 
         ret.chunks_exact_mut(num_hands)
             .enumerate()
@@ -788,11 +797,13 @@ impl PostFlopGame {
                     .zip(self.weights[player].iter())
                     .zip(self.normalized_weights[player].iter())
                     .for_each(|((v, &w_raw), &w_normalized)| {
-                        if is_fold || w_normalized == 0.0 {
+                        if w_normalized == 0.0 {
                             *v = 0.0;
+                        } else if is_fold {
+                            *v = fold_value;
                         } else {
                             *v *= normalizer * (w_raw / w_normalized);
-                            *v += starting_pot as f32 * 0.5 + (self.node().amount + bias) as f32;
+                            *v += offset;
                         }
                     });
             });
@@ -830,7 +841,9 @@ impl PostFlopGame {
         let num_actions = node.num_actions();
         let num_hands = self.num_private_hands(player);
 
-        let mut ret = if self.is_compression_enabled {
+        let mut ret = if self.is_quantization_enabled {
+            normalized_strategy_compressed(node.strategy_quantized(), num_actions)
+        } else if self.is_compression_enabled {
             normalized_strategy_compressed(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy(node.strategy(), num_actions)
@@ -846,12 +859,123 @@ impl PostFlopGame {
         ret
     }
 
+    /// Returns the best-response strategy of the current player against the opponent's current
+    /// strategy.
+    ///
+    /// The return value has the same layout as the [`strategy`] method. Each hand takes the action
+    /// that maximizes its expected value, assuming that the current player also plays the best
+    /// response in the subsequent nodes. As in [`compute_mes_ev`], the locked strategies of the
+    /// current player are respected; the locked hands take their locked strategies, and the node
+    /// whose aggregate frequencies are locked returns the same strategy as the [`strategy`]
+    /// method.
+    ///
+    /// Panics if the current node is a terminal node or a chance node. Also, panics if the memory
+    /// is not yet allocated.
+    ///
+    /// **Time complexity:** *O*(#(nodes in the subtree) * #(private hands)).
+    ///
+    /// [`strategy`]: #method.strategy
+    /// [`compute_mes_ev`]: crate::compute_mes_ev
+    pub fn current_best_response(&self) -> Vec<f32> {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.is_terminal_node() {
+            panic!("Terminal node is not allowed");
+        }
+
+        if self.is_chance_node() {
+            panic!("Chance node is not allowed");
+        }
+
+        let node = self.node();
+        if self
+            .locking_frequencies
+            .contains_key(&self.node_index(&node))
+        {
+            return self.strategy();
+        }
+
+        let player = self.current_player();
+        let num_actions = node.num_actions();
+        let num_hands = self.num_private_hands(player);
+
+        let cfv_actions = self.best_response_cfvalues();
+        let mut locking = self.locking_strategy(&node).to_vec();
+        locking.chunks_exact_mut(num_hands).for_each(|chunk| {
+            self.apply_swap(chunk, player, false);
+        });
+
+        let mut ret = vec![0.0; num_actions * num_hands];
+
+        for hand in 0..num_hands {
+            if !locking.is_empty() && locking[hand].is_sign_positive() {
+                for action in 0..num_actions {
+                    let index = action * num_hands + hand;
+                    ret[index] = locking[index];
+                }
+            } else {
+                let best_action = (1..num_actions).fold(0, |best, action| {
+                    let value = cfv_actions[action * num_hands + hand];
+                    if value > cfv_actions[best * num_hands + hand] {
+                        action
+                    } else {
+                        best
+                    }
+                });
+                ret[best_action * num_hands + hand] = 1.0;
+            }
+        }
+
+        ret
+    }
+
+    /// Returns the counterfactual values of each action of the current player at the current
+    /// node, assuming that the current player plays the best response in the subsequent nodes.
+    ///
+    /// The values are not normalized; the reach probabilities of the opponent do not include the
+    /// chance probabilities of the dealt cards.
+    pub(super) fn best_response_cfvalues(&self) -> Vec<f32> {
+        let node = self.node();
+        let player = self.current_player();
+        let num_actions = node.num_actions();
+        let num_hands = self.num_private_hands(player);
+
+        let mut cfreach = self.weights[player ^ 1].clone();
+        self.apply_swap(&mut cfreach, player ^ 1, true);
+
+        let mut ret = Vec::with_capacity(num_actions * num_hands);
+        for action in 0..num_actions {
+            compute_best_cfv_recursive(
+                row_mut(ret.spare_capacity_mut(), action, num_hands),
+                self,
+                &node.play(action),
+                player,
+                &cfreach,
+            );
+        }
+        unsafe { ret.set_len(num_actions * num_hands) };
+
+        ret.chunks_exact_mut(num_hands).for_each(|chunk| {
+            self.apply_swap(chunk, player, false);
+        });
+
+        ret
+    }
+
     /// Returns the total bet amount of each player (OOP, IP).
     #[inline]
     pub fn total_bet_amount(&self) -> [i32; 2] {
         self.total_bet_amount
     }
 
+    /// Returns the remaining stack of each player (OOP, IP) based on the starting stacks.
+    #[inline]
+    pub fn remaining_stacks(&self) -> [i32; 2] {
+        [0, 1].map(|player| self.tree_config.starting_stack(player) - self.total_bet_amount[player])
+    }
+
     /// Locks the strategy of the current node.
     ///
     /// The `strategy` argument must be a slice of the length of `#(actions) * #(private hands)`.
@@ -952,8 +1076,8 @@ impl PostFlopGame {
             return;
         }
 
-        node.is_locked = false;
         let index = self.node_index(&node);
+        node.is_locked = self.locking_frequencies.contains_key(&index);
         self.locking_strategy.remove(&index);
     }
 
@@ -992,9 +1116,121 @@ impl PostFlopGame {
         })
     }
 
+    /// Locks the aggregate frequencies of the actions at the current node.
+    ///
+    /// The `frequencies` argument must be a slice of the length of `#(actions)`. Negative values
+    /// are treated as zeros, and the values are normalized so that their sum is 1.0.
+    ///
+    /// Unlike [`lock_current_strategy`], the strategy of each hand is not locked. The solver
+    /// decides which hands take each action so that the frequencies of the actions over the range
+    /// reaching the node match `frequencies`, i.e., it computes a best response subject to the
+    /// frequencies. The frequencies are matched approximately, and the hands locked by
+    /// [`lock_current_strategy`] at the same node are taken into account. When computing the
+    /// exploitability, the strategy of a node locked by this method is treated as fixed.
+    ///
+    /// This method must be called after allocating memory and before solving the game.
+    /// Panics if the memory is not yet allocated or the game is already solved.
+    /// Also, panics if the current node is a terminal node or a chance node, or if the sum of
+    /// `frequencies` is not positive.
+    ///
+    /// [`lock_current_strategy`]: #method.lock_current_strategy
+    pub fn lock_current_frequencies(&mut self, frequencies: &[f32]) {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.state == State::Solved {
+            panic!("Game is already solved");
+        }
+
+        if self.is_terminal_node() {
+            panic!("Terminal node is not allowed");
+        }
+
+        if self.is_chance_node() {
+            panic!("Chance node is not allowed");
+        }
+
+        let mut node = self.node();
+        let num_actions = node.num_actions();
+
+        if frequencies.len() != num_actions {
+            panic!("Invalid frequencies length");
+        }
+
+        let sum = frequencies.iter().map(|&f| f.max(0.0) as f64).sum::<f64>();
+        if sum <= 0.0 {
+            panic!("Invalid frequencies");
+        }
+
+        let frequencies = frequencies
+            .iter()
+            .map(|&f| (f.max(0.0) as f64 / sum) as f32)
+            .collect();
+
+        node.is_locked = true;
+        let index = self.node_index(&node);
+        let multipliers = MutexLike::new(vec![1.0; num_actions]);
+        self.locking_frequencies
+            .insert(index, (frequencies, multipliers));
+    }
+
+    /// Unlocks the aggregate frequencies of the current node.
+    ///
+    /// This method must be called after allocating memory and before solving the game.
+    /// Panics if the memory is not yet allocated or the game is already solved.
+    /// Also, panics if the current node is a terminal node or a chance node.
+    #[inline]
+    pub fn unlock_current_frequencies(&mut self) {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.state == State::Solved {
+            panic!("Game is already solved");
+        }
+
+        if self.is_terminal_node() {
+            panic!("Terminal node is not allowed");
+        }
+
+        if self.is_chance_node() {
+            panic!("Chance node is not allowed");
+        }
+
+        let mut node = self.node();
+        let index = self.node_index(&node);
+        node.is_locked = self.locking_strategy.contains_key(&index);
+        self.locking_frequencies.remove(&index);
+    }
+
+    /// Returns the locked aggregate frequencies of the current node.
+    ///
+    /// If the frequencies of the current node are not locked, `None` is returned. Otherwise,
+    /// returns the normalized frequencies of the length of `#(actions)`.
+    #[inline]
+    pub fn current_locking_frequencies(&self) -> Option<Vec<f32>> {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.is_terminal_node() {
+            panic!("Terminal node is not allowed");
+        }
+
+        if self.is_chance_node() {
+            panic!("Chance node is not allowed");
+        }
+
+        let index = self.node_index(&self.node());
+        self.locking_frequencies
+            .get(&index)
+            .map(|(frequencies, _)| frequencies.clone())
+    }
+
     /// Returns the reference to the current node.
     #[inline]
-    fn node(&self) -> MutexGuardLike<PostFlopNode> {
+    fn node(&self) -> MutexGuardLike<'_, PostFlopNode> {
         self.node_arena[self.node_history.last().cloned().unwrap_or(0)].lock()
     }
 
@@ -1005,6 +1241,82 @@ impl PostFlopGame {
         unsafe { node_ptr.offset_from(self.node_arena.as_ptr()) as usize }
     }
 
+    /// Returns the index of the node reached by dealing `card` at the current chance node, or
+    /// `None` if the card cannot be dealt. As in [`play`], `usize::MAX` selects the possible card
+    /// with the lowest index.
+    ///
+    /// [`play`]: #method.play
+    #[cfg(feature = "bincode")]
+    pub(crate) fn chance_child_index(&self, card: usize) -> Option<usize> {
+        let next_street = if self.turn == NOT_DEALT {
+            BoardState::Turn
+        } else {
+            BoardState::River
+        };
+
+        if !self.is_chance_node() || !self.has_street_nodes(next_street) {
+            return None;
+        }
+
+        let actual_card = match card {
+            usize::MAX => self.possible_cards().trailing_zeros() as Card,
+            0..=51 => card as Card,
+            _ => return None,
+        };
+
+        let action_card = self.chance_action_card(actual_card);
+        let (action_index, _) = self.find_chance_action(action_card)?;
+        Some(self.node_index(&self.node().play(action_index)))
+    }
+
+    /// Returns the card of the chance action that deals `actual_card`, swapping the suit if
+    /// swapping was performed in turn.
+    fn chance_action_card(&self, actual_card: Card) -> Card {
+        if let Some((suit1, suit2)) = self.turn_swapped_suit {
+            if actual_card & 3 == suit1 {
+                actual_card - suit1 + suit2
+            } else if actual_card & 3 == suit2 {
+                actual_card + suit1 - suit2
+            } else {
+                actual_card
+            }
+        } else {
+            actual_card
+        }
+    }
+
+    /// Finds the chance action that deals `action_card` at the current chance node.
+    ///
+    /// Returns the action index and, if the card is dealt through an isomorphic chance, the index
+    /// of the card in the isomorphic cards.
+    fn find_chance_action(&self, action_card: Card) -> Option<(usize, Option<usize>)> {
+        let actions = self.available_actions();
+
+        // find the action index from available actions
+        for (i, &action) in actions.iter().enumerate() {
+            if action == Action::Chance(action_card) {
+                return Some((i, None));
+            }
+        }
+
+        // find the action index from isomorphic chances
+        let node = self.node();
+        let isomorphism = self.isomorphic_chances(&node);
+        let isomorphic_cards = if node.turn == NOT_DEALT {
+            &self.isomorphism_card_turn
+        } else {
+            &self.isomorphism_card_river[node.turn as usize & 3]
+        };
+
+        for (i, &repr_index) in isomorphism.iter().enumerate() {
+            if action_card == isomorphic_cards[i] {
+                return Some((repr_index as usize, Some(i)));
+            }
+        }
+
+        None
+    }
+
     /// Assigns zero weights to the hands that are not possible.
     pub(super) fn assign_zero_weights(&mut self) {
         if self.bunching_num_dead_cards == 0 {
@@ -1083,7 +1395,7 @@ impl PostFlopGame {
 
     /// Applies the swap.
     #[inline]
-    fn apply_swap(&self, slice: &mut [f32], player: usize, reverse: bool) {
+    pub(super) fn apply_swap(&self, slice: &mut [f32], player: usize, reverse: bool) {
         let turn_swap = self
             .turn_swap
             .map(|suit| &self.isomorphism_swap_turn[suit as usize][player]);
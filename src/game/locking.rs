@@ -0,0 +1,796 @@
+use super::*;
+use crate::interface::*;
+use crate::range::*;
+use std::ops::RangeInclusive;
+
+/// A pattern matching a single action, used in [`LockRule`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionPattern {
+    /// Matches any action.
+    Any,
+
+    /// Matches a fold.
+    Fold,
+
+    /// Matches a check.
+    Check,
+
+    /// Matches a call.
+    Call,
+
+    /// Matches a bet of any size, including an all-in when no one has bet on the street.
+    Bet,
+
+    /// Matches a raise of any size, including an all-in when facing a bet.
+    Raise,
+
+    /// Matches a bet or a raise of any size.
+    Aggressive,
+
+    /// Matches the given action exactly.
+    Exact(Action),
+}
+
+/// A pattern matching the actions taken on a street, used in [`LockRule`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum LinePattern {
+    /// Matches any line.
+    #[default]
+    Any,
+
+    /// Matches the line that consists of the given patterns. An empty vector matches the first
+    /// action of the street.
+    Exact(Vec<ActionPattern>),
+
+    /// Matches the line that ends with the given patterns. For example, `EndsWith(vec![Aggressive])`
+    /// matches any node facing a bet or a raise.
+    ///
+    /// [`Aggressive`]: ActionPattern::Aggressive
+    EndsWith(Vec<ActionPattern>),
+}
+
+/// A pattern matching the board, used in [`LockRule`].
+///
+/// The pattern is matched against all the board cards dealt before the node, so the flush
+/// textures of the turn and the river are determined by the number of cards of the most common
+/// suit. For example, a four-card board with two suits of two cards is two-tone.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BoardPattern {
+    /// Matches any board.
+    #[default]
+    Any,
+
+    /// Matches the boards with at least two cards of the same rank.
+    Paired,
+
+    /// Matches the boards with at least three cards of the same suit (a flush is possible).
+    Monotone,
+
+    /// Matches the boards whose most common suit has exactly two cards.
+    TwoTone,
+
+    /// Matches the boards whose cards all have different suits.
+    Rainbow,
+
+    /// Matches the boards on which a straight is possible, i.e., at least three cards of
+    /// different ranks fit in a straight.
+    Connected,
+
+    /// Matches the boards containing a card of the given rank (`0` for deuce, ..., `12` for ace).
+    /// Ranks are used instead of cards because isomorphic runouts share the same node.
+    ContainsRank(u8),
+}
+
+/// A rule for locking the strategy of every matching node, applied with
+/// [`PostFlopGame::lock_by_rules`].
+///
+/// The rule locks the hands of `player` in `hands` at the nodes on `street` whose board matches
+/// `board`, whose line on the street matches `line`, and whose lines on the earlier streets match
+/// `previous_streets`. The locked hands take the actions matching `action` with the total
+/// probability of `frequency`, and the other actions with the remaining probability, split equally
+/// among the actions in each group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LockRule {
+    /// Player to lock (0 for OOP, 1 for IP).
+    pub player: usize,
+
+    /// Street to lock. `None` means all streets.
+    pub street: Option<BoardState>,
+
+    /// Pattern of the board at the node.
+    pub board: BoardPattern,
+
+    /// Pattern of the actions taken on the street before the node.
+    pub line: LinePattern,
+
+    /// Patterns of the actions taken on the streets before the current street, in chronological
+    /// order; the last pattern is matched against the previous street. An empty vector matches
+    /// any earlier actions. The streets before the initial state of the tree cannot be matched.
+    ///
+    /// For example, `vec![LinePattern::Exact(vec![Check, Bet, Call])]` on the turn matches the
+    /// nodes after OOP check-called a flop bet.
+    pub previous_streets: Vec<LinePattern>,
+
+    /// Hand categories to lock.
+    pub hands: RangeInclusive<HandCategory>,
+
+    /// Actions to take with the locked hands.
+    pub action: ActionPattern,
+
+    /// Total probability of the actions matching `action` (in range [`0.0`, `1.0`]).
+    pub frequency: f32,
+}
+
+/// A portable set of node locks, keyed by action lines and hand strings.
+///
+/// A lock set is exported from a game with [`PostFlopGame::export_lock_set`] and imported into
+/// another game, possibly with a different board, with [`PostFlopGame::import_lock_set`]. It can
+/// also be saved and loaded with `save_data_to_file` and `load_data_from_file` (requires the
+/// `bincode` feature).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct LockSet {
+    /// Node locks.
+    pub entries: Vec<LockEntry>,
+}
+
+/// A node lock in a [`LockSet`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "bincode", derive(Decode, Encode))]
+pub struct LockEntry {
+    /// Actions from the root node to the locked node. [`Action::Chance`] specifies the dealt card;
+    /// if a chance node is reached without it, the lock is applied under every card.
+    pub line: Vec<Action>,
+
+    /// Available actions at the locked node.
+    pub actions: Vec<Action>,
+
+    /// Locked strategies of hands: pairs of a hand string (e.g., `"AsKs"`) and the probabilities
+    /// of the actions.
+    pub strategy: Vec<(String, Vec<f32>)>,
+
+    /// Locked aggregate frequencies of the actions, if any.
+    pub frequencies: Option<Vec<f32>>,
+}
+
+impl ActionPattern {
+    /// Returns whether the pattern matches `action`. `is_facing_bet` indicates whether someone
+    /// has bet on the street before `action`.
+    #[inline]
+    fn matches(self, action: Action, is_facing_bet: bool) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Fold => action == Action::Fold,
+            Self::Check => action == Action::Check,
+            Self::Call => action == Action::Call,
+            Self::Bet => match action {
+                Action::Bet(_) => true,
+                Action::AllIn(_) => !is_facing_bet,
+                _ => false,
+            },
+            Self::Raise => match action {
+                Action::Raise(_) => true,
+                Action::AllIn(_) => is_facing_bet,
+                _ => false,
+            },
+            Self::Aggressive => is_aggressive(action),
+            Self::Exact(expected) => action == expected,
+        }
+    }
+}
+
+impl LinePattern {
+    /// Returns whether the pattern matches `line`, the actions taken on a street.
+    fn matches(&self, line: &[Action]) -> bool {
+        let patterns = match self {
+            Self::Any => return true,
+            Self::Exact(patterns) if patterns.len() != line.len() => return false,
+            Self::Exact(patterns) | Self::EndsWith(patterns) => patterns,
+        };
+
+        if patterns.len() > line.len() {
+            return false;
+        }
+
+        let start = line.len() - patterns.len();
+        patterns.iter().enumerate().all(|(i, pattern)| {
+            let index = start + i;
+            let is_facing_bet = line[..index].iter().any(|&a| is_aggressive(a));
+            pattern.matches(line[index], is_facing_bet)
+        })
+    }
+}
+
+impl BoardPattern {
+    /// Returns whether the pattern matches `board`.
+    fn matches(self, board: &[Card], variant: GameVariant) -> bool {
+        let mut rankset = 0u16;
+        let mut suit_count = [0; 4];
+        for &card in board {
+            rankset |= 1 << (card >> 2);
+            suit_count[card as usize & 3] += 1;
+        }
+
+        let max_suit_count = suit_count.into_iter().max().unwrap_or(0);
+
+        match self {
+            Self::Any => true,
+            Self::Paired => (rankset.count_ones() as usize) < board.len(),
+            Self::Monotone => max_suit_count >= 3,
+            Self::TwoTone => max_suit_count == 2,
+            Self::Rainbow => max_suit_count <= 1,
+            Self::Connected => {
+                // five consecutive ranks, and the wheel (A-2-3-4-5, or A-6-7-8-9 in short deck)
+                const WHEEL: u16 = 0b1_0000_0000_1111;
+                const SHORT_DECK_WHEEL: u16 = 0b1_0000_1111_0000;
+                let (lowest, wheel) = match variant {
+                    GameVariant::Holdem => (0, WHEEL),
+                    GameVariant::ShortDeck => (4, SHORT_DECK_WHEEL),
+                };
+                (lowest..=8)
+                    .map(|i| 0b1_1111 << i)
+                    .chain([wheel])
+                    .any(|window| (rankset & window).count_ones() >= 3)
+            }
+            Self::ContainsRank(rank) => rankset & (1 << rank) != 0,
+        }
+    }
+}
+
+impl PostFlopGame {
+    /// Locks the strategies of all the nodes matching the given rules.
+    ///
+    /// Each rule is applied to every node of its player that matches its street and lines, and
+    /// locks the hands in its hand categories (see [`LockRule`]). When multiple rules lock the same
+    /// hand at the same node, the latter rule takes precedence. The hands that are not locked by
+    /// any rule keep their current locking state, so this method can be combined with
+    /// [`lock_current_strategy`]. A rule whose `action` matches no action at a node is ignored at
+    /// that node.
+    ///
+    /// Returns the number of nodes whose strategies are locked by the rules. `Err` is returned if
+    /// a rule is invalid.
+    ///
+    /// This method must be called after allocating memory and before solving the game.
+    /// Panics if the memory is not yet allocated or the game is already solved.
+    ///
+    /// [`lock_current_strategy`]: #method.lock_current_strategy
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["QQ+,AK".parse().unwrap(), "TT+,AQ+".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     turn: card_from_str("Qc").unwrap(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let tree_config = TreeConfig {
+    ///     initial_state: BoardState::Turn,
+    ///     starting_pot: 100,
+    ///     effective_stack: 200,
+    ///     turn_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+    ///     river_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let action_tree = ActionTree::new(tree_config).unwrap();
+    /// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    /// game.allocate_memory(false);
+    ///
+    /// // IP folds all hands weaker than second pair when facing a river bet
+    /// let rule = LockRule {
+    ///     player: 1,
+    ///     street: Some(BoardState::River),
+    ///     board: BoardPattern::Any,
+    ///     line: LinePattern::EndsWith(vec![ActionPattern::Aggressive]),
+    ///     previous_streets: Vec::new(),
+    ///     hands: HandCategory::HighCard..=HandCategory::WeakPair,
+    ///     action: ActionPattern::Fold,
+    ///     frequency: 1.0,
+    /// };
+    ///
+    /// let num_locked = game.lock_by_rules(&[rule]).unwrap();
+    /// assert!(num_locked > 0);
+    /// ```
+    pub fn lock_by_rules(&mut self, rules: &[LockRule]) -> Result<usize, String> {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.state == State::Solved {
+            panic!("Game is already solved");
+        }
+
+        for rule in rules {
+            if rule.player > 1 {
+                return Err(format!("Invalid player: {}", rule.player));
+            }
+
+            if !(0.0..=1.0).contains(&rule.frequency) {
+                return Err(format!("Invalid frequency: {}", rule.frequency));
+            }
+
+            if let BoardPattern::ContainsRank(rank @ 13..) = rule.board {
+                return Err(format!("Invalid rank: {rank}"));
+            }
+        }
+
+        let mut locks = Vec::new();
+        let mut lines = vec![Vec::new()];
+        self.lock_by_rules_recursive(0, rules, &mut lines, &mut locks);
+
+        let num_locked = locks.len();
+        for (index, locking) in locks {
+            self.node_arena[index].lock().is_locked = true;
+            self.locking_strategy.insert(index, locking);
+        }
+
+        Ok(num_locked)
+    }
+
+    /// Locks the strategy of the node reached by `line` under every runout.
+    ///
+    /// The `line` argument is a list of actions from the root node. Whenever a chance node is
+    /// reached, every possible card is dealt, unless the next element of `line` is
+    /// [`Action::Chance`], in which case only that card is dealt. At each node reached, the
+    /// interpreter is moved to the node and `strategy_fn` is called with the game to obtain the
+    /// strategy, which is locked in the same way as [`lock_current_strategy`]. Thus, `strategy_fn`
+    /// can inspect [`current_board`] and [`private_cards`] to build the strategy.
+    ///
+    /// The runouts that are isomorphic to each other share the same node, and the strategies of
+    /// such runouts are mapped through the suit swaps. Therefore, `strategy_fn` should treat the
+    /// suits symmetrically (e.g., depend only on the ranks or the hand categories). Otherwise, the
+    /// strategy of the last visited runout overwrites the others for the whole node: the cards
+    /// are dealt in ascending order of their IDs (`2c`, `2d`, `2h`, `2s`, `3c`, ...), so on the
+    /// flop `Td9d6h`, the turn `2s` takes precedence over the isomorphic turn `2c`.
+    ///
+    /// Returns the number of runouts locked. `Err` is returned if an action in `line` is not
+    /// available or `line` reaches a terminal node. If `line` ends at a chance node, the node of
+    /// the first player to act on the next street is locked. The interpreter is moved back to the
+    /// node where it was before calling this method.
+    ///
+    /// This method must be called after allocating memory and before solving the game.
+    /// Panics if the memory is not yet allocated or the game is already solved, or if
+    /// `strategy_fn` returns a strategy of an invalid length.
+    ///
+    /// [`lock_current_strategy`]: #method.lock_current_strategy
+    /// [`current_board`]: #method.current_board
+    /// [`private_cards`]: #method.private_cards
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["QQ+,AK".parse().unwrap(), "TT+,AQ+".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let tree_config = TreeConfig {
+    ///     starting_pot: 100,
+    ///     effective_stack: 200,
+    ///     river_bet_sizes: [("50%", "").try_into().unwrap(), ("50%", "").try_into().unwrap()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let action_tree = ActionTree::new(tree_config).unwrap();
+    /// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    /// game.allocate_memory(false);
+    ///
+    /// // IP always bets the river after check-check on the flop and the turn
+    /// let line = [Action::Check; 5];
+    /// let num_runouts = game
+    ///     .lock_line_all_runouts(&line, |game| {
+    ///         let num_hands = game.private_cards(1).len();
+    ///         [vec![0.0; num_hands], vec![1.0; num_hands]].concat()
+    ///     })
+    ///     .unwrap();
+    ///
+    /// assert_eq!(num_runouts, 49 * 48);
+    /// ```
+    pub fn lock_line_all_runouts<F>(
+        &mut self,
+        line: &[Action],
+        mut strategy_fn: F,
+    ) -> Result<usize, String>
+    where
+        F: FnMut(&Self) -> Vec<f32>,
+    {
+        let mut num_locked = 0;
+        self.for_each_runout(line, |game| {
+            let strategy = strategy_fn(game);
+            game.lock_current_strategy(&strategy);
+            num_locked += 1;
+            Ok(())
+        })?;
+
+        Ok(num_locked)
+    }
+
+    /// Exports the node locks of the game as a [`LockSet`].
+    ///
+    /// Each locked node is exported with the line from the root node, including the dealt cards,
+    /// and the locked hands are keyed by their strings (e.g., `"AsKs"`). The runouts that are
+    /// isomorphic to each other share the same node, so only one of them is exported.
+    pub fn export_lock_set(&self) -> LockSet {
+        let mut lock_set = LockSet::default();
+        let mut line = Vec::new();
+        self.export_lock_set_recursive(0, &mut line, &mut lock_set);
+        lock_set
+    }
+
+    /// Imports the node locks in `lock_set` into the game.
+    ///
+    /// Each entry is applied to the node reached by its line in the same way as
+    /// [`lock_line_all_runouts`], so an entry whose line contains no dealt cards is applied under
+    /// every runout. The locks of the hands that are not in the ranges of the game or that conflict
+    /// with the board are ignored. This is useful for applying the same exploitative profile to
+    /// games with different boards.
+    ///
+    /// Returns the lines of the entries that do not match the game, i.e., those that contain an
+    /// action that is not available, that reach a terminal node, whose available actions differ
+    /// from those of the node, or that contain an invalid hand string. An entry that does not
+    /// match may be partially applied to the runouts visited before the mismatch was found.
+    ///
+    /// This method must be called after allocating memory and before solving the game.
+    /// Panics if the memory is not yet allocated or the game is already solved.
+    ///
+    /// [`lock_line_all_runouts`]: #method.lock_line_all_runouts
+    pub fn import_lock_set(&mut self, lock_set: &LockSet) -> Vec<Vec<Action>> {
+        let mut unmatched = Vec::new();
+
+        for entry in &lock_set.entries {
+            let parsed = entry
+                .strategy
+                .iter()
+                .map(|(hand, strategy)| {
+                    let mut chars = hand.chars();
+                    let card1 = card_from_chars(&mut chars)?;
+                    let card2 = card_from_chars(&mut chars)?;
+                    match chars.next() {
+                        None if card1 != card2 => {
+                            Ok(((card1.min(card2), card1.max(card2)), strategy))
+                        }
+                        _ => Err(format!("Invalid hand: {hand}")),
+                    }
+                })
+                .collect::<Result<BTreeMap<_, _>, String>>();
+
+            let result = parsed.and_then(|strategy| {
+                self.for_each_runout(&entry.line, |game| game.import_lock_entry(entry, &strategy))
+            });
+
+            if result.is_err() {
+                unmatched.push(entry.line.clone());
+            }
+        }
+
+        unmatched
+    }
+
+    /// Locks the current node with `entry`, whose strategy is keyed by the sorted hole cards.
+    fn import_lock_entry(
+        &mut self,
+        entry: &LockEntry,
+        strategy: &BTreeMap<(Card, Card), &Vec<f32>>,
+    ) -> Result<(), String> {
+        let num_actions = entry.actions.len();
+        if self.available_actions() != entry.actions
+            || strategy.values().any(|s| s.len() != num_actions)
+            || entry
+                .frequencies
+                .as_ref()
+                .is_some_and(|f| f.len() != num_actions)
+        {
+            return Err("Actions do not match".to_string());
+        }
+
+        if !strategy.is_empty() {
+            let player = self.current_player();
+            let private_cards = &self.private_cards[player];
+            let num_hands = private_cards.len();
+            let mut locking = vec![0.0; num_actions * num_hands];
+            let mut is_locked = false;
+
+            for (hand, &(c1, c2)) in private_cards.iter().enumerate() {
+                if let Some(s) = strategy.get(&(c1.min(c2), c1.max(c2))) {
+                    for (action, &prob) in s.iter().enumerate() {
+                        locking[action * num_hands + hand] = prob;
+                    }
+                    is_locked = true;
+                }
+            }
+
+            if is_locked {
+                self.lock_current_strategy(&locking);
+            }
+        }
+
+        if let Some(frequencies) = &entry.frequencies {
+            self.lock_current_frequencies(frequencies);
+        }
+
+        Ok(())
+    }
+
+    /// Calls `f` at the node reached by `line` from the root node under every runout (see
+    /// [`lock_line_all_runouts`]), and moves the interpreter back to the original node.
+    ///
+    /// [`lock_line_all_runouts`]: #method.lock_line_all_runouts
+    fn for_each_runout<F>(&mut self, line: &[Action], mut f: F) -> Result<(), String>
+    where
+        F: FnMut(&mut Self) -> Result<(), String>,
+    {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        if self.state == State::Solved {
+            panic!("Game is already solved");
+        }
+
+        let history = self.action_history.clone();
+        self.back_to_root();
+
+        let result = self.for_each_runout_recursive(line, &mut f);
+
+        self.apply_history(&history);
+        result
+    }
+
+    /// Calls `f` at the node reached by `line` from the current node under every runout.
+    fn for_each_runout_recursive<F>(&mut self, line: &[Action], f: &mut F) -> Result<(), String>
+    where
+        F: FnMut(&mut Self) -> Result<(), String>,
+    {
+        if self.is_terminal_node() {
+            return Err("Line reaches a terminal node".to_string());
+        }
+
+        if self.is_chance_node() {
+            let cards = match line.first() {
+                Some(&Action::Chance(card)) if self.possible_cards() & (1 << card) != 0 => {
+                    vec![card]
+                }
+                Some(&Action::Chance(card)) => return Err(format!("Card is not possible: {card}")),
+                _ => (0..52)
+                    .filter(|&c| self.possible_cards() & (1 << c) != 0)
+                    .collect(),
+            };
+
+            let rest = match line.first() {
+                Some(Action::Chance(_)) => &line[1..],
+                _ => line,
+            };
+
+            let history = self.action_history.clone();
+            for card in cards {
+                self.play(card as usize);
+                self.for_each_runout_recursive(rest, f)?;
+                self.apply_history(&history);
+            }
+
+            return Ok(());
+        }
+
+        let Some((&action, rest)) = line.split_first() else {
+            return f(self);
+        };
+
+        let actions = self.available_actions();
+        let Some(index) = actions.iter().position(|&a| a == action) else {
+            return Err(format!("Action is not available: {action:?}"));
+        };
+
+        self.play(index);
+        self.for_each_runout_recursive(rest, f)
+    }
+
+    /// Collects the node locks under the node at `index` into `lock_set`.
+    fn export_lock_set_recursive(
+        &self,
+        index: usize,
+        line: &mut Vec<Action>,
+        lock_set: &mut LockSet,
+    ) {
+        let node = self.node_arena[index].lock();
+        if node.is_terminal() {
+            return;
+        }
+
+        let actions = node
+            .children()
+            .iter()
+            .map(|child| child.lock().prev_action)
+            .collect::<Vec<_>>();
+
+        let locking = self.locking_strategy(&node);
+        let frequencies = self.locking_frequencies.get(&index);
+
+        if !node.is_chance() && (!locking.is_empty() || frequencies.is_some()) {
+            let private_cards = &self.private_cards[node.player()];
+            let num_hands = private_cards.len();
+            let strategy = if locking.is_empty() {
+                Vec::new()
+            } else {
+                private_cards
+                    .iter()
+                    .enumerate()
+                    .filter(|&(hand, _)| locking[hand].is_sign_positive())
+                    .map(|(hand, &cards)| {
+                        let strategy = (0..actions.len())
+                            .map(|action| locking[action * num_hands + hand])
+                            .collect();
+                        (hole_to_string(cards).unwrap(), strategy)
+                    })
+                    .collect()
+            };
+
+            lock_set.entries.push(LockEntry {
+                line: line.clone(),
+                actions: actions.clone(),
+                strategy,
+                frequencies: frequencies.map(|(f, _)| f.clone()),
+            });
+        }
+
+        let children_index = index + node.children_offset as usize;
+        for (i, &action) in actions.iter().enumerate() {
+            line.push(action);
+            self.export_lock_set_recursive(children_index + i, line, lock_set);
+            line.pop();
+        }
+    }
+
+    /// Collects the locking strategies of the nodes matching `rules` under the node at `index`.
+    ///
+    /// `lines` holds the actions taken on each street so far; the last element is the current
+    /// street.
+    fn lock_by_rules_recursive(
+        &self,
+        index: usize,
+        rules: &[LockRule],
+        lines: &mut Vec<Vec<Action>>,
+        locks: &mut Vec<(usize, Vec<f32>)>,
+    ) {
+        let node = self.node_arena[index].lock();
+        if node.is_terminal() {
+            return;
+        }
+
+        let children_index = index + node.children_offset as usize;
+
+        if node.is_chance() {
+            for i in 0..node.num_actions() {
+                lines.push(Vec::new());
+                self.lock_by_rules_recursive(children_index + i, rules, lines, locks);
+                lines.pop();
+            }
+            return;
+        }
+
+        if let Some(locking) = self.locking_by_rules(index, &node, rules, lines) {
+            locks.push((index, locking));
+        }
+
+        for (i, child) in node.children().iter().enumerate() {
+            lines.last_mut().unwrap().push(child.lock().prev_action);
+            self.lock_by_rules_recursive(children_index + i, rules, lines, locks);
+            lines.last_mut().unwrap().pop();
+        }
+    }
+
+    /// Computes the locking strategy of the given node, or returns `None` if no rule locks any
+    /// hand.
+    fn locking_by_rules(
+        &self,
+        index: usize,
+        node: &PostFlopNode,
+        rules: &[LockRule],
+        lines: &[Vec<Action>],
+    ) -> Option<Vec<f32>> {
+        let (line, previous_lines) = lines.split_last().unwrap();
+        let player = node.player();
+        let street = match (node.turn, node.river) {
+            (NOT_DEALT, _) => BoardState::Flop,
+            (_, NOT_DEALT) => BoardState::Turn,
+            _ => BoardState::River,
+        };
+
+        let mut board = self.card_config.flop.to_vec();
+        board.extend(
+            [node.turn, node.river]
+                .into_iter()
+                .filter(|&c| c != NOT_DEALT),
+        );
+        let board_mask: u64 = board.iter().map(|&c| 1 << c).sum();
+
+        let mut rules = rules
+            .iter()
+            .filter(|rule| {
+                rule.player == player
+                    && (rule.street.is_none() || rule.street == Some(street))
+                    && rule.board.matches(&board, self.card_config.variant)
+                    && rule.line.matches(line)
+                    && rule.previous_streets.len() <= previous_lines.len()
+                    && rule
+                        .previous_streets
+                        .iter()
+                        .rev()
+                        .zip(previous_lines.iter().rev())
+                        .all(|(pattern, line)| pattern.matches(line))
+            })
+            .peekable();
+
+        rules.peek()?;
+
+        let private_cards = &self.private_cards[player];
+        let categories = private_cards
+            .iter()
+            .map(|&(c1, c2)| {
+                let hand_mask: u64 = (1 << c1) | (1 << c2);
+                (hand_mask & board_mask == 0)
+                    .then(|| HandCategory::classify((c1, c2), &board, self.card_config.variant))
+            })
+            .collect::<Vec<_>>();
+
+        let actions = node
+            .children()
+            .iter()
+            .map(|child| child.lock().prev_action)
+            .collect::<Vec<_>>();
+
+        let num_actions = actions.len();
+        let num_hands = private_cards.len();
+        let is_facing_bet = line.iter().any(|&a| is_aggressive(a));
+
+        let mut locking = self
+            .locking_strategy
+            .get(&index)
+            .cloned()
+            .unwrap_or_else(|| vec![-1.0; num_actions * num_hands]);
+        let mut is_modified = false;
+
+        for rule in rules {
+            let matched = actions
+                .iter()
+                .map(|&action| rule.action.matches(action, is_facing_bet))
+                .collect::<Vec<_>>();
+
+            let num_matched = matched.iter().filter(|&&m| m).count();
+            if num_matched == 0 {
+                continue;
+            }
+
+            let num_others = num_actions - num_matched;
+            let probs = matched
+                .iter()
+                .map(|&m| match (m, num_others) {
+                    (true, 0) => 1.0 / num_matched as f32,
+                    (true, _) => rule.frequency / num_matched as f32,
+                    (false, _) => (1.0 - rule.frequency) / num_others as f32,
+                })
+                .collect::<Vec<_>>();
+
+            for (hand, category) in categories.iter().enumerate() {
+                if category.is_some_and(|c| rule.hands.contains(&c)) {
+                    for (action, &prob) in probs.iter().enumerate() {
+                        locking[action * num_hands + hand] = prob;
+                    }
+                    is_modified = true;
+                }
+            }
+        }
+
+        is_modified.then_some(locking)
+    }
+}
+
+/// Returns whether the action is a bet, a raise, or an all-in.
+#[inline]
+fn is_aggressive(action: Action) -> bool {
+    matches!(action, Action::Bet(_) | Action::Raise(_) | Action::AllIn(_))
+}
@@ -0,0 +1,419 @@
+use super::*;
+use crate::bet_size::*;
+use crate::interface::*;
+use std::mem;
+
+/// Memory usage of a game tree estimated before building it.
+///
+/// See [`PostFlopGame::estimate_memory_usage`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    /// Number of nodes on each street (flop, turn, river), including terminal and chance nodes.
+    pub num_nodes: [u64; 3],
+
+    /// Estimated memory usage in bytes without compression.
+    pub uncompressed: u64,
+
+    /// Estimated memory usage in bytes with [`CompressionMode::Int16`].
+    pub compressed: u64,
+
+    /// Estimated memory usage in bytes with [`CompressionMode::Int8`].
+    pub quantized: u64,
+}
+
+/// A modification of [`TreeConfig`] that reduces the size of the game tree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TreeTrim {
+    /// Removes a bet size of `player` on `street`. If `is_raise` is `true`, the size is removed
+    /// from the raise sizes; otherwise, from the first bet sizes.
+    RemoveBetSize {
+        street: BoardState,
+        player: usize,
+        is_raise: bool,
+        size: BetSize,
+    },
+
+    /// Removes a donk size on `street`.
+    RemoveDonkSize { street: BoardState, size: BetSize },
+
+    /// Removes all raise sizes of both players on `street`.
+    RemoveRaises { street: BoardState },
+
+    /// Removes all bet, raise, and donk sizes of both players on `street`, so that the street is
+    /// checked through unless an all-in action is added (e.g., by `add_allin_threshold`).
+    RemoveBets { street: BoardState },
+}
+
+/// A plan for fitting a game tree into a memory budget.
+///
+/// See [`PostFlopGame::plan_memory_budget`].
+#[derive(Debug, Clone)]
+pub struct TrimPlan {
+    /// Trims to be applied in order, each paired with the estimate after applying it.
+    pub steps: Vec<(TreeTrim, MemoryEstimate)>,
+
+    /// Tree configuration after applying all the trims.
+    pub tree_config: TreeConfig,
+
+    /// Whether the estimated memory usage after applying all the trims fits in the budget.
+    pub fits_budget: bool,
+}
+
+impl MemoryEstimate {
+    /// Returns the estimated memory usage in bytes with the given compression mode.
+    #[inline]
+    pub fn memory_usage(&self, compression: impl Into<CompressionMode>) -> u64 {
+        match compression.into() {
+            CompressionMode::Disabled => self.uncompressed,
+            CompressionMode::Int16 => self.compressed,
+            CompressionMode::Int8 => self.quantized,
+        }
+    }
+}
+
+impl TreeTrim {
+    /// Applies the trim to the given tree configuration.
+    pub fn apply(&self, config: &mut TreeConfig) {
+        match *self {
+            Self::RemoveBetSize {
+                street,
+                player,
+                is_raise,
+                size,
+            } => {
+                let options = &mut bet_sizes_mut(config, street)[player];
+                let sizes = if is_raise {
+                    &mut options.raise
+                } else {
+                    &mut options.bet
+                };
+                sizes.retain(|&s| s != size);
+            }
+            Self::RemoveDonkSize { street, size } => {
+                if let Some(options) = donk_sizes_mut(config, street) {
+                    options.donk.retain(|&s| s != size);
+                }
+            }
+            Self::RemoveRaises { street } => {
+                for options in bet_sizes_mut(config, street) {
+                    options.raise.clear();
+                }
+            }
+            Self::RemoveBets { street } => {
+                for options in bet_sizes_mut(config, street) {
+                    options.bet.clear();
+                    options.raise.clear();
+                }
+                if let Some(options) = donk_sizes_mut(config, street) {
+                    options.donk.clear();
+                }
+            }
+        }
+    }
+}
+
+impl PostFlopGame {
+    /// Estimates the memory usage of the game tree without building it.
+    ///
+    /// The result matches [`memory_usage`] of the game built with the same configuration, but only
+    /// the card configuration is processed here, so this is much cheaper than [`with_config`] for
+    /// large trees.
+    ///
+    /// [`memory_usage`]: #method.memory_usage
+    /// [`with_config`]: #method.with_config
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["QQ+,AK".parse().unwrap(), "TT+,AQ+".parse().unwrap()],
+    ///     flop: flop_from_str("Td9d6h").unwrap(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let tree_config = TreeConfig {
+    ///     starting_pot: 60,
+    ///     effective_stack: 970,
+    ///     flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let action_tree = ActionTree::new(tree_config).unwrap();
+    /// let estimate = PostFlopGame::estimate_memory_usage(&card_config, &action_tree).unwrap();
+    ///
+    /// let game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    /// assert_eq!(estimate.compressed, game.memory_usage().1);
+    /// ```
+    pub fn estimate_memory_usage(
+        card_config: &CardConfig,
+        action_tree: &ActionTree,
+    ) -> Result<MemoryEstimate, String> {
+        let game = Self::for_estimation(card_config, action_tree)?;
+        let estimate = game.estimate_internal(&action_tree.root());
+        Ok(estimate)
+    }
+
+    /// Plans how to trim the game tree so that its memory usage with `compression` fits in
+    /// `budget` bytes, without building the tree.
+    ///
+    /// The candidate trims are removing a single bet, raise, or donk size, removing all raises on
+    /// a street, and removing all bets on a street (see [`TreeTrim`]). The initial state of the
+    /// tree is never raised, because starting on a later street requires fixing the board cards
+    /// of the card configuration. The trims are chosen greedily: if some candidate fits the budget
+    /// by itself, the one that keeps the largest tree is chosen; otherwise, the one that saves the
+    /// most memory is chosen. Lines added to or removed from `action_tree` are reapplied after each
+    /// trim, and the trims after which some of the lines cannot be reapplied are skipped. If the
+    /// budget cannot be met, the returned plan contains all the trims found and `fits_budget` is
+    /// `false`.
+    pub fn plan_memory_budget(
+        card_config: &CardConfig,
+        action_tree: &ActionTree,
+        budget: u64,
+        compression: impl Into<CompressionMode>,
+    ) -> Result<TrimPlan, String> {
+        let compression = compression.into();
+        let game = Self::for_estimation(card_config, action_tree)?;
+        let mut estimate = game.estimate_internal(&action_tree.root());
+        let mut tree_config = action_tree.config().clone();
+        let mut steps = Vec::new();
+
+        while estimate.memory_usage(compression) > budget {
+            let current = estimate.memory_usage(compression);
+            let mut best: Option<(TreeTrim, TreeConfig, MemoryEstimate)> = None;
+
+            for trim in trim_candidates(&tree_config) {
+                let mut config = tree_config.clone();
+                trim.apply(&mut config);
+
+                let Ok(tree) = rebuild_action_tree(
+                    config.clone(),
+                    action_tree.added_lines(),
+                    action_tree.removed_lines(),
+                ) else {
+                    continue;
+                };
+
+                let candidate = game.estimate_internal(&tree.root());
+                let usage = candidate.memory_usage(compression);
+                if usage >= current {
+                    continue;
+                }
+
+                let is_better = match &best {
+                    None => true,
+                    Some((_, _, best_estimate)) => {
+                        let best_usage = best_estimate.memory_usage(compression);
+                        match (usage <= budget, best_usage <= budget) {
+                            (true, true) => usage > best_usage,
+                            (true, false) => true,
+                            (false, true) => false,
+                            (false, false) => usage < best_usage,
+                        }
+                    }
+                };
+
+                if is_better {
+                    best = Some((trim, config, candidate));
+                }
+            }
+
+            let Some((trim, config, candidate)) = best else {
+                break;
+            };
+
+            steps.push((trim, candidate));
+            tree_config = config;
+            estimate = candidate;
+        }
+
+        Ok(TrimPlan {
+            steps,
+            tree_config,
+            fits_budget: estimate.memory_usage(compression) <= budget,
+        })
+    }
+
+    /// Creates an instance that only has the card information.
+    fn for_estimation(card_config: &CardConfig, action_tree: &ActionTree) -> Result<Self, String> {
+        let mut game = Self::new();
+        game.card_config = card_config.clone();
+        game.tree_config = action_tree.config().clone();
+        game.added_lines = action_tree.added_lines().to_vec();
+        game.removed_lines = action_tree.removed_lines().to_vec();
+        game.check_card_config()?;
+        game.init_card_fields();
+        Ok(game)
+    }
+
+    /// Estimates the memory usage of the game tree built from `root`.
+    fn estimate_internal(&self, root: &ActionTreeNode) -> MemoryEstimate {
+        let coef = self.street_coefficients();
+        let num_action_nodes = count_num_action_nodes(root);
+        let num_nodes = [0, 1, 2].map(|street| num_action_nodes[street] * coef[street]);
+
+        // [storage, storage_ip, storage_chance]
+        let mut num_storage = [0; 3];
+        self.count_storage_recursive(root, Action::None, &coef, &mut num_storage);
+
+        let node_size = mem::size_of::<MutexLike<PostFlopNode>>() as u64;
+        let misc_memory_usage =
+            self.memory_usage_internal() + node_size * num_nodes.iter().sum::<u64>();
+
+        let usage = |mode: CompressionMode| {
+            let num_bytes = mode.num_bytes();
+            (num_bytes[0] + num_bytes[1]) * num_storage[0]
+                + num_bytes[2] * num_storage[1]
+                + num_bytes[3] * num_storage[2]
+                + misc_memory_usage
+        };
+
+        MemoryEstimate {
+            num_nodes,
+            uncompressed: usage(CompressionMode::Disabled),
+            compressed: usage(CompressionMode::Int16),
+            quantized: usage(CompressionMode::Int8),
+        }
+    }
+
+    /// Counts the number of storage elements recursively.
+    fn count_storage_recursive(
+        &self,
+        node: &ActionTreeNode,
+        prev_action: Action,
+        coef: &[u64; 3],
+        count: &mut [u64; 3],
+    ) {
+        let coef_street = coef[node.board_state as usize];
+
+        if node.is_terminal() {
+            // do nothing
+        } else if node.is_chance() {
+            let num_elements = match node.player & PLAYER_MASK {
+                0 => self.num_private_hands(1),
+                1 => self.num_private_hands(0),
+                _ => 0,
+            };
+            count[2] += coef_street * num_elements as u64;
+            let child = &node.children[0].lock();
+            self.count_storage_recursive(child, Action::Chance(0), coef, count);
+        } else {
+            let num_private_hands = self.num_private_hands(node.player as usize);
+            count[0] += coef_street * (node.actions.len() * num_private_hands) as u64;
+            if matches!(prev_action, Action::None | Action::Chance(_)) {
+                count[1] += coef_street * self.num_private_hands(PLAYER_IP as usize) as u64;
+            }
+            for (&action, child) in node.actions.iter().zip(&node.children) {
+                self.count_storage_recursive(&child.lock(), action, coef, count);
+            }
+        }
+    }
+}
+
+/// Returns the bet size options of the given street.
+#[inline]
+fn bet_sizes(config: &TreeConfig, street: BoardState) -> &[BetSizeOptions; 2] {
+    match street {
+        BoardState::Flop => &config.flop_bet_sizes,
+        BoardState::Turn => &config.turn_bet_sizes,
+        BoardState::River => &config.river_bet_sizes,
+    }
+}
+
+/// Returns the mutable reference to the bet size options of the given street.
+#[inline]
+fn bet_sizes_mut(config: &mut TreeConfig, street: BoardState) -> &mut [BetSizeOptions; 2] {
+    match street {
+        BoardState::Flop => &mut config.flop_bet_sizes,
+        BoardState::Turn => &mut config.turn_bet_sizes,
+        BoardState::River => &mut config.river_bet_sizes,
+    }
+}
+
+/// Returns the donk size options of the given street.
+#[inline]
+fn donk_sizes(config: &TreeConfig, street: BoardState) -> Option<&DonkSizeOptions> {
+    match street {
+        BoardState::Flop => None,
+        BoardState::Turn => config.turn_donk_sizes.as_ref(),
+        BoardState::River => config.river_donk_sizes.as_ref(),
+    }
+}
+
+/// Returns the mutable reference to the donk size options of the given street.
+#[inline]
+fn donk_sizes_mut(config: &mut TreeConfig, street: BoardState) -> Option<&mut DonkSizeOptions> {
+    match street {
+        BoardState::Flop => None,
+        BoardState::Turn => config.turn_donk_sizes.as_mut(),
+        BoardState::River => config.river_donk_sizes.as_mut(),
+    }
+}
+
+/// Lists the trims applicable to the given tree configuration.
+fn trim_candidates(config: &TreeConfig) -> Vec<TreeTrim> {
+    let mut ret = Vec::new();
+
+    if config.fixed_limit.is_some() {
+        return ret;
+    }
+
+    for street in [BoardState::Flop, BoardState::Turn, BoardState::River] {
+        if street < config.initial_state {
+            continue;
+        }
+
+        let options = bet_sizes(config, street);
+        let donk_options = donk_sizes(config, street);
+        if options.iter().any(|o| !o.bet.is_empty())
+            || donk_options.is_some_and(|o| !o.donk.is_empty())
+        {
+            ret.push(TreeTrim::RemoveBets { street });
+        }
+
+        if options.iter().any(|o| !o.raise.is_empty()) {
+            ret.push(TreeTrim::RemoveRaises { street });
+        }
+
+        for (player, options) in options.iter().enumerate() {
+            for (is_raise, sizes) in [(false, &options.bet), (true, &options.raise)] {
+                for &size in sizes {
+                    ret.push(TreeTrim::RemoveBetSize {
+                        street,
+                        player,
+                        is_raise,
+                        size,
+                    });
+                }
+            }
+        }
+
+        if let Some(options) = donk_options {
+            for &size in &options.donk {
+                ret.push(TreeTrim::RemoveDonkSize { street, size });
+            }
+        }
+    }
+
+    ret
+}
+
+/// Builds the action tree with `config` and reapplies `added_lines` and `removed_lines`.
+///
+/// Returns `Err` if some of the lines cannot be reapplied to the new tree.
+pub(super) fn rebuild_action_tree(
+    config: TreeConfig,
+    added_lines: &[Vec<Action>],
+    removed_lines: &[Vec<Action>],
+) -> Result<ActionTree, String> {
+    let mut tree = ActionTree::new(config)?;
+    for line in added_lines {
+        tree.add_line(line)
+            .map_err(|e| format!("Failed to add line {line:?}: {e}"))?;
+    }
+    for line in removed_lines {
+        tree.remove_line(line)
+            .map_err(|e| format!("Failed to remove line {line:?}: {e}"))?;
+    }
+    Ok(tree)
+}
@@ -1,7 +1,14 @@
+mod analysis;
 mod base;
 mod evaluation;
 mod interpreter;
+mod locking;
+mod memory_plan;
 mod node;
+mod review;
+mod simulation;
+mod storage;
+mod translation;
 
 #[cfg(feature = "bincode")]
 mod serialization;
@@ -9,10 +16,21 @@ mod serialization;
 #[cfg(test)]
 mod tests;
 
+pub use analysis::{
+    diff_solutions, BetSizeAdvice, BetSizeCandidate, ComboDiff, ExploitReport,
+    ExploitabilityReport, NodeDiff, NodeExploitability, NodeGain,
+};
+pub use locking::{ActionPattern, BoardPattern, LinePattern, LockEntry, LockRule, LockSet};
+pub use memory_plan::{MemoryEstimate, TreeTrim, TrimPlan};
+pub use review::{DecisionReview, HandReview, HandReviewConfig};
+pub use simulation::{simulate, simulate_head_to_head, SimulationResult};
+pub use translation::TranslationMethod;
+
 use crate::action_tree::*;
 use crate::card::*;
 use crate::mutex_like::*;
 use std::collections::BTreeMap;
+use storage::*;
 
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
@@ -29,6 +47,54 @@ enum State {
     Solved = 4,
 }
 
+/// Compression mode of the game tree storage, specified in [`PostFlopGame::allocate_memory`].
+///
+/// `bool` values can also be used to specify the mode: `false` means [`Disabled`] and `true`
+/// means [`Int16`].
+///
+/// [`Disabled`]: CompressionMode::Disabled
+/// [`Int16`]: CompressionMode::Int16
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionMode {
+    /// All values are stored as 32-bit floating-point numbers.
+    #[default]
+    Disabled,
+
+    /// All values are stored as 16-bit integers with a 32-bit floating-point scale per node.
+    Int16,
+
+    /// Same as `Int16`, except that the cumulative strategy and the counterfactual values that
+    /// are only used for displaying results are stored as 8-bit integers. The cumulative strategy
+    /// is rounded stochastically so that it keeps converging, but the precision loss is still
+    /// noticeable (especially for hands with low reach), so this mode is intended for coarse
+    /// solves.
+    Int8,
+}
+
+impl CompressionMode {
+    /// Returns the number of bytes per element of `storage1`, `storage2`, `storage_ip`, and
+    /// `storage_chance`.
+    #[inline]
+    fn num_bytes(self) -> [u64; 4] {
+        match self {
+            Self::Disabled => [4, 4, 4, 4],
+            Self::Int16 => [2, 2, 2, 2],
+            Self::Int8 => [1, 2, 1, 1],
+        }
+    }
+}
+
+impl From<bool> for CompressionMode {
+    #[inline]
+    fn from(enable_compression: bool) -> Self {
+        if enable_compression {
+            Self::Int16
+        } else {
+            Self::Disabled
+        }
+    }
+}
+
 /// A struct representing a postflop game.
 #[derive(Default)]
 pub struct PostFlopGame {
@@ -84,6 +150,7 @@ pub struct PostFlopGame {
     target_storage_mode: BoardState,
     num_nodes: [u64; 3],
     is_compression_enabled: bool,
+    is_quantization_enabled: bool,
     num_storage: u64,
     num_storage_ip: u64,
     num_storage_chance: u64,
@@ -93,11 +160,12 @@ pub struct PostFlopGame {
     // `storage*` are used as a global storage and are referenced by `PostFlopNode::storage*`.
     // Methods like `PostFlopNode::strategy` define how the storage is used.
     node_arena: Vec<MutexLike<PostFlopNode>>,
-    storage1: Vec<u8>,
-    storage2: Vec<u8>,
-    storage_ip: Vec<u8>,
-    storage_chance: Vec<u8>,
+    storage1: Storage,
+    storage2: Storage,
+    storage_ip: Storage,
+    storage_chance: Storage,
     locking_strategy: BTreeMap<usize, Vec<f32>>,
+    locking_frequencies: BTreeMap<usize, (Vec<f32>, MutexLike<Vec<f32>>)>,
 
     // result interpreter
     action_history: Vec<usize>,
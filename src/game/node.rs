@@ -35,7 +35,7 @@ impl GameNode for PostFlopNode {
     }
 
     #[inline]
-    fn play(&self, action: usize) -> MutexGuardLike<Self> {
+    fn play(&self, action: usize) -> MutexGuardLike<'_, Self> {
         self.children()[action].lock()
     }
 
@@ -106,6 +106,16 @@ impl GameNode for PostFlopNode {
         unsafe { slice::from_raw_parts_mut(self.storage1 as *mut u16, self.num_elements as usize) }
     }
 
+    #[inline]
+    fn strategy_quantized(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.storage1 as *const u8, self.num_elements as usize) }
+    }
+
+    #[inline]
+    fn strategy_quantized_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.storage1, self.num_elements as usize) }
+    }
+
     #[inline]
     fn regrets_compressed(&self) -> &[i16] {
         unsafe { slice::from_raw_parts(self.storage2 as *const i16, self.num_elements as usize) }
@@ -148,6 +158,28 @@ impl GameNode for PostFlopNode {
         unsafe { slice::from_raw_parts_mut(self.storage1 as *mut i16, self.num_elements as usize) }
     }
 
+    #[inline]
+    fn cfvalues_ip_quantized(&self) -> &[i8] {
+        unsafe { slice::from_raw_parts(self.storage3 as *const i8, self.num_elements_ip as usize) }
+    }
+
+    #[inline]
+    fn cfvalues_ip_quantized_mut(&mut self) -> &mut [i8] {
+        unsafe {
+            slice::from_raw_parts_mut(self.storage3 as *mut i8, self.num_elements_ip as usize)
+        }
+    }
+
+    #[inline]
+    fn cfvalues_chance_quantized(&self) -> &[i8] {
+        unsafe { slice::from_raw_parts(self.storage1 as *const i8, self.num_elements as usize) }
+    }
+
+    #[inline]
+    fn cfvalues_chance_quantized_mut(&mut self) -> &mut [i8] {
+        unsafe { slice::from_raw_parts_mut(self.storage1 as *mut i8, self.num_elements as usize) }
+    }
+
     #[inline]
     fn strategy_scale(&self) -> f32 {
         self.scale1
@@ -0,0 +1,342 @@
+use super::*;
+use crate::hand_history::*;
+
+/// Configuration of [`PostFlopGame::review_hand`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HandReviewConfig {
+    /// Maximum relative difference between a bet or raise amount in the hand history (after
+    /// scaling to the game) and the nearest amount in the game tree. For example, `0.25` allows
+    /// a bet of 60 chips to be mapped onto a bet of 50 chips but not onto 40 chips.
+    ///
+    /// Default value is `0.25`.
+    pub bet_size_tolerance: f64,
+
+    /// Method of translating a bet or raise that cannot be mapped within `bet_size_tolerance`. If
+    /// set, such an action is mapped onto the most probable action given by
+    /// [`PostFlopGame::translate_bet`]; otherwise, the review fails.
+    ///
+    /// Default value is `None`.
+    pub translation: Option<TranslationMethod>,
+}
+
+impl Default for HandReviewConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            bet_size_tolerance: 0.25,
+            translation: None,
+        }
+    }
+}
+
+/// Review of the hero's decisions in a hand history.
+///
+/// See [`PostFlopGame::review_hand`]. The EVs are in the units of the game, i.e., multiply them by
+/// `1.0 / scale` to convert them into the units of the hand history.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HandReview {
+    /// Hand ID of the reviewed hand history.
+    pub hand_id: String,
+
+    /// Hero (0 for OOP, 1 for IP).
+    pub hero: usize,
+
+    /// Private cards of the hero.
+    pub hand: (Card, Card),
+
+    /// Ratio of the game's starting pot to the pot in the hand history.
+    pub scale: f64,
+
+    /// Decisions of the hero in the order of appearance.
+    pub decisions: Vec<DecisionReview>,
+
+    /// Sum of `ev_loss` of all the decisions.
+    pub ev_loss: f32,
+}
+
+/// A decision of the hero, a part of [`HandReview`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionReview {
+    /// Line of the decision node, including chance actions.
+    pub line: Vec<Action>,
+
+    /// Street of the decision.
+    pub street: BoardState,
+
+    /// Action taken in the hand history.
+    pub actual: HistoryAction,
+
+    /// Action in the game tree that `actual` was mapped onto.
+    pub action: Action,
+
+    /// Available actions.
+    pub actions: Vec<Action>,
+
+    /// Solver's strategy of the hero's hand, in the same order as `actions`.
+    pub strategy: Vec<f32>,
+
+    /// EV of each action with the hero's hand, in the same order as `actions`.
+    pub ev: Vec<f32>,
+
+    /// EV lost by taking `action` instead of the best action.
+    pub ev_loss: f32,
+}
+
+impl PostFlopGame {
+    /// Maps the actions of `history` onto the game tree and reviews the decisions of the hero.
+    ///
+    /// The amounts in the hand history are scaled so that the pot at the beginning of the street
+    /// of [`TreeConfig::initial_state`] matches the starting pot of the game, and the actions on
+    /// the earlier streets are ignored. Each bet or raise is mapped onto the nearest bet, raise,
+    /// or all-in action of the tree within `config.bet_size_tolerance`, or translated by
+    /// `config.translation` if set; an all-in action in the hand history is mapped onto the
+    /// all-in action of the tree if available. The review stops when the game reaches a terminal
+    /// node.
+    ///
+    /// For each decision of the hero, the review reports the solver's strategy and the EV of each
+    /// action with the hero's hand. Note that the EVs are zero at the nodes that the solver never
+    /// reaches with the hero's hand. The current node of the game is preserved.
+    ///
+    /// Panics if the game is not solved. Returns `Err` if the hero is unknown, if the hero's hand
+    /// is not in the range, if the boards do not match, or if an action cannot be mapped.
+    ///
+    /// # Examples
+    /// ```
+    /// use postflop_solver::*;
+    ///
+    /// let text = "\
+    /// PokerStars Hand #1001: Hold'em No Limit ($0.50/$1.00 USD) - 2024/01/01 12:00:00 ET
+    /// Table 'Alpha' 6-max Seat #3 is the button
+    /// Seat 1: Alice ($100 in chips)
+    /// Seat 3: Bob ($100 in chips)
+    /// Alice: posts big blind $1
+    /// *** HOLE CARDS ***
+    /// Dealt to Bob [Ah Kd]
+    /// Bob: raises $2 to $3
+    /// Alice: calls $2
+    /// *** FLOP *** [Qs 7h 2c]
+    /// Alice: checks
+    /// Bob: checks
+    /// *** TURN *** [Qs 7h 2c] [9d]
+    /// Alice: checks
+    /// Bob: checks
+    /// *** RIVER *** [Qs 7h 2c 9d] [3s]
+    /// Alice: bets $97 and is all-in
+    /// Bob: folds
+    /// *** SUMMARY ***
+    /// ";
+    /// let history = text.parse::<HandHistory>().unwrap();
+    ///
+    /// let card_config = CardConfig {
+    ///     range: ["QQ,77,22,AJo,KTo".parse().unwrap(), "AKo,JJ,TT".parse().unwrap()],
+    ///     flop: flop_from_str("Qs7h2c").unwrap(),
+    ///     turn: card_from_str("9d").unwrap(),
+    ///     river: card_from_str("3s").unwrap(),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let tree_config = TreeConfig {
+    ///     initial_state: BoardState::River,
+    ///     starting_pot: 60,
+    ///     effective_stack: 970,
+    ///     river_bet_sizes: [("50%, a", "").try_into().unwrap(), Default::default()],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let action_tree = ActionTree::new(tree_config).unwrap();
+    /// let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    /// game.allocate_memory(false);
+    /// solve(&mut game, 1000, 0.01, false);
+    ///
+    /// let review = game.review_hand(&history, &Default::default()).unwrap();
+    /// assert_eq!(review.scale, 10.0);
+    /// assert_eq!(review.decisions.len(), 1);
+    ///
+    /// let decision = &review.decisions[0];
+    /// assert_eq!(decision.line, vec![Action::AllIn(970)]);
+    /// assert_eq!(decision.actions, vec![Action::Fold, Action::Call]);
+    /// assert_eq!(decision.action, Action::Fold);
+    /// ```
+    pub fn review_hand(
+        &mut self,
+        history: &HandHistory,
+        config: &HandReviewConfig,
+    ) -> Result<HandReview, String> {
+        if self.state != State::Solved {
+            panic!("Game is not solved");
+        }
+
+        let (hero, hand) = match (history.hero, history.hero_cards) {
+            (Some(hero), Some(hand)) => (hero, hand),
+            _ => return Err("Hero did not see the flop".to_string()),
+        };
+
+        let hand_index = self.private_cards[hero]
+            .binary_search(&hand)
+            .map_err(|_| "Hero hand is not in the range".to_string())?;
+
+        let mut history_flop = history.board.get(..3).unwrap_or_default().to_vec();
+        let mut game_flop = self.card_config.flop;
+        history_flop.sort_unstable();
+        game_flop.sort_unstable();
+        let turn = self.card_config.turn;
+        let river = self.card_config.river;
+        if history_flop != game_flop
+            || (turn != NOT_DEALT && history.board.get(3) != Some(&turn))
+            || (river != NOT_DEALT && history.board.get(4) != Some(&river))
+        {
+            return Err("Boards do not match".to_string());
+        }
+
+        let initial_state = self.tree_config.initial_state;
+        let pot = history
+            .pots
+            .get(initial_state as usize)
+            .ok_or_else(|| "Hand did not reach the initial street of the game".to_string())?;
+        let scale = self.tree_config.starting_pot as f64 / pot;
+
+        let action_history = self.action_history.clone();
+        self.back_to_root();
+        let result = self.review_hand_internal(history, hero, hand_index, scale, config);
+        self.apply_history(&action_history);
+
+        let decisions = result?;
+        Ok(HandReview {
+            hand_id: history.hand_id.clone(),
+            hero,
+            hand,
+            scale,
+            ev_loss: decisions.iter().map(|d| d.ev_loss).sum(),
+            decisions,
+        })
+    }
+
+    /// Plays the actions of `history` from the root and collects the decisions of the hero.
+    fn review_hand_internal(
+        &mut self,
+        history: &HandHistory,
+        hero: usize,
+        hand_index: usize,
+        scale: f64,
+        config: &HandReviewConfig,
+    ) -> Result<Vec<DecisionReview>, String> {
+        let initial_state = self.tree_config.initial_state;
+        let mut line = Vec::new();
+        let mut decisions = Vec::new();
+
+        for entry in history.actions.iter().filter(|e| e.street >= initial_state) {
+            while self.is_chance_node() {
+                let card = *history
+                    .board
+                    .get(self.current_board().len())
+                    .ok_or_else(|| format!("Board card not found at {line:?}"))?;
+                self.play(card as usize);
+                line.push(Action::Chance(card));
+            }
+
+            if self.is_terminal_node() {
+                break;
+            }
+
+            let street = self.current_board().len() - 3;
+            if entry.street as usize != street || entry.player != self.current_player() {
+                return Err(format!("Action order does not match at {line:?}"));
+            }
+
+            let actions = self.available_actions();
+            let action_index = map_history_action(
+                &actions,
+                entry,
+                scale,
+                config.bet_size_tolerance,
+            )
+            .or_else(|| match (entry.action, config.translation) {
+                (HistoryAction::Bet(amount) | HistoryAction::Raise(amount), Some(method)) => self
+                    .translate_bet(amount * scale, method)
+                    .into_iter()
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(index, _)| index),
+                _ => None,
+            });
+
+            let Some(action_index) = action_index else {
+                let action = entry.action;
+                return Err(format!(
+                    "Action {action:?} does not match any action at {line:?}"
+                ));
+            };
+
+            let action = actions[action_index];
+            if entry.player == hero {
+                self.cache_normalized_weights();
+                let num_hands = self.private_cards[hero].len();
+                let strategy = self.strategy();
+                let ev = self.expected_values_detail(hero);
+                let of_hand = |values: &[f32]| {
+                    (0..actions.len())
+                        .map(|action| values[action * num_hands + hand_index])
+                        .collect::<Vec<_>>()
+                };
+
+                let strategy = of_hand(&strategy);
+                let ev = of_hand(&ev);
+                let max_ev = ev.iter().fold(f32::MIN, |acc, &x| acc.max(x));
+                decisions.push(DecisionReview {
+                    line: line.clone(),
+                    street: entry.street,
+                    actual: entry.action,
+                    action,
+                    ev_loss: max_ev - ev[action_index],
+                    actions,
+                    strategy,
+                    ev,
+                });
+            }
+
+            line.push(action);
+            self.play(action_index);
+        }
+
+        Ok(decisions)
+    }
+}
+
+/// Returns the index of the action in `actions` that `entry` is mapped onto.
+fn map_history_action(
+    actions: &[Action],
+    entry: &HistoryEntry,
+    scale: f64,
+    tolerance: f64,
+) -> Option<usize> {
+    let find = |target: Action| actions.iter().position(|&action| action == target);
+
+    match entry.action {
+        HistoryAction::Fold => find(Action::Fold),
+        HistoryAction::Check => find(Action::Check),
+        HistoryAction::Call => find(Action::Call),
+        HistoryAction::Bet(amount) | HistoryAction::Raise(amount) => {
+            if entry.is_all_in {
+                let all_in = actions
+                    .iter()
+                    .position(|action| matches!(action, Action::AllIn(_)));
+                if all_in.is_some() {
+                    return all_in;
+                }
+            }
+
+            let amount = amount * scale;
+            actions
+                .iter()
+                .enumerate()
+                .filter_map(|(i, &action)| match action {
+                    Action::Bet(x) | Action::Raise(x) | Action::AllIn(x) => {
+                        Some((i, (x as f64 - amount).abs()))
+                    }
+                    _ => None,
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .filter(|&(_, diff)| diff <= tolerance * amount)
+                .map(|(i, _)| i)
+        }
+    }
+}
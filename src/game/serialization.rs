@@ -2,7 +2,11 @@ use super::*;
 
 use crate::interface::*;
 use crate::utility::*;
+use std::array;
 use std::cell::Cell;
+use std::io::{Read, Write};
+use std::iter;
+use std::ops::Range;
 use std::ptr;
 
 use bincode::{
@@ -11,6 +15,38 @@ use bincode::{
     error::{DecodeError, EncodeError},
 };
 
+/// A street section of the game.
+///
+/// The section of the initial street is the game encoded as if the target storage mode were the
+/// initial street. The sections of the later streets contain the locking strategies and
+/// frequencies and the nodes of the street without their storages, which are stored in the
+/// subtree sections.
+struct StreetSection<'a> {
+    game: &'a PostFlopGame,
+    street: BoardState,
+}
+
+/// The nodes of a chance node child on the street of the child, which are the unit of partial
+/// loading: the child itself and its descendants on the same street.
+pub(crate) struct Subtree {
+    root: usize,
+    descendants: Range<usize>,
+}
+
+impl Subtree {
+    /// Returns the indices of the nodes in the order of the node arena.
+    #[inline]
+    fn nodes(&self) -> impl Iterator<Item = usize> {
+        iter::once(self.root).chain(self.descendants.clone())
+    }
+}
+
+/// A subtree section of the game, which contains the storages of the nodes of a [`Subtree`].
+struct SubtreeSection<'a> {
+    game: &'a PostFlopGame,
+    subtree: &'a Subtree,
+}
+
 impl PostFlopGame {
     /// Returns the storage mode of this instance.
     ///
@@ -46,76 +82,509 @@ impl PostFlopGame {
     /// Returns the memory usage when the target storage mode is used for serialization.
     #[inline]
     pub fn target_memory_usage(&self) -> u64 {
-        match self.target_storage_mode {
-            BoardState::River => match self.is_compression_enabled {
-                false => self.memory_usage().0,
-                true => self.memory_usage().1,
-            },
+        self.memory_usage_until(self.target_storage_mode)
+    }
+
+    /// Returns the memory usage when the nodes up to the given street are loaded.
+    pub(crate) fn memory_usage_until(&self, mode: BoardState) -> u64 {
+        match mode {
+            BoardState::River => self.memory_usage_with(self.compression_mode()),
             _ => {
-                let num_target_storage = self.num_target_storage();
-                num_target_storage.iter().map(|&x| x as u64).sum::<u64>() + self.misc_memory_usage
+                let num_storage = self.num_storage_until(mode);
+                num_storage.iter().map(|&x| x as u64).sum::<u64>() + self.misc_memory_usage
             }
         }
     }
 
-    /// Returns the number of storage elements required for the target storage mode.
-    fn num_target_storage(&self) -> [usize; 4] {
+    /// Returns the number of storage elements required for serialization with the given mode.
+    fn num_target_storage(&self, mode: BoardState) -> [usize; 4] {
+        let num_storage = self.num_storage_until(mode);
+        if mode == BoardState::River {
+            // omit storing the counterfactual values
+            return [num_storage[0], 0, 0, 0];
+        }
+        num_storage
+    }
+
+    /// Returns the number of bytes of each storage used by the nodes up to the given street.
+    pub(super) fn num_storage_until(&self, mode: BoardState) -> [usize; 4] {
         if self.state <= State::TreeBuilt {
             return [0; 4];
         }
 
-        let num_bytes = if self.is_compression_enabled { 2 } else { 4 };
-        if self.target_storage_mode == BoardState::River {
-            // omit storing the counterfactual values
-            return [num_bytes * self.num_storage as usize, 0, 0, 0];
+        let num_bytes = self.compression_mode().num_bytes().map(|x| x as usize);
+        if mode == BoardState::River {
+            return [
+                num_bytes[0] * self.num_storage as usize,
+                num_bytes[1] * self.num_storage as usize,
+                num_bytes[2] * self.num_storage_ip as usize,
+                num_bytes[3] * self.num_storage_chance as usize,
+            ];
         }
 
-        let mut node_index = match self.target_storage_mode {
-            BoardState::Flop => self.num_nodes[0],
-            _ => self.num_nodes[0] + self.num_nodes[1],
-        } as usize;
+        let mut node_index = self.node_range(mode).end;
 
         let mut num_storage = [0; 4];
 
-        while num_storage.iter().any(|&x| x == 0) {
+        while num_storage.contains(&0) {
             node_index -= 1;
             let node = self.node_arena[node_index].lock();
             if num_storage[0] == 0 && !node.is_terminal() && !node.is_chance() {
-                let offset = unsafe { node.storage1.offset_from(self.storage1.as_ptr()) };
+                let offset1 = unsafe { node.storage1.offset_from(self.storage1.as_ptr()) };
+                let offset2 = unsafe { node.storage2.offset_from(self.storage2.as_ptr()) };
                 let offset_ip = unsafe { node.storage3.offset_from(self.storage_ip.as_ptr()) };
-                let len = num_bytes * node.num_elements as usize;
-                let len_ip = num_bytes * node.num_elements_ip as usize;
-                num_storage[0] = offset as usize + len;
-                num_storage[1] = offset as usize + len;
+                let len1 = num_bytes[0] * node.num_elements as usize;
+                let len2 = num_bytes[1] * node.num_elements as usize;
+                let len_ip = num_bytes[2] * node.num_elements_ip as usize;
+                num_storage[0] = offset1 as usize + len1;
+                num_storage[1] = offset2 as usize + len2;
                 num_storage[2] = offset_ip as usize + len_ip;
             }
             if num_storage[3] == 0 && node.is_chance() {
                 let offset = unsafe { node.storage1.offset_from(self.storage_chance.as_ptr()) };
-                let len = num_bytes * node.num_elements as usize;
+                let len = num_bytes[3] * node.num_elements as usize;
                 num_storage[3] = offset as usize + len;
             }
         }
 
         num_storage
     }
+
+    /// Writes the section of the given street into the writer.
+    ///
+    /// The sections must be written in order from the initial street, and the section of a later
+    /// street must be followed by its subtree sections.
+    pub(crate) fn save_street_section<W: Write>(
+        &self,
+        writer: &mut W,
+        street: BoardState,
+    ) -> Result<(), String> {
+        if street < self.tree_config.initial_state || street > self.storage_mode {
+            return Err(format!("Street is not stored in the game: {street:?}"));
+        }
+
+        let section = StreetSection { game: self, street };
+        bincode::encode_into_std_write(section, writer, bincode::config::standard())
+            .map_err(|e| format!("Failed to write section: {e}"))?;
+
+        Ok(())
+    }
+
+    /// Writes the section of the given subtree into the writer.
+    pub(crate) fn save_subtree_section<W: Write>(
+        &self,
+        writer: &mut W,
+        subtree: &Subtree,
+    ) -> Result<(), String> {
+        let section = SubtreeSection {
+            game: self,
+            subtree,
+        };
+        bincode::encode_into_std_write(section, writer, bincode::config::standard())
+            .map_err(|e| format!("Failed to write section: {e}"))?;
+
+        Ok(())
+    }
+
+    /// Reads the section of the next street from the reader and appends its nodes to the game.
+    ///
+    /// The game must be loaded from the section of the initial street, and the sections must be
+    /// read in order. The storages of the appended nodes are not loaded until their subtree
+    /// sections are read by `load_subtrees`.
+    pub(crate) fn load_street_section<R: Read>(
+        &mut self,
+        reader: &mut R,
+        street: BoardState,
+    ) -> Result<(), String> {
+        let config = bincode::config::standard();
+        let err = |e: DecodeError| format!("Failed to read section: {e}");
+
+        let range = self.node_range(street);
+        if street <= self.storage_mode || self.node_arena.len() != range.start {
+            return Err(format!("Section cannot be appended: {street:?}"));
+        }
+
+        if self.state < State::MemoryAllocated {
+            return Err("Section does not match the game".to_string());
+        }
+
+        // decode the whole section before modifying the game, so that an error leaves the game
+        // unchanged
+        let locking_strategy: BTreeMap<usize, Vec<f32>> =
+            bincode::decode_from_std_read(reader, config).map_err(err)?;
+        let locking_frequencies: BTreeMap<usize, Vec<f32>> =
+            bincode::decode_from_std_read(reader, config).map_err(err)?;
+
+        // the nodes are stored without their storages
+        PTR_BASE_MUT.with(|c| c.set([ptr::null_mut(); 3]));
+        CHANCE_BASE_MUT.with(|c| c.set(ptr::null_mut()));
+        DECODE_VERSION.with(|c| c.set(VERSIONS.len() - 1));
+        let nodes: Vec<MutexLike<PostFlopNode>> =
+            bincode::decode_from_std_read(reader, config).map_err(err)?;
+        if nodes.len() != range.len() {
+            return Err("Section does not match the game".to_string());
+        }
+
+        self.locking_strategy.extend(locking_strategy);
+        self.locking_frequencies
+            .extend(with_multipliers(locking_frequencies));
+        self.node_arena.extend(nodes);
+        self.update_storage_mode();
+
+        Ok(())
+    }
+
+    /// Reads a subtree section from the reader.
+    pub(crate) fn read_subtree_section<R: Read>(reader: &mut R) -> Result<[Vec<u8>; 4], String> {
+        let config = bincode::config::standard();
+        let err = |e: DecodeError| format!("Failed to read section: {e}");
+
+        let mut bytes: [Vec<u8>; 4] = Default::default();
+        for storage in &mut bytes {
+            *storage = bincode::decode_from_std_read(reader, config).map_err(err)?;
+        }
+
+        Ok(bytes)
+    }
+
+    /// Loads the storages of the subtrees from the sections read by `read_subtree_section`.
+    ///
+    /// Each section is given with the root of its subtree. The nodes of the subtrees must be
+    /// appended by `load_street_section`, and the subtrees that are already loaded are skipped.
+    /// The storage mode is updated when all the subtrees of a street are loaded.
+    pub(crate) fn load_subtrees(
+        &mut self,
+        sections: Vec<(usize, [Vec<u8>; 4])>,
+    ) -> Result<(), String> {
+        let mismatch = || "Section does not match the game".to_string();
+
+        // validate all the sections before modifying the game, so that an error leaves the game
+        // unchanged
+        let mut subtrees = Vec::with_capacity(sections.len());
+        for (root, bytes) in sections {
+            if root >= self.node_arena.len()
+                || !matches!(self.node_arena[root].lock().prev_action, Action::Chance(_))
+            {
+                return Err(mismatch());
+            }
+
+            if self.is_subtree_loaded(root) {
+                continue;
+            }
+
+            let subtree = self.subtree(root);
+            let mut lengths = [0; 4];
+            for index in subtree.nodes() {
+                let node_lengths = self.storage_lengths(&self.node_arena[index].lock());
+                (0..4).for_each(|i| lengths[i] += node_lengths[i]);
+            }
+
+            if lengths != bytes.each_ref().map(Vec::len) {
+                return Err(mismatch());
+            }
+
+            subtrees.push((subtree, bytes));
+        }
+
+        subtrees.sort_unstable_by_key(|(subtree, _)| subtree.root);
+        subtrees.dedup_by_key(|(subtree, _)| subtree.root);
+
+        // split the sections into the storages of each node
+        let mut chunks = Vec::new();
+        for (subtree, bytes) in &subtrees {
+            let mut offsets = [0; 4];
+            for index in subtree.nodes() {
+                let lengths = self.storage_lengths(&self.node_arena[index].lock());
+                let chunk: [&[u8]; 4] =
+                    array::from_fn(|i| &bytes[i][offsets[i]..offsets[i] + lengths[i]]);
+                (0..4).for_each(|i| offsets[i] += lengths[i]);
+                chunks.push((index, chunk));
+            }
+        }
+
+        chunks.sort_unstable_by_key(|&(index, _)| index);
+
+        // the storages of the loaded nodes are laid out in the order of the node arena
+        let mut insertions: [Vec<(usize, &[u8])>; 4] = Default::default();
+        let mut cursor = [0; 4];
+        let mut chunk_iter = chunks.iter().peekable();
+        for (index, node) in self.node_arena.iter().enumerate() {
+            let Some(&&(chunk_index, chunk)) = chunk_iter.peek() else {
+                break;
+            };
+
+            if index == chunk_index {
+                (0..4).for_each(|i| insertions[i].push((cursor[i], chunk[i])));
+                chunk_iter.next();
+            } else if self.is_subtree_loaded(index) {
+                let lengths = self.storage_lengths(&node.lock());
+                (0..4).for_each(|i| cursor[i] += lengths[i]);
+            }
+        }
+
+        // grow all the storages before moving any bytes, so that a failure to resize a storage
+        // backed by a file leaves the game unchanged
+        let mut storages = [
+            &mut self.storage1,
+            &mut self.storage2,
+            &mut self.storage_ip,
+            &mut self.storage_chance,
+        ];
+        let lengths = storages.each_ref().map(|storage| storage.len());
+        let mut result = Ok(());
+        for (i, chunks) in insertions.iter().enumerate() {
+            let additional = chunks.iter().map(|(_, bytes)| bytes.len()).sum::<usize>();
+            result = storages[i].resize(lengths[i] + additional);
+            if result.is_err() {
+                for (storage, &len) in storages.iter_mut().zip(&lengths).take(i) {
+                    let _ = storage.resize(len);
+                }
+                break;
+            }
+        }
+
+        if result.is_ok() {
+            for (storage, chunks) in storages.into_iter().zip(&insertions) {
+                storage.insert_chunks(chunks);
+            }
+        } else {
+            // the storages may have been reallocated
+            self.assign_loaded_storage();
+            return result;
+        }
+
+        // mark the nodes as loaded, and then assign the pointers
+        for &(index, _) in &chunks {
+            let mut node = self.node_arena[index].lock();
+            if !node.is_terminal() {
+                node.storage1 = ptr::NonNull::dangling().as_ptr();
+            }
+        }
+
+        self.assign_loaded_storage();
+        self.update_storage_mode();
+
+        Ok(())
+    }
+
+    /// Returns the roots of the subtrees of the given street, which are the children of the chance
+    /// nodes on the previous street in the order of the node arena.
+    ///
+    /// The nodes of the previous street must be loaded.
+    pub(crate) fn subtree_roots(&self, street: BoardState) -> Vec<usize> {
+        if street <= self.tree_config.initial_state {
+            return Vec::new();
+        }
+
+        let prev_street = match street {
+            BoardState::River => BoardState::Turn,
+            _ => BoardState::Flop,
+        };
+
+        let mut roots = Vec::new();
+        for index in self.node_range(prev_street) {
+            let node = self.node_arena[index].lock();
+            if node.is_chance() {
+                let start = index + node.children_offset as usize;
+                roots.extend(start..start + node.num_children as usize);
+            }
+        }
+
+        roots
+    }
+
+    /// Returns the subtrees of the given street in the order of [`subtree_roots`].
+    ///
+    /// [`subtree_roots`]: #method.subtree_roots
+    pub(crate) fn subtrees(&self, street: BoardState) -> Vec<Subtree> {
+        let roots = self.subtree_roots(street);
+        roots.into_iter().map(|root| self.subtree(root)).collect()
+    }
+
+    /// Returns the subtree of the given root.
+    fn subtree(&self, root: usize) -> Subtree {
+        // the descendants on the same street are allocated contiguously (see `push_actions`)
+        let (mut start, mut end) = (usize::MAX, 0);
+        let mut stack = vec![root];
+        while let Some(index) = stack.pop() {
+            if index != root {
+                start = start.min(index);
+                end = end.max(index + 1);
+            }
+
+            let node = self.node_arena[index].lock();
+            if !node.is_terminal() && !node.is_chance() {
+                let start = index + node.children_offset as usize;
+                stack.extend(start..start + node.num_children as usize);
+            }
+        }
+
+        let descendants = if start < end { start..end } else { 0..0 };
+        Subtree { root, descendants }
+    }
+
+    /// Returns the lengths of the storages of the node in bytes, in the order of `storage1`,
+    /// `storage2`, `storage_ip`, and `storage_chance`.
+    fn storage_lengths(&self, node: &PostFlopNode) -> [usize; 4] {
+        let num_bytes = self.compression_mode().num_bytes().map(|x| x as usize);
+        if node.is_terminal() {
+            [0; 4]
+        } else if node.is_chance() {
+            [0, 0, 0, num_bytes[3] * node.num_elements as usize]
+        } else {
+            [
+                num_bytes[0] * node.num_elements as usize,
+                num_bytes[1] * node.num_elements as usize,
+                num_bytes[2] * node.num_elements_ip as usize,
+                0,
+            ]
+        }
+    }
+
+    /// Returns the storages of the nodes of the subtree.
+    fn subtree_storages(&self, subtree: &Subtree) -> [Vec<u8>; 4] {
+        let mut bytes: [Vec<u8>; 4] = Default::default();
+        for index in subtree.nodes() {
+            let node = self.node_arena[index].lock();
+            let lengths = self.storage_lengths(&node);
+            if node.is_terminal() {
+                // do nothing
+            } else if node.is_chance() {
+                let offset = unsafe { node.storage1.offset_from(self.storage_chance.as_ptr()) };
+                let offset = offset as usize;
+                bytes[3].extend_from_slice(&self.storage_chance[offset..offset + lengths[3]]);
+            } else {
+                let offset1 = unsafe { node.storage1.offset_from(self.storage1.as_ptr()) };
+                let offset2 = unsafe { node.storage2.offset_from(self.storage2.as_ptr()) };
+                let offset_ip = unsafe { node.storage3.offset_from(self.storage_ip.as_ptr()) };
+                let (offset1, offset2, offset_ip) =
+                    (offset1 as usize, offset2 as usize, offset_ip as usize);
+                bytes[0].extend_from_slice(&self.storage1[offset1..offset1 + lengths[0]]);
+                bytes[1].extend_from_slice(&self.storage2[offset2..offset2 + lengths[1]]);
+                bytes[2].extend_from_slice(&self.storage_ip[offset_ip..offset_ip + lengths[2]]);
+            }
+        }
+
+        bytes
+    }
+
+    /// Assigns the storage pointers of the loaded nodes with the same layout as
+    /// `allocate_memory_nodes`, skipping the nodes whose storages are not loaded.
+    fn assign_loaded_storage(&mut self) {
+        let num_bytes = self.compression_mode().num_bytes().map(|x| x as usize);
+        let mut action_counter = [0; 2];
+        let mut ip_counter = 0;
+        let mut chance_counter = 0;
+
+        for node in &self.node_arena {
+            let mut node = node.lock();
+            if node.is_terminal() || node.storage1.is_null() {
+                // do nothing
+            } else if node.is_chance() {
+                unsafe {
+                    let ptr = self.storage_chance.as_mut_ptr();
+                    node.storage1 = ptr.add(chance_counter);
+                }
+                chance_counter += num_bytes[3] * node.num_elements as usize;
+            } else {
+                unsafe {
+                    let ptr1 = self.storage1.as_mut_ptr();
+                    let ptr2 = self.storage2.as_mut_ptr();
+                    let ptr3 = self.storage_ip.as_mut_ptr();
+                    node.storage1 = ptr1.add(action_counter[0]);
+                    node.storage2 = ptr2.add(action_counter[1]);
+                    node.storage3 = ptr3.add(ip_counter);
+                }
+                action_counter[0] += num_bytes[0] * node.num_elements as usize;
+                action_counter[1] += num_bytes[1] * node.num_elements as usize;
+                ip_counter += num_bytes[2] * node.num_elements_ip as usize;
+            }
+        }
+    }
+
+    /// Advances the storage mode over the streets whose nodes and subtrees are all loaded.
+    fn update_storage_mode(&mut self) {
+        for street in [BoardState::Turn, BoardState::River] {
+            if street as usize == self.storage_mode as usize + 1
+                && self.has_street_nodes(street)
+                && self
+                    .subtree_roots(street)
+                    .into_iter()
+                    .all(|root| self.is_subtree_loaded(root))
+            {
+                self.storage_mode = street;
+                self.target_storage_mode = street;
+            }
+        }
+    }
+
+    /// Reads the version string and the card and tree configurations from the beginning of an
+    /// encoded game.
+    ///
+    /// The configurations are `None` if the game was encoded by an unsupported version.
+    pub(crate) fn read_configs<R: Read>(
+        reader: &mut R,
+    ) -> Result<(String, Option<(CardConfig, TreeConfig)>), String> {
+        let config = bincode::config::standard();
+        let err = |e: DecodeError| format!("Failed to read configurations: {e}");
+
+        let version: String = bincode::decode_from_std_read(reader, config).map_err(err)?;
+        let Some(version_index) = VERSIONS.iter().position(|&v| v == version) else {
+            return Ok((version, None));
+        };
+
+        DECODE_VERSION.with(|c| c.set(version_index));
+        let _state: State = bincode::decode_from_std_read(reader, config).map_err(err)?;
+        let configs: VersionedConfigs =
+            bincode::decode_from_std_read(reader, config).map_err(err)?;
+
+        Ok((version, Some((configs.0, configs.1))))
+    }
 }
 
-static VERSION_STR: &str = "2023-03-19";
+/// Supported versions of the encoded layout of `PostFlopGame`, from oldest to newest.
+///
+/// - `2023-03-19`: Initial layout.
+/// - `2026-10-18`: Adds the new fields of `CardConfig` and `TreeConfig`, the quantization flag, the
+///   `locking_frequencies` map (in the full game and in each street section), and the `storage2`
+///   offsets of the nodes.
+///
+/// Games are always encoded with the newest layout. When the layout is changed, append a new
+/// version and keep decoding the older ones.
+const VERSIONS: [&str; 2] = ["2023-03-19", "2026-10-18"];
+
+static VERSION_STR: &str = VERSIONS[VERSIONS.len() - 1];
+
+/// Card and tree configurations decoded with the layout of `DECODE_VERSION`.
+struct VersionedConfigs(CardConfig, TreeConfig);
 
 thread_local! {
-    static PTR_BASE: Cell<[*const u8; 2]> = const {Cell::new([ptr::null(); 2])};
+    static DECODE_VERSION: Cell<usize> = const {Cell::new(VERSIONS.len() - 1)};
+    static PTR_BASE: Cell<[*const u8; 3]> = const {Cell::new([ptr::null(); 3])};
     static CHANCE_BASE: Cell<*const u8> = const {Cell::new(ptr::null())};
     static PTR_BASE_MUT: Cell<[*mut u8; 3]> = const {Cell::new([ptr::null_mut(); 3])};
     static CHANCE_BASE_MUT: Cell<*mut u8> = const {Cell::new(ptr::null_mut())};
 }
 
 impl Encode for PostFlopGame {
+    #[inline]
     fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        self.encode_truncated(encoder, self.target_storage_mode)
+    }
+}
+
+impl PostFlopGame {
+    /// Encodes the game as if the target storage mode were `mode`.
+    fn encode_truncated<E: Encoder>(
+        &self,
+        encoder: &mut E,
+        mode: BoardState,
+    ) -> Result<(), EncodeError> {
         if self.state <= State::Uninitialized {
             return Err(EncodeError::Other("Game is not successfully initialized"));
         }
 
-        let num_storage = self.num_target_storage();
+        let num_storage = self.num_target_storage(mode);
 
         // version
         VERSION_STR.to_string().encode(encoder)?;
@@ -127,9 +596,10 @@ impl Encode for PostFlopGame {
         self.added_lines.encode(encoder)?;
         self.removed_lines.encode(encoder)?;
         self.action_root.encode(encoder)?;
-        self.target_storage_mode.encode(encoder)?;
+        mode.encode(encoder)?;
         self.num_nodes.encode(encoder)?;
         self.is_compression_enabled.encode(encoder)?;
+        self.is_quantization_enabled.encode(encoder)?;
         self.num_storage.encode(encoder)?;
         self.num_storage_ip.encode(encoder)?;
         self.num_storage_chance.encode(encoder)?;
@@ -139,23 +609,48 @@ impl Encode for PostFlopGame {
         self.storage_ip[0..num_storage[2]].encode(encoder)?;
         self.storage_chance[0..num_storage[3]].encode(encoder)?;
 
-        let num_nodes = match self.target_storage_mode {
-            BoardState::Flop => self.num_nodes[0] as usize,
-            BoardState::Turn => (self.num_nodes[0] + self.num_nodes[1]) as usize,
-            BoardState::River => self.node_arena.len(),
-        };
+        let num_nodes = self.node_range(mode).end;
 
         // locking strategy (need to filter)
         let mut locking_strategy = self.locking_strategy.clone();
         locking_strategy.retain(|&i, _| i < num_nodes);
         locking_strategy.encode(encoder)?;
 
-        // store base pointers
+        // locking frequencies (the multipliers are not saved)
+        self.encode_locking_frequencies(encoder, 0..num_nodes)?;
+
+        // game tree
+        self.store_base_pointers();
+        self.node_arena[0..num_nodes].encode(encoder)?;
+
+        Ok(())
+    }
+
+    /// Encodes the locking frequencies of the nodes in `range` without the multipliers.
+    fn encode_locking_frequencies<E: Encoder>(
+        &self,
+        encoder: &mut E,
+        range: Range<usize>,
+    ) -> Result<(), EncodeError> {
+        let locking_frequencies = self
+            .locking_frequencies
+            .range(range)
+            .map(|(&i, (frequencies, _))| (i, frequencies))
+            .collect::<BTreeMap<_, _>>();
+        locking_frequencies.encode(encoder)
+    }
+
+    /// Stores the base pointers of the storages for encoding the nodes.
+    fn store_base_pointers(&self) {
         PTR_BASE.with(|c| {
             if self.state >= State::MemoryAllocated {
-                c.set([self.storage1.as_ptr(), self.storage_ip.as_ptr()]);
+                c.set([
+                    self.storage1.as_ptr(),
+                    self.storage2.as_ptr(),
+                    self.storage_ip.as_ptr(),
+                ]);
             } else {
-                c.set([ptr::null(); 2]);
+                c.set([ptr::null(); 3]);
             }
         });
 
@@ -166,35 +661,106 @@ impl Encode for PostFlopGame {
                 c.set(ptr::null());
             }
         });
+    }
 
-        // game tree
-        self.node_arena[0..num_nodes].encode(encoder)?;
+    /// Stores the base pointers of the storages for decoding the nodes.
+    fn store_base_pointers_mut(&mut self) {
+        PTR_BASE_MUT.with(|c| {
+            if self.state >= State::MemoryAllocated {
+                c.set([
+                    self.storage1.as_mut_ptr(),
+                    self.storage2.as_mut_ptr(),
+                    self.storage_ip.as_mut_ptr(),
+                ]);
+            } else {
+                c.set([ptr::null_mut(); 3]);
+            }
+        });
+
+        CHANCE_BASE_MUT.with(|c| {
+            if self.state >= State::MemoryAllocated {
+                c.set(self.storage_chance.as_mut_ptr());
+            } else {
+                c.set(ptr::null_mut());
+            }
+        });
+    }
+}
+
+impl Encode for StreetSection<'_> {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        let game = self.game;
+        if self.street == game.tree_config.initial_state {
+            return game.encode_truncated(encoder, self.street);
+        }
+
+        // locking strategy
+        let range = game.node_range(self.street);
+        let locking_strategy = game
+            .locking_strategy
+            .range(range.clone())
+            .map(|(&i, strategy)| (i, strategy.clone()))
+            .collect::<BTreeMap<_, _>>();
+        locking_strategy.encode(encoder)?;
+
+        // locking frequencies
+        game.encode_locking_frequencies(encoder, range.clone())?;
+
+        // game tree without the storages (same encoding as `Vec<MutexLike<PostFlopNode>>`)
+        (range.len() as u64).encode(encoder)?;
+        for node in &game.node_arena[range] {
+            let node = PostFlopNode {
+                storage1: ptr::null_mut(),
+                storage2: ptr::null_mut(),
+                storage3: ptr::null_mut(),
+                ..*node.lock()
+            };
+            node.encode(encoder)?;
+        }
 
         Ok(())
     }
 }
 
+impl Encode for SubtreeSection<'_> {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        for bytes in self.game.subtree_storages(self.subtree) {
+            bytes.encode(encoder)?;
+        }
+        Ok(())
+    }
+}
+
 impl Decode for PostFlopGame {
     fn decode<D: Decoder>(decoder: &mut D) -> Result<Self, DecodeError> {
         // version check
         let version = String::decode(decoder)?;
-        if version != VERSION_STR {
+        let Some(version_index) = VERSIONS.iter().position(|&v| v == version) else {
             return Err(DecodeError::OtherString(format!(
-                "Version mismatch: expected '{VERSION_STR}', but got '{version}'"
+                "Version mismatch: expected one of {VERSIONS:?}, but got '{version}'"
             )));
-        }
+        };
+
+        DECODE_VERSION.with(|c| c.set(version_index));
+        let is_2023_03_19 = version_index == 0;
 
         // game instance
+        let state = Decode::decode(decoder)?;
+        let VersionedConfigs(card_config, tree_config) = Decode::decode(decoder)?;
         let mut game = Self {
-            state: Decode::decode(decoder)?,
-            card_config: Decode::decode(decoder)?,
-            tree_config: Decode::decode(decoder)?,
+            state,
+            card_config,
+            tree_config,
             added_lines: Decode::decode(decoder)?,
             removed_lines: Decode::decode(decoder)?,
             action_root: Decode::decode(decoder)?,
             storage_mode: Decode::decode(decoder)?,
             num_nodes: Decode::decode(decoder)?,
             is_compression_enabled: Decode::decode(decoder)?,
+            is_quantization_enabled: match is_2023_03_19 {
+                true => false,
+                false => Decode::decode(decoder)?,
+            },
             num_storage: Decode::decode(decoder)?,
             num_storage_ip: Decode::decode(decoder)?,
             num_storage_chance: Decode::decode(decoder)?,
@@ -204,39 +770,24 @@ impl Decode for PostFlopGame {
             storage_ip: Decode::decode(decoder)?,
             storage_chance: Decode::decode(decoder)?,
             locking_strategy: Decode::decode(decoder)?,
+            locking_frequencies: match is_2023_03_19 {
+                true => BTreeMap::new(),
+                false => with_multipliers(Decode::decode(decoder)?).collect(),
+            },
             ..Default::default()
         };
 
         game.target_storage_mode = game.storage_mode;
         if game.storage_mode == BoardState::River && game.state >= State::MemoryAllocated {
-            let num_bytes = if game.is_compression_enabled { 2 } else { 4 };
-            game.storage2 = vec![0; (num_bytes * game.num_storage) as usize];
-            game.storage_ip = vec![0; (num_bytes * game.num_storage_ip) as usize];
-            game.storage_chance = vec![0; (num_bytes * game.num_storage_chance) as usize];
+            let num_bytes = game.compression_mode().num_bytes();
+            game.storage2 = Storage::zeroed((num_bytes[1] * game.num_storage) as usize);
+            game.storage_ip = Storage::zeroed((num_bytes[2] * game.num_storage_ip) as usize);
+            game.storage_chance =
+                Storage::zeroed((num_bytes[3] * game.num_storage_chance) as usize);
         }
 
-        // store base pointers
-        PTR_BASE_MUT.with(|c| {
-            if game.state >= State::MemoryAllocated {
-                c.set([
-                    game.storage1.as_mut_ptr(),
-                    game.storage2.as_mut_ptr(),
-                    game.storage_ip.as_mut_ptr(),
-                ]);
-            } else {
-                c.set([ptr::null_mut(); 3]);
-            }
-        });
-
-        CHANCE_BASE_MUT.with(|c| {
-            if game.state >= State::MemoryAllocated {
-                c.set(game.storage_chance.as_mut_ptr());
-            } else {
-                c.set(ptr::null_mut());
-            }
-        });
-
         // game tree
+        game.store_base_pointers_mut();
         game.node_arena = Decode::decode(decoder)?;
 
         // initialization
@@ -255,6 +806,16 @@ impl Decode for PostFlopGame {
     }
 }
 
+/// Attaches the initial multipliers to the decoded locking frequencies.
+fn with_multipliers(
+    locking_frequencies: BTreeMap<usize, Vec<f32>>,
+) -> impl Iterator<Item = (usize, (Vec<f32>, MutexLike<Vec<f32>>))> {
+    locking_frequencies.into_iter().map(|(i, frequencies)| {
+        let multipliers = MutexLike::new(vec![1.0; frequencies.len()]);
+        (i, (frequencies, multipliers))
+    })
+}
+
 impl Encode for PostFlopNode {
     fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
         // contents
@@ -283,7 +844,8 @@ impl Encode for PostFlopNode {
                 let bases = PTR_BASE.with(|c| c.get());
                 unsafe {
                     self.storage1.offset_from(bases[0]).encode(encoder)?;
-                    self.storage3.offset_from(bases[1]).encode(encoder)?;
+                    self.storage2.offset_from(bases[1]).encode(encoder)?;
+                    self.storage3.offset_from(bases[2]).encode(encoder)?;
                 }
             }
         }
@@ -323,10 +885,14 @@ impl Decode for PostFlopNode {
         } else {
             let bases = PTR_BASE_MUT.with(|c| c.get());
             if !bases[0].is_null() {
-                let offset = isize::decode(decoder)?;
+                let offset1 = isize::decode(decoder)?;
+                let offset2 = match DECODE_VERSION.with(|c| c.get()) {
+                    0 => offset1, // 2023-03-19: same as `storage1`
+                    _ => isize::decode(decoder)?,
+                };
                 let offset_ip = isize::decode(decoder)?;
-                node.storage1 = unsafe { bases[0].offset(offset) };
-                node.storage2 = unsafe { bases[1].offset(offset) };
+                node.storage1 = unsafe { bases[0].offset(offset1) };
+                node.storage2 = unsafe { bases[1].offset(offset2) };
                 node.storage3 = unsafe { bases[2].offset(offset_ip) };
             }
         }
@@ -334,3 +900,39 @@ impl Decode for PostFlopNode {
         Ok(node)
     }
 }
+
+impl Decode for VersionedConfigs {
+    fn decode<D: Decoder>(decoder: &mut D) -> Result<Self, DecodeError> {
+        if DECODE_VERSION.with(|c| c.get()) > 0 {
+            return Ok(Self(Decode::decode(decoder)?, Decode::decode(decoder)?));
+        }
+
+        // 2023-03-19
+        let card_config = CardConfig {
+            range: Decode::decode(decoder)?,
+            flop: Decode::decode(decoder)?,
+            turn: Decode::decode(decoder)?,
+            river: Decode::decode(decoder)?,
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: Decode::decode(decoder)?,
+            starting_pot: Decode::decode(decoder)?,
+            effective_stack: Decode::decode(decoder)?,
+            rake_rate: Decode::decode(decoder)?,
+            rake_cap: Decode::decode(decoder)?,
+            flop_bet_sizes: Decode::decode(decoder)?,
+            turn_bet_sizes: Decode::decode(decoder)?,
+            river_bet_sizes: Decode::decode(decoder)?,
+            turn_donk_sizes: Decode::decode(decoder)?,
+            river_donk_sizes: Decode::decode(decoder)?,
+            add_allin_threshold: Decode::decode(decoder)?,
+            force_allin_threshold: Decode::decode(decoder)?,
+            merging_threshold: Decode::decode(decoder)?,
+            ..Default::default()
+        };
+
+        Ok(Self(card_config, tree_config))
+    }
+}
@@ -0,0 +1,338 @@
+use super::*;
+use crate::interface::*;
+
+/// Result of a Monte Carlo simulation.
+///
+/// See [`simulate`] and [`simulate_head_to_head`]. Index 0 of each array is OOP and index 1 is IP.
+/// The payoffs are on the same scale as [`compute_current_ev`], i.e., (starting pot) / 2 is
+/// subtracted from the amount each player wins.
+///
+/// [`compute_current_ev`]: crate::compute_current_ev
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SimulationResult {
+    /// Number of simulated hands.
+    pub num_hands: usize,
+
+    /// Mean payoff per hand of each player.
+    pub mean: [f32; 2],
+
+    /// Sample standard deviation of the payoff of each player.
+    pub std_dev: [f32; 2],
+
+    /// Standard error of `mean`.
+    pub std_error: [f32; 2],
+
+    /// 95% confidence interval of the mean payoff of each player, as `(lower, upper)`.
+    pub confidence_interval: [(f32, f32); 2],
+}
+
+/// Maximum number of attempts to deal non-conflicting hands.
+const MAX_DEAL_ATTEMPTS: usize = 10000;
+
+/// Simulates `num_hands` hands of self-play of `game` and reports the mean payoff of each player.
+///
+/// The private hands are dealt from the ranges of `game`, and the remaining board cards are dealt
+/// uniformly from the rest of the deck. Each player samples its actions from the current strategy
+/// of `game`, including the locked strategies. When the players are all-in before the river, the
+/// rest of the board is dealt and the hands go to showdown. The bunching effect is not simulated.
+///
+/// The results are reproducible for the same `seed`. The current node of `game` is preserved.
+///
+/// Panics if the memory is not yet allocated. Returns `Err` if `num_hands` is zero or if the hands
+/// cannot be dealt without conflicts.
+///
+/// # Examples
+/// ```no_run
+/// use postflop_solver::*;
+///
+/// let (mut game, _): (PostFlopGame, _) = load_data_from_file("river.flop", None).unwrap();
+///
+/// let result = simulate(&mut game, 10000, 42).unwrap();
+/// let (lower, upper) = result.confidence_interval[0];
+/// println!("OOP: {:.2} ({:.2} - {:.2})", result.mean[0], lower, upper);
+/// ```
+pub fn simulate(
+    game: &mut PostFlopGame,
+    num_hands: usize,
+    seed: u64,
+) -> Result<SimulationResult, String> {
+    simulate_internal(&mut [game], [0, 0], num_hands, seed)
+}
+
+/// Simulates `num_hands` hands in which OOP plays the strategy of `game_oop` and IP plays the
+/// strategy of `game_ip`, and reports the mean payoff of each player.
+///
+/// This can be used to measure how a solution performs against a locked or alternative strategy.
+/// The private hands of each player are dealt from its own game, and the payoffs (including the
+/// rake) are computed with the configuration of `game_oop`. See [`simulate`] for the other
+/// details.
+///
+/// The current nodes of both games are preserved.
+///
+/// Panics if the memory of either game is not yet allocated. Returns `Err` if the boards or the
+/// game trees do not match, if `num_hands` is zero, or if the hands cannot be dealt without
+/// conflicts.
+pub fn simulate_head_to_head(
+    game_oop: &mut PostFlopGame,
+    game_ip: &mut PostFlopGame,
+    num_hands: usize,
+    seed: u64,
+) -> Result<SimulationResult, String> {
+    if game_oop.card_config.flop != game_ip.card_config.flop
+        || game_oop.card_config.turn != game_ip.card_config.turn
+        || game_oop.card_config.river != game_ip.card_config.river
+        || game_oop.card_config.variant != game_ip.card_config.variant
+    {
+        return Err("Boards do not match".to_string());
+    }
+
+    simulate_internal(&mut [game_oop, game_ip], [0, 1], num_hands, seed)
+}
+
+/// Runs the simulation. Player `p` acts with the strategy of `games[owner[p]]`.
+fn simulate_internal(
+    games: &mut [&mut PostFlopGame],
+    owner: [usize; 2],
+    num_hands: usize,
+    seed: u64,
+) -> Result<SimulationResult, String> {
+    if games.iter().any(|game| game.state < State::MemoryAllocated) {
+        panic!("Memory is not allocated");
+    }
+
+    if num_hands == 0 {
+        return Err("Number of hands must be positive".to_string());
+    }
+
+    let histories = games
+        .iter()
+        .map(|game| game.action_history.clone())
+        .collect::<Vec<_>>();
+
+    let mut rng = SplitMix64::new(seed);
+    let cumulative_weights = [0, 1].map(|player| {
+        let mut sum = 0.0;
+        games[owner[player]]
+            .initial_weights(player)
+            .iter()
+            .map(|&w| {
+                sum += w as f64;
+                sum
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let card_config = &games[0].card_config;
+    let mut initial_board = card_config.flop.to_vec();
+    for card in [card_config.turn, card_config.river] {
+        if card != NOT_DEALT {
+            initial_board.push(card);
+        }
+    }
+
+    let initial_board_mask = initial_board.iter().fold(0u64, |acc, &c| acc | (1 << c));
+    let deck_mask = ((1u64 << 52) - 1) & !card_config.variant.removed_cards_mask();
+
+    let mut sum = [0.0f64; 2];
+    let mut sum_squared = [0.0f64; 2];
+
+    let mut result = Ok(());
+    for _ in 0..num_hands {
+        let payoffs = match simulate_hand(
+            games,
+            owner,
+            &cumulative_weights,
+            &initial_board,
+            initial_board_mask,
+            deck_mask,
+            &mut rng,
+        ) {
+            Ok(payoffs) => payoffs,
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        };
+
+        for player in 0..2 {
+            sum[player] += payoffs[player];
+            sum_squared[player] += payoffs[player] * payoffs[player];
+        }
+    }
+
+    for (game, history) in games.iter_mut().zip(histories.iter()) {
+        game.apply_history(history);
+    }
+
+    result?;
+
+    let n = num_hands as f64;
+    let mean = sum.map(|s| s / n);
+    let std_dev = [0, 1].map(|player| {
+        if num_hands < 2 {
+            0.0
+        } else {
+            let variance = (sum_squared[player] - n * mean[player] * mean[player]) / (n - 1.0);
+            variance.max(0.0).sqrt()
+        }
+    });
+    let std_error = std_dev.map(|s| s / n.sqrt());
+
+    Ok(SimulationResult {
+        num_hands,
+        mean: mean.map(|x| x as f32),
+        std_dev: std_dev.map(|x| x as f32),
+        std_error: std_error.map(|x| x as f32),
+        confidence_interval: [0, 1].map(|player| {
+            let margin = 1.96 * std_error[player];
+            (
+                (mean[player] - margin) as f32,
+                (mean[player] + margin) as f32,
+            )
+        }),
+    })
+}
+
+/// Deals and plays out one hand, and returns the payoffs of both players.
+fn simulate_hand(
+    games: &mut [&mut PostFlopGame],
+    owner: [usize; 2],
+    cumulative_weights: &[Vec<f64>; 2],
+    initial_board: &[Card],
+    initial_board_mask: u64,
+    deck_mask: u64,
+    rng: &mut SplitMix64,
+) -> Result<[f64; 2], String> {
+    // deal the private hands
+    let mut hand_indices = [0; 2];
+    let mut dealt = false;
+    for _ in 0..MAX_DEAL_ATTEMPTS {
+        hand_indices = [0, 1].map(|player| rng.sample(&cumulative_weights[player]));
+        let masks = [0, 1].map(|player| {
+            let (c1, c2) = games[owner[player]].private_cards[player][hand_indices[player]];
+            (1u64 << c1) | (1u64 << c2)
+        });
+        if masks[0] & masks[1] == 0 && (masks[0] | masks[1]) & initial_board_mask == 0 {
+            dealt = true;
+            break;
+        }
+    }
+
+    if !dealt {
+        return Err("Failed to deal non-conflicting hands".to_string());
+    }
+
+    let hands =
+        [0, 1].map(|player| games[owner[player]].private_cards[player][hand_indices[player]]);
+    let hand_mask = hands
+        .iter()
+        .fold(0u64, |acc, &(c1, c2)| acc | (1 << c1) | (1 << c2));
+
+    games.iter_mut().for_each(|game| game.back_to_root());
+    let mut board = initial_board.to_vec();
+
+    while !games[0].is_terminal_node() {
+        if games[0].is_chance_node() {
+            let possible_cards = games
+                .iter()
+                .fold(!hand_mask, |acc, game| acc & game.possible_cards());
+            if possible_cards == 0 {
+                return Err("No card can be dealt".to_string());
+            }
+            let card = nth_set_bit(possible_cards, rng.below(possible_cards.count_ones()));
+            games.iter_mut().for_each(|game| game.play(card as usize));
+            board.push(card);
+        } else {
+            let actions = games[0].available_actions();
+            if games[1..]
+                .iter()
+                .any(|game| game.available_actions() != actions)
+            {
+                return Err(format!(
+                    "Game trees do not match: {:?}",
+                    games[0].action_history
+                ));
+            }
+
+            let player = games[0].current_player();
+            let game = &games[owner[player]];
+            let num_hands = game.num_private_hands(player);
+            let strategy = game.strategy();
+            let probabilities = (0..actions.len())
+                .map(|action| strategy[action * num_hands + hand_indices[player]] as f64)
+                .scan(0.0, |sum, p| {
+                    *sum += p;
+                    Some(*sum)
+                })
+                .collect::<Vec<_>>();
+
+            let action = rng.sample(&probabilities);
+            games.iter_mut().for_each(|game| game.play(action));
+        }
+    }
+
+    // deal the rest of the board after the all-in call
+    let game = &games[0];
+    let node = game.node_arena[game.node_history.last().cloned().unwrap_or(0)].lock();
+    if node.player & PLAYER_FOLD_FLAG != PLAYER_FOLD_FLAG {
+        while board.len() < 5 {
+            let board_mask = board.iter().fold(0u64, |acc, &c| acc | (1 << c));
+            let remaining = deck_mask & !board_mask & !hand_mask;
+            let card = nth_set_bit(remaining, rng.below(remaining.count_ones()));
+            board.push(card);
+        }
+    }
+
+    Ok(game.terminal_payoffs(&node, hands, &board))
+}
+
+/// Returns the position of the `n`-th set bit (0-indexed) of `mask`.
+#[inline]
+fn nth_set_bit(mut mask: u64, n: usize) -> Card {
+    for _ in 0..n {
+        mask &= mask - 1;
+    }
+    mask.trailing_zeros() as Card
+}
+
+/// A small deterministic pseudo-random number generator (SplitMix64).
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    #[inline]
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a uniform random number in [0, 1).
+    #[inline]
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a uniform random integer in [0, `n`).
+    #[inline]
+    fn below(&mut self, n: u32) -> usize {
+        (((self.next_u64() >> 32) * n as u64) >> 32) as usize
+    }
+
+    /// Samples an index with the probabilities given by the cumulative weights.
+    #[inline]
+    fn sample(&mut self, cumulative_weights: &[f64]) -> usize {
+        let total = *cumulative_weights.last().unwrap();
+        let x = self.next_f64() * total;
+        let index = cumulative_weights.partition_point(|&w| w <= x);
+        index.min(cumulative_weights.len() - 1)
+    }
+}
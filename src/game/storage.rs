@@ -0,0 +1,197 @@
+use std::ops::{Deref, DerefMut};
+
+#[cfg(feature = "mmap")]
+use memmap2::MmapMut;
+
+#[cfg(feature = "mmap")]
+use std::{
+    fs::{self, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+#[cfg(feature = "bincode")]
+use bincode::{de::Decoder, error::DecodeError, Decode};
+
+/// Byte buffer used as a global storage of `PostFlopGame`.
+///
+/// The buffer is either allocated on the heap or backed by a memory-mapped file. In the latter
+/// case, the OS pages the hot regions in and out, so the resident memory can be much smaller than
+/// the size of the buffer.
+#[derive(Debug)]
+pub(super) enum Storage {
+    Memory(Vec<u8>),
+    #[cfg(feature = "mmap")]
+    Disk(DiskBuffer),
+}
+
+/// Memory-mapped temporary file. The file is removed when the buffer is dropped.
+#[cfg(feature = "mmap")]
+#[derive(Debug)]
+pub(super) struct DiskBuffer {
+    mmap: MmapMut,
+    path: PathBuf,
+}
+
+impl Storage {
+    /// Allocates a zero-filled buffer on the heap.
+    #[inline]
+    pub(super) fn zeroed(len: usize) -> Self {
+        Self::Memory(vec![0; len])
+    }
+
+    /// Allocates a zero-filled buffer backed by a new temporary file in `dir`.
+    #[cfg(feature = "mmap")]
+    pub(super) fn zeroed_on_disk(len: usize, dir: &Path) -> io::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        // zero-length files cannot be mapped
+        if len == 0 {
+            return Ok(Self::default());
+        }
+
+        let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let path = dir.join(format!("postflop-{}-{id}.storage", std::process::id()));
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+
+        // `set_len` fills the file with zeros
+        let mmap = file
+            .set_len(len as u64)
+            .and_then(|_| unsafe { MmapMut::map_mut(&file) });
+
+        match mmap {
+            Ok(mmap) => Ok(Self::Disk(DiskBuffer { mmap, path })),
+            Err(e) => {
+                let _ = fs::remove_file(&path);
+                Err(e)
+            }
+        }
+    }
+
+    /// Resizes the buffer, filling the new bytes with zeros.
+    ///
+    /// A buffer backed by a file stays on disk: the file is resized and mapped again.
+    #[cfg(feature = "bincode")]
+    pub(super) fn resize(&mut self, len: usize) -> Result<(), String> {
+        match self {
+            Self::Memory(vec) => {
+                vec.resize(len, 0);
+                Ok(())
+            }
+            #[cfg(feature = "mmap")]
+            Self::Disk(buf) => buf
+                .resize(len)
+                .map_err(|e| format!("Failed to resize storage file: {e}")),
+        }
+    }
+
+    /// Inserts the chunks of bytes into the buffer. Each chunk is given with its offset in the
+    /// buffer before the insertion, and the chunks must be sorted by the offsets. The chunks with
+    /// the same offset are inserted in the given order.
+    ///
+    /// The buffer must be grown by the total length of the chunks by [`resize`] beforehand.
+    ///
+    /// [`resize`]: Storage::resize
+    #[cfg(feature = "bincode")]
+    pub(super) fn insert_chunks(&mut self, chunks: &[(usize, &[u8])]) {
+        let total_len = chunks.iter().map(|(_, bytes)| bytes.len()).sum::<usize>();
+        if total_len == 0 {
+            return;
+        }
+
+        // move the existing bytes from the back, so that each byte is moved at most once
+        let buf: &mut [u8] = self;
+        let mut end = buf.len() - total_len;
+        let mut shift = total_len;
+        for &(offset, bytes) in chunks.iter().rev() {
+            buf.copy_within(offset..end, offset + shift);
+            shift -= bytes.len();
+            buf[offset + shift..offset + shift + bytes.len()].copy_from_slice(bytes);
+            end = offset;
+        }
+    }
+
+    /// Returns whether the buffer is backed by a file.
+    #[inline]
+    pub(super) fn is_on_disk(&self) -> bool {
+        match self {
+            Self::Memory(_) => false,
+            #[cfg(feature = "mmap")]
+            Self::Disk(_) => true,
+        }
+    }
+}
+
+impl Default for Storage {
+    #[inline]
+    fn default() -> Self {
+        Self::Memory(Vec::new())
+    }
+}
+
+impl From<Vec<u8>> for Storage {
+    #[inline]
+    fn from(vec: Vec<u8>) -> Self {
+        Self::Memory(vec)
+    }
+}
+
+impl Deref for Storage {
+    type Target = [u8];
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        match self {
+            Self::Memory(vec) => vec,
+            #[cfg(feature = "mmap")]
+            Self::Disk(buf) => &buf.mmap,
+        }
+    }
+}
+
+impl DerefMut for Storage {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [u8] {
+        match self {
+            Self::Memory(vec) => vec,
+            #[cfg(feature = "mmap")]
+            Self::Disk(buf) => &mut buf.mmap,
+        }
+    }
+}
+
+#[cfg(all(feature = "mmap", feature = "bincode"))]
+impl DiskBuffer {
+    /// Resizes the file and maps it again. The existing bytes are preserved.
+    fn resize(&mut self, len: usize) -> io::Result<()> {
+        if len == self.mmap.len() {
+            return Ok(());
+        }
+
+        let file = OpenOptions::new().read(true).write(true).open(&self.path)?;
+        file.set_len(len as u64)?;
+        self.mmap = unsafe { MmapMut::map_mut(&file)? };
+        Ok(())
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl Drop for DiskBuffer {
+    #[inline]
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl Decode for Storage {
+    #[inline]
+    fn decode<D: Decoder>(decoder: &mut D) -> Result<Self, DecodeError> {
+        Ok(Self::Memory(Vec::decode(decoder)?))
+    }
+}
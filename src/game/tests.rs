@@ -1,8 +1,36 @@
 use super::*;
+use crate::bet_size::*;
+use crate::hand_history::*;
+use crate::rake::*;
 use crate::range::*;
 use crate::solver::*;
 use crate::utility::*;
 use crate::BunchingData;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+/// Builds the river game of "AA,QQ" vs. "KK" on 2c3d4h 5s 9s with the starting pot of 100, in
+/// which only OOP can bet (with `oop_bet_sizes`), and allocates its memory.
+fn river_toy_game(effective_stack: i32, oop_bet_sizes: &str) -> PostFlopGame {
+    let card_config = CardConfig {
+        range: ["AA,QQ".parse().unwrap(), "KK".parse().unwrap()],
+        flop: flop_from_str("2c3d4h").unwrap(),
+        turn: card_from_str("5s").unwrap(),
+        river: card_from_str("9s").unwrap(),
+        ..Default::default()
+    };
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack,
+        river_bet_sizes: [(oop_bet_sizes, "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    game
+}
 
 #[test]
 fn all_check_all_range() {
@@ -306,6 +334,108 @@ fn one_raise_all_range_compressed() {
     assert!((ev_ip - 60.0).abs() < 1e-2);
 }
 
+#[test]
+fn quantized_storage() {
+    let card_config = CardConfig {
+        range: ["QQ+,AK".parse().unwrap(), "TT+,AQ+".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        turn_bet_sizes: [("50%", "60%").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "60%").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let (uncompressed, compressed) = game.memory_usage();
+    let quantized = game.memory_usage_quantized();
+    assert!(uncompressed > compressed && compressed > quantized);
+
+    let solve_ev = |game: &mut PostFlopGame| {
+        solve(game, 100, 0.0, false);
+        game.back_to_root();
+        game.cache_normalized_weights();
+        let ev_oop = compute_average(&game.expected_values(0), game.normalized_weights(0));
+        game.play(0);
+        game.play(0);
+        assert!(game.is_chance_node());
+        game.cache_normalized_weights();
+        let ev_ip = compute_average(&game.expected_values(1), game.normalized_weights(1));
+        [ev_oop, ev_ip]
+    };
+
+    game.allocate_memory(CompressionMode::Int16);
+    let ev_compressed = solve_ev(&mut game);
+
+    game.allocate_memory(CompressionMode::Int8);
+    assert_eq!(game.is_memory_allocated(), Some(true));
+    assert_eq!(game.allocated_memory_usage(), (quantized, 0));
+    let ev_quantized = solve_ev(&mut game);
+
+    assert!((ev_compressed[0] - ev_quantized[0]).abs() < 0.1);
+    assert!((ev_compressed[1] - ev_quantized[1]).abs() < 0.1);
+}
+
+#[test]
+fn quantized_storage_long_solve() {
+    let card_config = CardConfig {
+        range: ["QQ+,AK".parse().unwrap(), "TT+,AQ+".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 60,
+        effective_stack: 970,
+        turn_bet_sizes: [("50%", "60%").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "60%").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    assert!(game.memory_usage_quantized() < game.memory_usage().1);
+
+    let solve_ev = |game: &mut PostFlopGame| {
+        let exploitability = solve(game, 1000, 0.0, false);
+        game.cache_normalized_weights();
+        let ev = [game.expected_values(0), game.expected_values(1)];
+        (exploitability, ev, game.strategy())
+    };
+
+    game.allocate_memory(CompressionMode::Int16);
+    let (exploitability_compressed, ev_compressed, strategy_compressed) = solve_ev(&mut game);
+
+    game.allocate_memory(CompressionMode::Int8);
+    let (exploitability_quantized, ev_quantized, strategy_quantized) = solve_ev(&mut game);
+
+    // the average strategy keeps converging late in the solve (rounding to the nearest value
+    // froze it at about 0.014)
+    assert!(exploitability_compressed < 60.0 * 0.00001);
+    assert!(exploitability_quantized < 60.0 * 0.0002);
+
+    // the displayed results stay close to those of `Int16`
+    let max_diff = |a: &[f32], b: &[f32]| {
+        a.iter()
+            .zip(b)
+            .fold(0.0f32, |acc, (x, y)| acc.max((x - y).abs()))
+    };
+    assert!(max_diff(&strategy_compressed, &strategy_quantized) < 0.01);
+    assert!(max_diff(&ev_compressed[0], &ev_quantized[0]) < 60.0 * 0.01);
+    assert!(max_diff(&ev_compressed[1], &ev_quantized[1]) < 60.0 * 0.01);
+}
+
 #[test]
 fn one_raise_all_range_with_turn() {
     let card_config = CardConfig {
@@ -350,6 +480,7 @@ fn one_raise_all_range_with_river() {
         flop: flop_from_str("Td9d6h").unwrap(),
         turn: card_from_str("Qc").unwrap(),
         river: card_from_str("7s").unwrap(),
+        variant: GameVariant::Holdem,
     };
 
     let tree_config = TreeConfig {
@@ -547,6 +678,50 @@ fn always_win_raked() {
     assert!((ev_ip - 0.0).abs() < 1e-4);
 }
 
+#[test]
+fn always_win_raked_postflop_only() {
+    // be careful for straight flushes
+    let lose_range_str = "KK-22,K9-K2,Q8-Q2,J8-J2,T8-T2,92+,82+,72+,62+";
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), lose_range_str.parse().unwrap()],
+        flop: flop_from_str("AcAdKh").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        rake_model: Some(RakeModel::PostflopOnly {
+            rate: 0.05,
+            cap: 10.0,
+        }),
+        flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    let terminal_ev_oop = |game: &mut PostFlopGame, history: &[usize]| {
+        game.apply_history(history);
+        assert!(game.is_terminal_node());
+        game.cache_normalized_weights();
+        let weights_oop = game.normalized_weights(0);
+        compute_average(&game.expected_values(0), weights_oop)
+    };
+
+    // the starting pot is not raked
+    let check_down = [0, 0, usize::MAX, 0, 0, usize::MAX, 0, 0];
+    assert!((terminal_ev_oop(&mut game, &check_down) - 60.0).abs() < 1e-4);
+
+    // only the chips wagered after the starting pot are raked: 120 - 5% of 60
+    let bet_call = [1, 1, usize::MAX, 0, 0, usize::MAX, 0, 0];
+    assert!((terminal_ev_oop(&mut game, &bet_call) - 117.0).abs() < 1e-4);
+}
+
 #[test]
 fn always_lose() {
     // be careful for straight flushes
@@ -684,176 +859,1001 @@ fn always_tie_raked() {
 }
 
 #[test]
-fn no_assignment() {
+fn rake_models() {
     let card_config = CardConfig {
-        range: ["TT".parse().unwrap(), "TT".parse().unwrap()],
-        flop: flop_from_str("Td9d6h").unwrap(),
-        ..Default::default()
+        range: ["AsAh".parse().unwrap(), "KsKh".parse().unwrap()],
+        flop: flop_from_str("2s3h4d").unwrap(),
+        turn: card_from_str("6c").unwrap(),
+        river: card_from_str("7c").unwrap(),
+        variant: GameVariant::Holdem,
+    };
+
+    let compute_ev = |rake_model: RakeModel| {
+        let tree_config = TreeConfig {
+            initial_state: BoardState::River,
+            starting_pot: 60,
+            effective_stack: 970,
+            rake_model: Some(rake_model),
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        finalize(&mut game);
+        game.cache_normalized_weights();
+        game.expected_values(0)[0]
+    };
+
+    let percentage = RakeModel::Percentage {
+        rate: 0.05,
+        cap: 10.0,
+    };
+    assert!((compute_ev(percentage) - 57.0).abs() < 1e-4);
+
+    // no bets are called
+    let postflop_only = RakeModel::PostflopOnly {
+        rate: 0.05,
+        cap: 10.0,
+    };
+    assert!((compute_ev(postflop_only) - 60.0).abs() < 1e-4);
+
+    let street_caps = RakeModel::StreetCaps {
+        rate: 0.05,
+        caps: [10.0, 2.0, 1.0],
+    };
+    assert!((compute_ev(street_caps) - 59.0).abs() < 1e-4);
+
+    let pot_size_caps = RakeModel::PotSizeCaps {
+        rate: 0.05,
+        caps: vec![(50.0, 2.0), (100.0, 10.0)],
     };
+    assert!((compute_ev(pot_size_caps) - 58.0).abs() < 1e-4);
+
+    let player_count_caps = RakeModel::PlayerCountCaps {
+        rate: 0.05,
+        num_players: 6,
+        caps: vec![(2, 1.0), (5, 2.5)],
+    };
+    assert!((compute_ev(player_count_caps) - 57.5).abs() < 1e-4);
+
+    let custom = RakeModel::Custom(Arc::new(
+        |context: &RakeContext| {
+            if context.is_showdown {
+                0.5
+            } else {
+                0.0
+            }
+        },
+    ));
+    assert!((compute_ev(custom) - 59.5).abs() < 1e-4);
 
     let tree_config = TreeConfig {
         starting_pot: 60,
         effective_stack: 970,
+        rake_rate: 0.05,
+        rake_model: Some(RakeModel::Percentage {
+            rate: 0.05,
+            cap: 10.0,
+        }),
         ..Default::default()
     };
-
-    let action_tree = ActionTree::new(tree_config).unwrap();
-    let game = PostFlopGame::with_config(card_config, action_tree);
-    assert!(game.is_err());
+    assert!(ActionTree::new(tree_config).is_err());
 }
 
 #[test]
-fn remove_lines() {
-    use crate::bet_size::BetSizeOptions;
+#[cfg(feature = "mmap")]
+fn allocate_memory_on_disk() {
+    use crate::bet_size::*;
+
     let card_config = CardConfig {
-        range: ["TT+,AKo,AQs+".parse().unwrap(), "AA".parse().unwrap()],
-        flop: flop_from_str("2c6dTh").unwrap(),
+        range: ["QQ+,AK".parse().unwrap(), "TT+,AQ+".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
         ..Default::default()
     };
 
-    // simple tree: force checks on flop, and only use 1/2 pot bets on turn and river
+    let bet_sizes = BetSizeOptions::try_from(("50%", "60%")).unwrap();
     let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
         starting_pot: 60,
         effective_stack: 970,
-        turn_bet_sizes: [
-            BetSizeOptions::try_from(("50%", "")).unwrap(),
-            Default::default(),
-        ],
-        river_bet_sizes: [
-            BetSizeOptions::try_from(("50%", "")).unwrap(),
-            Default::default(),
-        ],
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
         ..Default::default()
     };
 
     let action_tree = ActionTree::new(tree_config).unwrap();
     let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
 
-    let lines = vec![
-        vec![
-            Action::Check,
-            Action::Check,
-            Action::Chance(2),
-            Action::Check,
-        ],
-        vec![
-            Action::Check,
-            Action::Check,
-            Action::Chance(2),
-            Action::Bet(30),
-            Action::Call,
-            Action::Chance(3),
-            Action::Bet(60),
-        ],
-    ];
+    let solve_ev = |game: &mut PostFlopGame| {
+        solve(game, 50, 0.0, false);
+        game.back_to_root();
+        game.cache_normalized_weights();
+        [
+            compute_average(&game.expected_values(0), game.normalized_weights(0)),
+            compute_average(&game.expected_values(1), game.normalized_weights(1)),
+        ]
+    };
 
-    let res = game.remove_lines(&lines);
-    assert!(res.is_ok());
+    game.allocate_memory(true);
+    let (resident, disk) = game.allocated_memory_usage();
+    assert_eq!(disk, 0);
+    assert!(resident >= game.memory_usage().1);
+    let ev_memory = solve_ev(&mut game);
 
-    game.allocate_memory(false);
+    let dir = std::env::temp_dir();
+    game.allocate_memory_on_disk(true, &dir).unwrap();
+    let (resident, disk) = game.allocated_memory_usage();
+    assert!(disk > 0);
+    assert_eq!(resident + disk, game.memory_usage().1);
+    let ev_disk = solve_ev(&mut game);
 
-    // check that the turn line is removed
-    game.apply_history(&[0, 0, 2]);
-    assert_eq!(game.available_actions(), vec![Action::Bet(30)]);
+    assert!((ev_memory[0] - ev_disk[0]).abs() < 1e-4);
+    assert!((ev_memory[1] - ev_disk[1]).abs() < 1e-4);
 
-    // check that other turn lines are correct
-    game.apply_history(&[0, 0, 3]);
-    assert_eq!(
-        game.available_actions(),
-        vec![Action::Check, Action::Bet(30)]
-    );
+    game.allocate_memory(true);
+    assert_eq!(game.allocated_memory_usage().1, 0);
+}
 
-    // check that the river line is removed
-    game.apply_history(&[0, 0, 2, 0, 1, 3]);
-    assert_eq!(game.available_actions(), vec![Action::Check]);
+#[test]
+#[cfg(all(feature = "mmap", feature = "bincode"))]
+fn insert_chunks_on_disk() {
+    let dir = std::env::temp_dir();
+    let mut storage = Storage::zeroed_on_disk(4, &dir).unwrap();
+    storage.copy_from_slice(&[1, 2, 3, 4]);
+
+    // the buffer stays on disk when it grows
+    storage.resize(7).unwrap();
+    storage.insert_chunks(&[(0, &[9]), (2, &[8, 8])]);
+    assert!(storage.is_on_disk());
+    assert_eq!(&storage[..], &[9, 1, 2, 8, 8, 3, 4]);
+}
 
-    // check that other river lines are correct
-    game.apply_history(&[0, 0, 2, 0, 1, 4]);
-    assert_eq!(
-        game.available_actions(),
-        vec![Action::Check, Action::Bet(60)]
-    );
+#[test]
+fn memory_budget_planning() {
+    let card_config = CardConfig {
+        range: [
+            "QQ+,AK,AQs".parse().unwrap(),
+            "TT+,AQ+,KQs".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
 
-    game.apply_history(&[0, 0, 3, 1, 1, 4]);
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        flop_bet_sizes: [
+            ("50%,100%", "60%").try_into().unwrap(),
+            ("75%", "").try_into().unwrap(),
+        ],
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let estimate = PostFlopGame::estimate_memory_usage(&card_config, &action_tree).unwrap();
+    let game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    let num_nodes = estimate.num_nodes.iter().sum::<u64>();
+    assert_eq!(num_nodes as usize, game.node_arena.len());
     assert_eq!(
-        game.available_actions(),
-        vec![Action::Check, Action::Bet(60)]
+        (estimate.uncompressed, estimate.compressed),
+        game.memory_usage()
     );
-
-    // check that `solve()` does not crash
-    solve(&mut game, 10, 0.01, false);
+    assert_eq!(estimate.quantized, game.memory_usage_quantized());
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let budget = estimate.compressed * 3 / 4;
+    let plan = PostFlopGame::plan_memory_budget(&card_config, &action_tree, budget, true).unwrap();
+    assert!(plan.fits_budget);
+    assert!(!plan.steps.is_empty());
+
+    let (_, last_estimate) = plan.steps.last().unwrap();
+    assert!(last_estimate.compressed <= budget);
+
+    let action_tree = ActionTree::new(plan.tree_config).unwrap();
+    let game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    assert_eq!(game.memory_usage().1, last_estimate.compressed);
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let plan = PostFlopGame::plan_memory_budget(&card_config, &action_tree, 0, true).unwrap();
+    assert!(!plan.fits_budget);
+    assert!(plan.steps.iter().any(|(trim, _)| *trim
+        == TreeTrim::RemoveBets {
+            street: BoardState::River
+        }));
+
+    // the trims that would make the removed line unavailable are skipped
+    let flop_bet = [Action::Bet(30)];
+    let mut action_tree = ActionTree::new(tree_config).unwrap();
+    action_tree.remove_line(&flop_bet).unwrap();
+    let plan = PostFlopGame::plan_memory_budget(&card_config, &action_tree, 0, true).unwrap();
+    assert!(plan.steps.iter().all(|(trim, _)| !matches!(
+        trim,
+        TreeTrim::RemoveBets {
+            street: BoardState::Flop
+        } | TreeTrim::RemoveBetSize {
+            street: BoardState::Flop,
+            player: 0,
+            is_raise: false,
+            size: BetSize::PotRelative(0.5),
+        }
+    )));
+
+    let mut action_tree = ActionTree::new(plan.tree_config).unwrap();
+    assert!(action_tree.remove_line(&flop_bet).is_ok());
 }
 
 #[test]
-fn isomorphism_monotone() {
-    let oop_range = "88+,A8s+,A5s-A2s:0.5,AJo+,ATo:0.75,K9s+,KQo,KJo:0.75,KTo:0.25,Q9s+,QJo:0.5,J8s+,JTo:0.25,T8s+,T7s:0.45,97s+,96s:0.45,87s,86s:0.75,85s:0.45,75s+:0.75,74s:0.45,65s:0.75,64s:0.5,63s:0.45,54s:0.75,53s:0.5,52s:0.45,43s:0.5,42s:0.45,32s:0.45";
-    let ip_range = "AA:0.25,99-22,AJs-A2s,AQo-A8o,K2s+,K9o+,Q2s+,Q9o+,J6s+,J9o+,T6s+,T9o,96s+,95s:0.5,98o,86s+,85s:0.5,75s+,74s:0.5,64s+,63s:0.5,54s,53s:0.5,43s";
-
+fn no_assignment() {
     let card_config = CardConfig {
-        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
-        flop: flop_from_str("QhJh2h").unwrap(),
+        range: ["TT".parse().unwrap(), "TT".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
         ..Default::default()
     };
 
     let tree_config = TreeConfig {
-        starting_pot: 100,
-        effective_stack: 100,
+        starting_pot: 60,
+        effective_stack: 970,
         ..Default::default()
     };
 
     let action_tree = ActionTree::new(tree_config).unwrap();
-    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree);
+    assert!(game.is_err());
+}
 
-    game.allocate_memory(false);
-    finalize(&mut game);
+#[test]
+fn short_deck_all_check_all_range() {
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        variant: GameVariant::ShortDeck,
+        ..Default::default()
+    };
 
-    let mut check = |history: &[usize],
-                     expected_turn_swap: Option<u8>,
-                     expected_river_swap: Option<(u8, u8)>| {
-        game.apply_history(history);
-        game.cache_normalized_weights();
-        let weights = game.normalized_weights(0);
-        let ev = game.expected_values(0);
-        weights.iter().zip(ev.iter()).for_each(|(&w, &v)| {
-            assert!(!(w > 0.0 && v == 50.0));
-        });
-        assert_eq!(game.turn_swap, expected_turn_swap);
-        assert_eq!(game.river_swap, expected_river_swap);
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
     };
 
-    check(&[0, 0, 4], None, None);
-    check(&[0, 0, 5], Some(1), None);
-    check(&[0, 0, 6], None, None);
-    check(&[0, 0, 7], Some(3), None);
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
 
-    check(&[0, 0, 4, 0, 0, 8], None, None);
-    check(&[0, 0, 4, 0, 0, 9], None, None);
-    check(&[0, 0, 4, 0, 0, 10], None, None);
-    check(&[0, 0, 4, 0, 0, 11], None, Some((0, 3)));
+    // 6s-Ad excluding the board: 33 * 32 / 2 = 528 combinations
+    assert_eq!(game.private_cards(0).len(), 528);
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    game.cache_normalized_weights();
+    let weights_oop = game.normalized_weights(0);
+    let weights_ip = game.normalized_weights(1);
+    let equity_oop = compute_average(&game.equity(0), weights_oop);
+    let equity_ip = compute_average(&game.equity(1), weights_ip);
+    let ev_oop = compute_average(&game.expected_values(0), weights_oop);
+    let ev_ip = compute_average(&game.expected_values(1), weights_ip);
+    assert!((equity_oop - 0.5).abs() < 1e-5);
+    assert!((equity_ip - 0.5).abs() < 1e-5);
+    assert!((ev_oop - 30.0).abs() < 1e-4);
+    assert!((ev_ip - 30.0).abs() < 1e-4);
+
+    game.play(0);
+    game.play(0);
+    assert!(game.is_chance_node());
+    let possible_cards = game.possible_cards();
+    assert_eq!(possible_cards.count_ones(), 33);
+    assert_eq!(
+        possible_cards & GameVariant::ShortDeck.removed_cards_mask(),
+        0
+    );
+
+    game.play(card_from_str("6c").unwrap() as usize);
+    game.play(0);
+    game.play(0);
+    assert_eq!(game.possible_cards().count_ones(), 32);
+}
+
+#[test]
+fn short_deck_flush_beats_full_house() {
+    let card_config = CardConfig {
+        range: ["8h7h".parse().unwrap(), "AsAc".parse().unwrap()],
+        flop: flop_from_str("AhKhTh").unwrap(),
+        turn: card_from_str("9s").unwrap(),
+        river: card_from_str("9c").unwrap(),
+        variant: GameVariant::ShortDeck,
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    game.cache_normalized_weights();
+    assert!((game.equity(0)[0] - 1.0).abs() < 1e-5);
+    assert!((game.expected_values(0)[0] - 60.0).abs() < 1e-4);
+
+    // the full house wins in Texas hold'em
+    let card_config = CardConfig {
+        variant: GameVariant::Holdem,
+        ..card_config
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    game.cache_normalized_weights();
+    assert!((game.equity(0)[0] - 0.0).abs() < 1e-5);
+}
+
+#[test]
+fn short_deck_invalid_board() {
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d5h").unwrap(),
+        variant: GameVariant::ShortDeck,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        ..Default::default()
+    })
+    .unwrap();
+    let game = PostFlopGame::with_config(card_config, action_tree);
+    assert!(game.is_err());
+}
+
+#[test]
+fn fixed_limit_tree() {
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 8,
+        effective_stack: 30,
+        fixed_limit: Some(FixedLimitConfig {
+            small_bet: 2,
+            big_bet: 4,
+            max_bets: 4,
+        }),
+        ..Default::default()
+    };
+
+    let mut action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    assert_eq!(
+        action_tree.available_actions(),
+        [Action::Check, Action::Bet(4)]
+    );
+
+    // bet, raise, 3-bet, and cap
+    action_tree.play(Action::Bet(4)).unwrap();
+    action_tree.play(Action::Raise(8)).unwrap();
+    action_tree.play(Action::Raise(12)).unwrap();
+    assert_eq!(
+        action_tree.available_actions(),
+        [Action::Fold, Action::Call, Action::Raise(16)]
+    );
+    action_tree.play(Action::Raise(16)).unwrap();
+    assert_eq!(
+        action_tree.available_actions(),
+        [Action::Fold, Action::Call]
+    );
+
+    // the remaining stack is 14 after the call, so the last raise on the river is all-in
+    action_tree.play(Action::Call).unwrap();
+    action_tree.play(Action::Bet(4)).unwrap();
+    action_tree.play(Action::Raise(8)).unwrap();
+    action_tree.play(Action::Raise(12)).unwrap();
+    assert_eq!(
+        action_tree.available_actions(),
+        [Action::Fold, Action::Call, Action::AllIn(14)]
+    );
+    assert_eq!(action_tree.total_bet_amount(), [28, 24]);
+
+    let card_config = CardConfig {
+        range: [Range::ones(); 2],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("Qc").unwrap(),
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    // pot-relative sizes cannot be mixed in
+    let tree_config = TreeConfig {
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..tree_config
+    };
+    assert!(ActionTree::new(tree_config).is_err());
+}
+
+#[test]
+fn asymmetric_starting_stacks() {
+    let card_config = CardConfig {
+        range: ["AsAh,QsQh".parse().unwrap(), "KsKh".parse().unwrap()],
+        flop: flop_from_str("2s3h4d").unwrap(),
+        turn: card_from_str("6c").unwrap(),
+        river: card_from_str("7c").unwrap(),
+        variant: GameVariant::Holdem,
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 20,
+        starting_stacks: Some([500, 30]),
+        river_bet_sizes: [("50%, a", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    assert_eq!(action_tree.config().effective_stack, 30);
+
+    // an explicit effective stack must match the smaller starting stack
+    let conflicting_config = TreeConfig {
+        effective_stack: 500,
+        ..tree_config.clone()
+    };
+    assert!(ActionTree::new(conflicting_config).is_err());
+    let explicit_config = TreeConfig {
+        effective_stack: 30,
+        ..tree_config.clone()
+    };
+    assert!(ActionTree::new(explicit_config).is_ok());
+    assert_eq!(
+        action_tree.available_actions(),
+        [Action::Check, Action::Bet(10), Action::AllIn(30)]
+    );
+
+    let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.0, false);
+    game.cache_normalized_weights();
+    let ev_oop = game.expected_values(0);
+    let ev_ip = game.expected_values(1);
+
+    game.play(2); // all-in
+    game.play(1); // call
+    assert!(game.is_terminal_node());
+    assert_eq!(game.total_bet_amount(), [30, 30]);
+    assert_eq!(game.remaining_stacks(), [470, 0]);
+
+    // the result must be the same as the game with the effective stack
+    let tree_config = TreeConfig {
+        effective_stack: 30,
+        starting_stacks: None,
+        ..tree_config
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.0, false);
+    game.cache_normalized_weights();
+    assert_eq!(game.expected_values(0), ev_oop);
+    assert_eq!(game.expected_values(1), ev_ip);
+}
+
+#[test]
+fn pot_contributions() {
+    let card_config = CardConfig {
+        range: ["AsAh".parse().unwrap(), "KsKh".parse().unwrap()],
+        flop: flop_from_str("2s3h4d").unwrap(),
+        turn: card_from_str("6c").unwrap(),
+        river: card_from_str("7c").unwrap(),
+        variant: GameVariant::Holdem,
+    };
+
+    // OOP: 25, IP: 15, dead money: 20
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 60,
+        effective_stack: 10,
+        pot_contributions: Some([25, 15]),
+        rake_rate: 0.05,
+        rake_cap: 2.0,
+        river_bet_sizes: [("a", "").try_into().unwrap(), ("a", "").try_into().unwrap()],
+        ..Default::default()
+    };
+    assert_eq!(tree_config.dead_money(), 20);
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.0, false);
+    game.cache_normalized_weights();
+
+    // OOP always wins the pot of 60 after the rake of 2 (IP never calls)
+    assert!((game.expected_values(0)[0] - 33.0).abs() < 1e-2);
+    assert!((game.expected_values(1)[0] - -15.0).abs() < 1e-2);
+
+    game.play(1); // all-in
+    game.cache_normalized_weights();
+    let ev_ip = game.expected_values_detail(1);
+    assert!((ev_ip[0] - -15.0).abs() < 1e-4); // fold
+    assert!((ev_ip[1] - -25.0).abs() < 1e-2); // call
+
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 10,
+        pot_contributions: Some([40, 30]),
+        ..Default::default()
+    };
+    assert!(ActionTree::new(tree_config).is_err());
+}
+
+#[test]
+fn remove_lines() {
+    use crate::bet_size::BetSizeOptions;
+    let card_config = CardConfig {
+        range: ["TT+,AKo,AQs+".parse().unwrap(), "AA".parse().unwrap()],
+        flop: flop_from_str("2c6dTh").unwrap(),
+        ..Default::default()
+    };
+
+    // simple tree: force checks on flop, and only use 1/2 pot bets on turn and river
+    let tree_config = TreeConfig {
+        starting_pot: 60,
+        effective_stack: 970,
+        turn_bet_sizes: [
+            BetSizeOptions::try_from(("50%", "")).unwrap(),
+            Default::default(),
+        ],
+        river_bet_sizes: [
+            BetSizeOptions::try_from(("50%", "")).unwrap(),
+            Default::default(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    let lines = vec![
+        vec![
+            Action::Check,
+            Action::Check,
+            Action::Chance(2),
+            Action::Check,
+        ],
+        vec![
+            Action::Check,
+            Action::Check,
+            Action::Chance(2),
+            Action::Bet(30),
+            Action::Call,
+            Action::Chance(3),
+            Action::Bet(60),
+        ],
+    ];
+
+    let res = game.remove_lines(&lines);
+    assert!(res.is_ok());
+
+    game.allocate_memory(false);
+
+    // check that the turn line is removed
+    game.apply_history(&[0, 0, 2]);
+    assert_eq!(game.available_actions(), vec![Action::Bet(30)]);
+
+    // check that other turn lines are correct
+    game.apply_history(&[0, 0, 3]);
+    assert_eq!(
+        game.available_actions(),
+        vec![Action::Check, Action::Bet(30)]
+    );
+
+    // check that the river line is removed
+    game.apply_history(&[0, 0, 2, 0, 1, 3]);
+    assert_eq!(game.available_actions(), vec![Action::Check]);
+
+    // check that other river lines are correct
+    game.apply_history(&[0, 0, 2, 0, 1, 4]);
+    assert_eq!(
+        game.available_actions(),
+        vec![Action::Check, Action::Bet(60)]
+    );
+
+    game.apply_history(&[0, 0, 3, 1, 1, 4]);
+    assert_eq!(
+        game.available_actions(),
+        vec![Action::Check, Action::Bet(60)]
+    );
+
+    // check that `solve()` does not crash
+    solve(&mut game, 10, 0.01, false);
+}
+
+#[test]
+fn isomorphism_monotone() {
+    let oop_range = "88+,A8s+,A5s-A2s:0.5,AJo+,ATo:0.75,K9s+,KQo,KJo:0.75,KTo:0.25,Q9s+,QJo:0.5,J8s+,JTo:0.25,T8s+,T7s:0.45,97s+,96s:0.45,87s,86s:0.75,85s:0.45,75s+:0.75,74s:0.45,65s:0.75,64s:0.5,63s:0.45,54s:0.75,53s:0.5,52s:0.45,43s:0.5,42s:0.45,32s:0.45";
+    let ip_range = "AA:0.25,99-22,AJs-A2s,AQo-A8o,K2s+,K9o+,Q2s+,Q9o+,J6s+,J9o+,T6s+,T9o,96s+,95s:0.5,98o,86s+,85s:0.5,75s+,74s:0.5,64s+,63s:0.5,54s,53s:0.5,43s";
+
+    let card_config = CardConfig {
+        range: [oop_range.parse().unwrap(), ip_range.parse().unwrap()],
+        flop: flop_from_str("QhJh2h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 100,
+        effective_stack: 100,
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    finalize(&mut game);
+
+    let mut check = |history: &[usize],
+                     expected_turn_swap: Option<u8>,
+                     expected_river_swap: Option<(u8, u8)>| {
+        game.apply_history(history);
+        game.cache_normalized_weights();
+        let weights = game.normalized_weights(0);
+        let ev = game.expected_values(0);
+        weights.iter().zip(ev.iter()).for_each(|(&w, &v)| {
+            assert!(!(w > 0.0 && v == 50.0));
+        });
+        assert_eq!(game.turn_swap, expected_turn_swap);
+        assert_eq!(game.river_swap, expected_river_swap);
+    };
+
+    check(&[0, 0, 4], None, None);
+    check(&[0, 0, 5], Some(1), None);
+    check(&[0, 0, 6], None, None);
+    check(&[0, 0, 7], Some(3), None);
+
+    check(&[0, 0, 4, 0, 0, 8], None, None);
+    check(&[0, 0, 4, 0, 0, 9], None, None);
+    check(&[0, 0, 4, 0, 0, 10], None, None);
+    check(&[0, 0, 4, 0, 0, 11], None, Some((0, 3)));
 
     check(&[0, 0, 5, 0, 0, 8], Some(1), None);
     check(&[0, 0, 5, 0, 0, 9], Some(1), None);
     check(&[0, 0, 5, 0, 0, 10], Some(1), None);
     check(&[0, 0, 5, 0, 0, 11], Some(1), Some((1, 3)));
 
-    check(&[0, 0, 6, 0, 0, 8], None, None);
-    check(&[0, 0, 6, 0, 0, 9], None, Some((2, 1)));
-    check(&[0, 0, 6, 0, 0, 10], None, None);
-    check(&[0, 0, 6, 0, 0, 11], None, Some((2, 3)));
+    check(&[0, 0, 6, 0, 0, 8], None, None);
+    check(&[0, 0, 6, 0, 0, 9], None, Some((2, 1)));
+    check(&[0, 0, 6, 0, 0, 10], None, None);
+    check(&[0, 0, 6, 0, 0, 11], None, Some((2, 3)));
+
+    check(&[0, 0, 7, 0, 0, 8], Some(3), Some((3, 1)));
+    check(&[0, 0, 7, 0, 0, 9], Some(3), None);
+    check(&[0, 0, 7, 0, 0, 10], Some(3), None);
+    check(&[0, 0, 7, 0, 0, 11], Some(3), None);
+}
+
+#[test]
+fn node_locking() {
+    let card_config = CardConfig {
+        range: ["AsAh,QsQh".parse().unwrap(), "KsKh".parse().unwrap()],
+        flop: flop_from_str("2s3h4d").unwrap(),
+        turn: card_from_str("6c").unwrap(),
+        river: card_from_str("7c").unwrap(),
+        variant: GameVariant::Holdem,
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 20,
+        effective_stack: 10,
+        river_bet_sizes: [("a", "").try_into().unwrap(), ("a", "").try_into().unwrap()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+
+    game.allocate_memory(false);
+    game.play(1); // all-in
+    game.lock_current_strategy(&[0.25, 0.75]); // 25% fold, 75% call
+    game.back_to_root();
+
+    solve(&mut game, 1000, 0.0, false);
+    game.cache_normalized_weights();
+
+    let ev_oop = game.expected_values(0);
+    let ev_ip = game.expected_values(1);
+    assert!((ev_oop[0] - 0.0).abs() < 1e-2);
+    assert!((ev_oop[1] - 27.5).abs() < 5e-2);
+    assert!((ev_ip[0] - 6.25).abs() < 1e-2);
+
+    let strategy_oop = game.strategy();
+    assert!((strategy_oop[0] - 1.0).abs() < 1e-3); // QQ check
+    assert!((strategy_oop[1] - 0.0).abs() < 1e-3); // AA check
+    assert!((strategy_oop[2] - 0.0).abs() < 1e-3); // QQ bet
+    assert!((strategy_oop[3] - 1.0).abs() < 1e-3); // AA bet
+
+    game.allocate_memory(false);
+    game.play(1); // all-in
+    game.lock_current_strategy(&[0.5, 0.5]); // 50% fold, 50% call
+    game.back_to_root();
+
+    solve(&mut game, 1000, 0.0, false);
+    game.cache_normalized_weights();
+
+    let ev_oop = game.expected_values(0);
+    let ev_ip = game.expected_values(1);
+    assert!((ev_oop[0] - 5.0).abs() < 1e-2);
+    assert!((ev_oop[1] - 25.0).abs() < 5e-2);
+    assert!((ev_ip[0] - 5.0).abs() < 1e-2);
+
+    let strategy_oop = game.strategy();
+    assert!((strategy_oop[0] - 0.0).abs() < 1e-3); // QQ check
+    assert!((strategy_oop[1] - 0.0).abs() < 1e-3); // AA check
+    assert!((strategy_oop[2] - 1.0).abs() < 1e-3); // QQ bet
+    assert!((strategy_oop[3] - 1.0).abs() < 1e-3); // AA bet
+}
+
+#[test]
+fn node_locking_rules() {
+    let card_config = CardConfig {
+        range: ["AsAh,QsQh".parse().unwrap(), "KsKh".parse().unwrap()],
+        flop: flop_from_str("2s3h4d").unwrap(),
+        turn: card_from_str("6c").unwrap(),
+        river: card_from_str("7c").unwrap(),
+        variant: GameVariant::Holdem,
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 20,
+        effective_stack: 10,
+        river_bet_sizes: [("a", "").try_into().unwrap(), ("a", "").try_into().unwrap()],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    // same as `node_locking`: KK (overpair) folds 25% and calls 75% facing the all-in
+    let rules = [
+        LockRule {
+            player: 1,
+            street: Some(BoardState::River),
+            board: BoardPattern::Any,
+            line: LinePattern::EndsWith(vec![ActionPattern::Aggressive]),
+            previous_streets: Vec::new(),
+            hands: HandCategory::Overpair..=HandCategory::Overpair,
+            action: ActionPattern::Fold,
+            frequency: 0.25,
+        },
+        // does not match: IP has no weak pairs
+        LockRule {
+            player: 1,
+            street: None,
+            board: BoardPattern::Any,
+            line: LinePattern::Any,
+            previous_streets: Vec::new(),
+            hands: HandCategory::HighCard..=HandCategory::WeakPair,
+            action: ActionPattern::Any,
+            frequency: 1.0,
+        },
+    ];
+
+    assert_eq!(game.lock_by_rules(&rules), Ok(1));
+
+    game.play(1); // all-in
+    assert_eq!(game.current_locking_strategy(), Some(vec![0.25, 0.75]));
+    game.back_to_root();
+
+    solve(&mut game, 1000, 0.0, false);
+    game.cache_normalized_weights();
+
+    let ev_oop = game.expected_values(0);
+    let ev_ip = game.expected_values(1);
+    assert!((ev_oop[0] - 0.0).abs() < 1e-2);
+    assert!((ev_oop[1] - 27.5).abs() < 5e-2);
+    assert!((ev_ip[0] - 6.25).abs() < 1e-2);
+
+    // OOP never bets: the rule with zero frequency locks the other action
+    game.allocate_memory(false);
+    let rule = LockRule {
+        player: 0,
+        street: Some(BoardState::River),
+        board: BoardPattern::Any,
+        line: LinePattern::Exact(vec![]),
+        previous_streets: Vec::new(),
+        hands: HandCategory::HighCard..=HandCategory::StraightFlush,
+        action: ActionPattern::Bet,
+        frequency: 0.0,
+    };
 
-    check(&[0, 0, 7, 0, 0, 8], Some(3), Some((3, 1)));
-    check(&[0, 0, 7, 0, 0, 9], Some(3), None);
-    check(&[0, 0, 7, 0, 0, 10], Some(3), None);
-    check(&[0, 0, 7, 0, 0, 11], Some(3), None);
+    assert_eq!(game.lock_by_rules(std::slice::from_ref(&rule)), Ok(1));
+    assert_eq!(
+        game.current_locking_strategy(),
+        Some(vec![1.0, 1.0, 0.0, 0.0])
+    );
+
+    let invalid = LockRule {
+        frequency: 1.5,
+        ..rule
+    };
+    assert!(game.lock_by_rules(&[invalid]).is_err());
 }
 
 #[test]
-fn node_locking() {
+fn node_locking_rules_previous_streets() {
+    let card_config = CardConfig {
+        range: ["AsAh,QsQh".parse().unwrap(), "KsKh".parse().unwrap()],
+        flop: flop_from_str("2s3h4d").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Flop,
+        starting_pot: 20,
+        effective_stack: 100,
+        flop_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        turn_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+
+    // OOP never donks the turn after check-calling a flop c-bet
+    let rule = LockRule {
+        player: 0,
+        street: Some(BoardState::Turn),
+        board: BoardPattern::Any,
+        line: LinePattern::Exact(vec![]),
+        previous_streets: vec![LinePattern::Exact(vec![
+            ActionPattern::Check,
+            ActionPattern::Bet,
+            ActionPattern::Call,
+        ])],
+        hands: HandCategory::HighCard..=HandCategory::StraightFlush,
+        action: ActionPattern::Bet,
+        frequency: 0.0,
+    };
+
+    let flop_actions = game.available_actions();
+    let bet = flop_actions
+        .iter()
+        .position(|&a| matches!(a, Action::Bet(_)))
+        .unwrap();
+
+    game.play(0); // check
+    game.play(bet);
+    game.play(1); // call
+    let turn_cards = game.possible_cards();
+    assert_eq!(game.lock_by_rules(&[rule]), Ok(49)); // one node for each turn card
+
+    // skip the turn cards that block OOP's hands (the blocked hands are left unlocked)
+    let oop_cards = game
+        .private_cards(0)
+        .iter()
+        .fold(0u64, |acc, &(c1, c2)| acc | (1 << c1) | (1 << c2));
+    for card in (0..52).filter(|&c| turn_cards & !oop_cards & (1 << c) != 0) {
+        game.apply_history(&[0, bet, 1, card]);
+        let expected = game
+            .available_actions()
+            .iter()
+            .flat_map(|&a| [(a == Action::Check) as u8 as f32; 2])
+            .collect::<Vec<_>>();
+        assert_eq!(game.current_locking_strategy(), Some(expected));
+    }
+
+    // the turn after check-check and the flop are not locked
+    game.apply_history(&[0, 0]);
+    game.play(game.possible_cards().trailing_zeros() as usize);
+    assert_eq!(game.current_locking_strategy(), None);
+    game.back_to_root();
+    assert_eq!(game.current_locking_strategy(), None);
+}
+
+#[test]
+fn node_locking_rules_board() {
+    let build_game = |flop: &str| {
+        let card_config = CardConfig {
+            range: ["AsAh,QsQh".parse().unwrap(), "KsKh".parse().unwrap()],
+            flop: flop_from_str(flop).unwrap(),
+            ..Default::default()
+        };
+
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Flop,
+            starting_pot: 20,
+            effective_stack: 100,
+            flop_bet_sizes: [
+                ("50%", "").try_into().unwrap(),
+                ("50%", "").try_into().unwrap(),
+            ],
+            ..Default::default()
+        };
+
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let rule = |street, board| LockRule {
+        player: 0,
+        street: Some(street),
+        board,
+        line: LinePattern::Exact(vec![]),
+        previous_streets: Vec::new(),
+        hands: HandCategory::HighCard..=HandCategory::StraightFlush,
+        action: ActionPattern::Check,
+        frequency: 1.0,
+    };
+
+    // three flop lines reach the turn (check-check, check-bet-call, and bet-call), and each of
+    // them has one node for each of the 49 turn cards
+    let mut game = build_game("2s3h4d");
+    let turn_count =
+        |game: &mut PostFlopGame, board| game.lock_by_rules(&[rule(BoardState::Turn, board)]);
+    assert_eq!(turn_count(&mut game, BoardPattern::Any), Ok(3 * 49));
+    assert_eq!(turn_count(&mut game, BoardPattern::Paired), Ok(3 * 9));
+    assert_eq!(turn_count(&mut game, BoardPattern::Monotone), Ok(0));
+    assert_eq!(turn_count(&mut game, BoardPattern::TwoTone), Ok(3 * 36));
+    assert_eq!(turn_count(&mut game, BoardPattern::Rainbow), Ok(3 * 13));
+    assert_eq!(turn_count(&mut game, BoardPattern::Connected), Ok(3 * 49));
+    assert_eq!(
+        turn_count(&mut game, BoardPattern::ContainsRank(12)),
+        Ok(3 * 4)
+    );
+    assert!(turn_count(&mut game, BoardPattern::ContainsRank(13)).is_err());
+
+    // only the root node is on the flop
+    let flop_count =
+        |game: &mut PostFlopGame, board| game.lock_by_rules(&[rule(BoardState::Flop, board)]);
+    assert_eq!(flop_count(&mut game, BoardPattern::Rainbow), Ok(1));
+    assert_eq!(flop_count(&mut game, BoardPattern::Paired), Ok(0));
+
+    let mut game = build_game("Kc7d2d");
+    assert_eq!(flop_count(&mut game, BoardPattern::Connected), Ok(0));
+    assert_eq!(flop_count(&mut game, BoardPattern::TwoTone), Ok(1));
+
+    let mut game = build_game("Ad3d4d");
+    assert_eq!(flop_count(&mut game, BoardPattern::Connected), Ok(1));
+    assert_eq!(flop_count(&mut game, BoardPattern::Monotone), Ok(1));
+}
+
+#[test]
+fn node_locking_frequencies() {
     let card_config = CardConfig {
         range: ["AsAh,QsQh".parse().unwrap(), "KsKh".parse().unwrap()],
         flop: flop_from_str("2s3h4d").unwrap(),
         turn: card_from_str("6c").unwrap(),
         river: card_from_str("7c").unwrap(),
+        variant: GameVariant::Holdem,
     };
 
     let tree_config = TreeConfig {
@@ -864,48 +1864,601 @@ fn node_locking() {
         ..Default::default()
     };
 
-    let action_tree = ActionTree::new(tree_config).unwrap();
-    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    let build_game = || {
+        let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+        let mut game = PostFlopGame::with_config(card_config.clone(), action_tree).unwrap();
+        game.allocate_memory(false);
+        game
+    };
 
-    game.allocate_memory(false);
-    game.play(1); // all-in
-    game.lock_current_strategy(&[0.25, 0.75]); // 25% fold, 75% call
-    game.back_to_root();
+    let mut game = build_game();
+    let is_locked = |game: &PostFlopGame| game.node_arena[0].lock().is_locked;
+
+    // the node stays locked while either the strategy or the frequencies are locked
+    game.lock_current_frequencies(&[1.0, 1.0]);
+    assert!(is_locked(&game));
+    game.lock_current_strategy(&[1.0, 1.0, 0.0, 0.0]);
+    game.unlock_current_frequencies();
+    assert!(is_locked(&game));
+    game.unlock_current_strategy();
+    assert!(!is_locked(&game));
+
+    game.lock_current_frequencies(&[3.0, 1.0]); // 75% check, 25% all-in
+    assert_eq!(game.current_locking_frequencies(), Some(vec![0.75, 0.25]));
+    assert!(is_locked(&game));
 
     solve(&mut game, 1000, 0.0, false);
     game.cache_normalized_weights();
 
-    let ev_oop = game.expected_values(0);
-    let ev_ip = game.expected_values(1);
-    assert!((ev_oop[0] - 0.0).abs() < 1e-2);
-    assert!((ev_oop[1] - 27.5).abs() < 5e-2);
-    assert!((ev_ip[0] - 6.25).abs() < 1e-2);
-
+    // the solver chooses the hands: QQ bluffs so that KK is indifferent to calling
     let strategy_oop = game.strategy();
-    assert!((strategy_oop[0] - 1.0).abs() < 1e-3); // QQ check
-    assert!((strategy_oop[1] - 0.0).abs() < 1e-3); // AA check
-    assert!((strategy_oop[2] - 0.0).abs() < 1e-3); // QQ bet
-    assert!((strategy_oop[3] - 1.0).abs() < 1e-3); // AA bet
+    let bet_frequency = (strategy_oop[2] + strategy_oop[3]) / 2.0;
+    assert!((bet_frequency - 0.25).abs() < 1e-5);
+    assert!((strategy_oop[2] - 0.125).abs() < 1e-2); // QQ bet
+    assert!((strategy_oop[3] - 0.375).abs() < 1e-2); // AA bet
+
+    // AA is locked to check, so QQ makes up all the bets
+    let mut game = build_game();
+    game.lock_current_strategy(&[-1.0, 1.0, -1.0, 0.0]);
+    game.lock_current_frequencies(&[3.0, 1.0]);
+    solve(&mut game, 1000, 0.0, false);
+    game.cache_normalized_weights();
+    let strategy_oop = game.strategy();
+    assert!((strategy_oop[2] - 0.5).abs() < 1e-5);
+    assert_eq!(strategy_oop[3], 0.0);
+}
+
+#[test]
+fn node_locking_all_runouts() {
+    let card_config = CardConfig {
+        range: ["AA,KK,T9s".parse().unwrap(), "QQ,AKs,87s".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        ..Default::default()
+    };
+
+    let tree_config = TreeConfig {
+        starting_pot: 100,
+        effective_stack: 200,
+        river_bet_sizes: [
+            ("50%", "").try_into().unwrap(),
+            ("50%", "").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
 
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
     game.allocate_memory(false);
-    game.play(1); // all-in
-    game.lock_current_strategy(&[0.5, 0.5]); // 50% fold, 50% call
+
+    // IP bets two pair or better on the river after checks on every street
+    let strategy_fn = |game: &PostFlopGame| {
+        let board = game.current_board();
+        let bets = game
+            .private_cards(1)
+            .iter()
+            .map(|&hand| {
+                let category = HandCategory::classify(hand, &board, GameVariant::Holdem);
+                (category >= HandCategory::TwoPair) as u8 as f32
+            })
+            .collect::<Vec<_>>();
+        let checks = bets.iter().map(|&b| 1.0 - b).collect::<Vec<_>>();
+        [checks, bets].concat()
+    };
+
+    let line = [Action::Check; 5];
+    assert_eq!(game.lock_line_all_runouts(&line, strategy_fn), Ok(49 * 48));
+    assert!(game.history().is_empty());
+
+    // every runout, including the isomorphic ones, has the expected locking strategy
+    for turn in 0..52 {
+        for river in 0..52 {
+            let history = [0, 0, turn, 0, 0, river, 0];
+            game.back_to_root();
+            game.play(0);
+            game.play(0);
+            if game.possible_cards() & (1 << turn) == 0 {
+                continue;
+            }
+            game.play(turn);
+            game.play(0);
+            game.play(0);
+            if game.possible_cards() & (1 << river) == 0 {
+                continue;
+            }
+            game.apply_history(&history);
+
+            let mut expected = strategy_fn(&game);
+            let locking = game.current_locking_strategy().unwrap();
+            let num_hands = game.private_cards(1).len();
+            for hand in 0..num_hands {
+                if game.weights(1)[hand] == 0.0 {
+                    expected[hand] = locking[hand];
+                    expected[num_hands + hand] = locking[num_hands + hand];
+                }
+            }
+            assert_eq!(locking, expected);
+        }
+    }
+
+    // 2c and 2s are isomorphic on the turn and share the same node: OOP checks with clubs and
+    // bets with spades, and 2s takes precedence because it is visited later
+    let strategy_fn = |game: &PostFlopGame| {
+        let num_actions = game.available_actions().len();
+        let num_hands = game.private_cards(0).len();
+        let action = match game.current_board()[3] & 3 {
+            3 => num_actions - 1,
+            _ => 0,
+        };
+        let mut strategy = vec![0.0; num_actions * num_hands];
+        strategy[action * num_hands..(action + 1) * num_hands].fill(1.0);
+        strategy
+    };
+
+    let line = [Action::Check; 2];
+    assert_eq!(game.lock_line_all_runouts(&line, strategy_fn), Ok(49));
+
+    let spade = card_from_str("2s").unwrap() as usize;
+    game.apply_history(&[0, 0, spade]);
+    let expected = strategy_fn(&game);
+    assert_eq!(game.current_locking_strategy(), Some(expected.clone()));
+
+    let club = card_from_str("2c").unwrap() as usize;
+    game.apply_history(&[0, 0, club]);
+    assert_eq!(game.current_locking_strategy(), Some(expected));
+
+    // invalid lines
+    assert!(game
+        .lock_line_all_runouts(&[Action::Fold], strategy_fn)
+        .is_err());
+    let line = [
+        Action::Check,
+        Action::Check,
+        Action::Chance(card_from_str("Td").unwrap()),
+    ];
+    assert!(game.lock_line_all_runouts(&line, strategy_fn).is_err());
+}
+
+#[test]
+fn node_locking_lock_set() {
+    let build_game = |flop: &str| {
+        let card_config = CardConfig {
+            range: ["AA,KK,T9s".parse().unwrap(), "QQ,AKs,87s".parse().unwrap()],
+            flop: flop_from_str(flop).unwrap(),
+            ..Default::default()
+        };
+        let tree_config = TreeConfig {
+            starting_pot: 100,
+            effective_stack: 200,
+            flop_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [
+                ("50%", "").try_into().unwrap(),
+                ("50%", "").try_into().unwrap(),
+            ],
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    // OOP bets AA on the flop
+    let lock_root = |game: &mut PostFlopGame| {
+        let num_actions = game.available_actions().len();
+        let bets = game
+            .private_cards(0)
+            .iter()
+            .map(|&(c1, c2)| (c1 / 4 == 12 && c2 / 4 == 12) as u8 as f32)
+            .collect::<Vec<_>>();
+        let checks = bets.iter().map(|&b| 1.0 - b).collect::<Vec<_>>();
+        let mut strategy = checks;
+        strategy.resize(bets.len() * (num_actions - 1), 0.0);
+        strategy.extend(bets);
+        game.lock_current_strategy(&strategy);
+    };
+
+    let mut game = build_game("Td9d6h");
+    lock_root(&mut game);
+    game.play(0);
+    let num_actions = game.available_actions().len();
+    game.lock_current_frequencies(&vec![1.0; num_actions]);
     game.back_to_root();
+    let strategy_fn = |game: &PostFlopGame| vec![0.5; 2 * game.private_cards(1).len()];
+    let line = [Action::Check; 5];
+    assert!(game.lock_line_all_runouts(&line, strategy_fn).is_ok());
+
+    let lock_set = game.export_lock_set();
+    assert!(lock_set.entries.len() > 2);
+    assert!(lock_set.entries[0].line.is_empty());
+    assert!(lock_set.entries[0].frequencies.is_none());
+    assert_eq!(lock_set.entries[1].line, vec![Action::Check]);
+    assert!(lock_set.entries[1].strategy.is_empty());
+    assert!(lock_set.entries[1].frequencies.is_some());
+
+    // importing into a game with the same board reproduces the locks
+    let mut same = build_game("Td9d6h");
+    assert!(same.import_lock_set(&lock_set).is_empty());
+    assert_eq!(same.export_lock_set(), lock_set);
+    assert_eq!(
+        same.current_locking_strategy(),
+        game.current_locking_strategy()
+    );
 
-    solve(&mut game, 1000, 0.0, false);
+    // entries dealing a card on the new flop do not match
+    let mut other = build_game("Ah7c2d");
+    let flop = flop_from_str("Ah7c2d").unwrap();
+    let unmatched = other.import_lock_set(&lock_set);
+    assert!(!unmatched.is_empty());
+    assert!(unmatched.iter().all(|line| line
+        .iter()
+        .any(|&action| matches!(action, Action::Chance(card) if flop.contains(&card)))));
+
+    // hands conflicting with the original flop are not locked
+    let mut expected = build_game("Ah7c2d");
+    lock_root(&mut expected);
+    let mut expected = expected.current_locking_strategy().unwrap();
+    let original_flop = flop_from_str("Td9d6h").unwrap();
+    let private_cards = other.private_cards(0);
+    for (hand, &(c1, c2)) in private_cards.iter().enumerate() {
+        if original_flop.contains(&c1) || original_flop.contains(&c2) {
+            for action in 0..expected.len() / private_cards.len() {
+                expected[action * private_cards.len() + hand] = -1.0;
+            }
+        }
+    }
+    assert_eq!(other.current_locking_strategy(), Some(expected));
+    other.play(0);
+    assert_eq!(
+        other.current_locking_frequencies(),
+        Some(vec![1.0 / num_actions as f32; num_actions])
+    );
+
+    // mismatched actions and invalid hands
+    let mut entry = lock_set.entries[0].clone();
+    entry.actions.pop();
+    let mut invalid = lock_set.entries[0].clone();
+    invalid.line = vec![Action::Bet(50)];
+    invalid.strategy[0].0 = "AsAs".to_string();
+    let lock_set = LockSet {
+        entries: vec![entry, invalid],
+    };
+    let mut game = build_game("Td9d6h");
+    assert_eq!(
+        game.import_lock_set(&lock_set),
+        vec![vec![], vec![Action::Bet(50)]]
+    );
+}
+
+#[test]
+fn exploit_report() {
+    let build_game = || {
+        let card_config = CardConfig {
+            range: ["AA,QQ,JJ,T9s".parse().unwrap(), "KK,AKs".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str("2c").unwrap(),
+            ..Default::default()
+        };
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 100,
+            effective_stack: 100,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        game
+    };
+
+    let mut baseline = build_game();
+    solve(&mut baseline, 1000, 0.1, false);
+
+    // OOP always checks the turn
+    let mut game = build_game();
+    let num_hands = game.private_cards(0).len();
+    game.lock_current_strategy(&[vec![1.0; num_hands], vec![0.0; num_hands]].concat());
+    solve(&mut game, 1000, 0.1, false);
+
+    game.play(0);
+    let history = game.history().to_vec();
+    let report = game
+        .exploit_report(&mut baseline, BoardState::River)
+        .unwrap();
+    assert_eq!(game.history(), history);
+    assert!(baseline.history().is_empty());
+
+    assert!(report.gain[0] < 0.0);
+    assert!(report.max_gain[1] >= report.gain[1] - 0.1);
+
+    let root = &report.nodes[0];
+    assert!(root.line.is_empty());
+    assert!(root.is_locked);
+    assert_eq!(root.reach, [1.0, 1.0]);
+    for player in 0..2 {
+        assert!((root.gain[player] - report.gain[player]).abs() < 1e-3);
+        let category_sum = root.categories[player].iter().map(|&(_, g)| g).sum::<f32>();
+        assert!((category_sum - root.gain[player]).abs() < 1e-3);
+    }
+
+    // the reach probabilities of the river nodes after checks on the turn sum up to the
+    // probability of IP checking behind
+    let river_nodes = report
+        .nodes
+        .iter()
+        .filter(|node| node.line.len() == 3 && node.line[..2] == [Action::Check; 2])
+        .collect::<Vec<_>>();
+    assert_eq!(river_nodes.len(), 48);
+    let turn_ip = &report.nodes[1];
+    assert_eq!(turn_ip.line, vec![Action::Check]);
+    let check_freq = river_nodes.iter().map(|node| node.reach[0]).sum::<f32>();
+    game.back_to_root();
+    game.play(0);
     game.cache_normalized_weights();
+    let strategy = game.strategy();
+    let weights = game.normalized_weights(1);
+    let expected = strategy[..weights.len()]
+        .iter()
+        .zip(weights)
+        .map(|(&s, &w)| s * w)
+        .sum::<f32>()
+        / weights.iter().sum::<f32>()
+        * turn_ip.reach[0];
+    assert!((check_freq - expected).abs() < 1e-3);
+
+    // nodes on the river are not reported with `BoardState::Turn`
+    let report = game
+        .exploit_report(&mut baseline, BoardState::Turn)
+        .unwrap();
+    assert!(report.nodes.len() < river_nodes.len());
+    assert!(report.nodes.iter().all(|node| node.line.len() < 3));
+
+    // mismatched games
+    let card_config = CardConfig {
+        range: ["AA".parse().unwrap(), "KK".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(game.tree_config().clone()).unwrap();
+    let mut other = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    other.allocate_memory(false);
+    finalize(&mut other);
+    assert!(game.exploit_report(&mut other, BoardState::Turn).is_err());
+}
 
-    let ev_oop = game.expected_values(0);
-    let ev_ip = game.expected_values(1);
-    assert!((ev_oop[0] - 5.0).abs() < 1e-2);
-    assert!((ev_oop[1] - 25.0).abs() < 5e-2);
-    assert!((ev_ip[0] - 5.0).abs() < 1e-2);
+#[test]
+fn best_response() {
+    let build_game = || river_toy_game(100, "50%");
+
+    // OOP bets with `bet_hands` and checks otherwise
+    let lock_bets = |game: &mut PostFlopGame, bet_hands: &str| {
+        let bet_range = bet_hands.parse::<Range>().unwrap();
+        let bets = game
+            .private_cards(0)
+            .iter()
+            .map(|&(c1, c2)| (bet_range.get_weight_by_cards(c1, c2) > 0.0) as u8 as f32)
+            .collect::<Vec<_>>();
+        let checks = bets.iter().map(|&b| 1.0 - b).collect::<Vec<_>>();
+        game.lock_current_strategy(&[checks, bets].concat());
+        finalize(game);
+        game.play(1);
+    };
 
-    let strategy_oop = game.strategy();
-    assert!((strategy_oop[0] - 0.0).abs() < 1e-3); // QQ check
-    assert!((strategy_oop[1] - 0.0).abs() < 1e-3); // AA check
-    assert!((strategy_oop[2] - 1.0).abs() < 1e-3); // QQ bet
-    assert!((strategy_oop[3] - 1.0).abs() < 1e-3); // AA bet
+    // IP calls when OOP bets with AA and QQ
+    let mut game = build_game();
+    lock_bets(&mut game, "AA,QQ");
+    assert_eq!(game.available_actions()[..2], [Action::Fold, Action::Call]);
+    let num_hands = game.private_cards(1).len();
+    let best_response = game.current_best_response();
+    assert_eq!(
+        best_response[num_hands..2 * num_hands],
+        vec![1.0; num_hands]
+    );
+
+    // IP folds when OOP bets only with AA
+    let mut game = build_game();
+    lock_bets(&mut game, "AA");
+    let best_response = game.current_best_response();
+    assert_eq!(best_response[..num_hands], vec![1.0; num_hands]);
+    assert_eq!(game.history(), [1]);
+
+    // the lock set contains every decision node of IP
+    let lock_set = game.best_response_lock_set(1);
+    assert_eq!(game.history(), [1]);
+    let lines = lock_set
+        .entries
+        .iter()
+        .map(|entry| entry.line.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(lines, vec![vec![Action::Check], vec![Action::Bet(50)]]);
+    let entry = &lock_set.entries[1];
+    assert_eq!(entry.strategy.len(), 6);
+    assert!(entry.strategy.iter().all(|(_, s)| s[0] == 1.0));
+
+    let mut other = build_game();
+    assert!(other.import_lock_set(&lock_set).is_empty());
+    other.play(1);
+    assert_eq!(other.current_locking_strategy(), Some(best_response));
+}
+
+#[test]
+fn exploitability_report() {
+    let card_config = CardConfig {
+        range: ["AA,QQ,JJ,T9s".parse().unwrap(), "KK,AKs".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 100,
+        turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    let exploitability = solve(&mut game, 5, 0.0, false);
+
+    // the contributions of all nodes sum up to the exploitability
+    let report = game.exploitability_report(usize::MAX);
+    assert!((report.exploitability - exploitability).abs() < 1e-3);
+    let sum = report
+        .nodes
+        .iter()
+        .map(|node| node.exploitability)
+        .sum::<f32>();
+    assert!((sum - exploitability).abs() < 1e-2 * exploitability.max(1.0));
+    assert_eq!(report.streets[BoardState::Flop as usize], 0.0);
+    let street_sum = report.streets.iter().sum::<f32>();
+    assert!((street_sum - exploitability).abs() < 1e-2 * exploitability.max(1.0));
+    assert!(report
+        .nodes
+        .windows(2)
+        .all(|w| w[0].exploitability >= w[1].exploitability));
+    assert!(report.nodes.iter().all(|node| node.exploitability >= -1e-4));
+
+    let root = report.nodes.iter().find(|n| n.line.is_empty()).unwrap();
+    assert_eq!(root.street, BoardState::Turn);
+    assert!((root.reach - 1.0).abs() < 1e-6);
+
+    // top nodes
+    let top = game.exploitability_report(3);
+    assert_eq!(top.nodes, report.nodes[..3]);
+    assert!(game.history().is_empty());
+}
+
+#[test]
+fn diff_solutions_ranges() {
+    let build_game = |oop_range: &str, flop: &str| {
+        let card_config = CardConfig {
+            range: [oop_range.parse().unwrap(), "KK,AKs".parse().unwrap()],
+            flop: flop_from_str(flop).unwrap(),
+            turn: card_from_str("2c").unwrap(),
+            ..Default::default()
+        };
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 100,
+            effective_stack: 100,
+            turn_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 100, 0.1, false);
+        game
+    };
+
+    // identical solutions
+    let mut game_a = build_game("AA,QQ,JJ,T9s", "Td9d6h");
+    let mut game_b = build_game("AA,QQ,JJ,T9s", "Td9d6h");
+    let diffs = diff_solutions(&mut game_a, &mut game_b, BoardState::River, 3).unwrap();
+    assert!(diffs.len() > 48);
+    assert!(diffs
+        .iter()
+        .all(|diff| diff.frequencies[0] == diff.frequencies[1]
+            && diff.ev[0] == diff.ev[1]
+            && diff.combos.len() <= 3
+            && diff.combos.iter().all(|combo| combo.change == 0.0)));
+
+    // adding hands to the OOP range
+    let mut game_c = build_game("AA,QQ,JJ,T9s,87s", "Td9d6h");
+    game_c.play(1);
+    let diffs = diff_solutions(&mut game_a, &mut game_c, BoardState::Turn, usize::MAX).unwrap();
+    assert_eq!(game_c.history(), [1]);
+    let root = &diffs[0];
+    assert!(root.line.is_empty());
+    assert_eq!(root.player, 0);
+    assert_eq!(root.actions, game_a.available_actions());
+    assert_ne!(root.frequencies[0], root.frequencies[1]);
+    assert!(root.combos.iter().all(|combo| !combo.hand.starts_with('8')));
+    assert!(root.combos.windows(2).all(|w| w[0].change >= w[1].change));
+    for combo in &root.combos {
+        let sum = combo.strategy.iter().map(|s| s.iter().sum::<f32>());
+        assert!(sum.into_iter().all(|s| (s - 1.0).abs() < 1e-4));
+    }
+    assert!(diffs.iter().all(|diff| diff.line.len() < 3));
+
+    // different boards
+    let mut game_d = build_game("AA,QQ,JJ,T9s", "Td9d7h");
+    assert!(diff_solutions(&mut game_a, &mut game_d, BoardState::River, 3).is_err());
+}
+
+#[test]
+fn diff_solutions_bet_sizes() {
+    let build_game = |bet_sizes: &str| {
+        let mut game = river_toy_game(100, bet_sizes);
+        solve(&mut game, 1000, 0.01, false);
+        game
+    };
+
+    // the action missing in the game without the 33% bet has zero frequency
+    let mut game_a = build_game("50%");
+    let mut game_b = build_game("33%, 50%");
+    let diffs = diff_solutions(&mut game_a, &mut game_b, BoardState::River, 5).unwrap();
+    let root = &diffs[0];
+    assert_eq!(
+        root.actions,
+        vec![Action::Check, Action::Bet(50), Action::Bet(33)]
+    );
+    assert_eq!(root.frequencies[0][2], 0.0);
+}
+
+#[test]
+fn simplify_bet_sizes() {
+    let card_config = CardConfig {
+        range: ["AA,QQ,JJ".parse().unwrap(), "KK".parse().unwrap()],
+        flop: flop_from_str("2c3d4h").unwrap(),
+        turn: card_from_str("5s").unwrap(),
+        river: card_from_str("9s").unwrap(),
+        ..Default::default()
+    };
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 200,
+        river_bet_sizes: [("33%, 100%", "").try_into().unwrap(), Default::default()],
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config.clone()).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.01, false);
+
+    // nothing is removed with a negative tolerance
+    let advice = game.simplify_bet_sizes(-1.0, 1000, 0.01).unwrap();
+    let lines = advice
+        .candidates
+        .iter()
+        .map(|candidate| candidate.line.clone())
+        .collect::<BTreeSet<_>>();
+    let expected = BTreeSet::from([vec![Action::Bet(33)], vec![Action::Bet(100)]]);
+    assert_eq!(lines, expected);
+    assert!(advice.candidates.iter().all(|c| c.player == 0));
+    assert!(advice
+        .candidates
+        .windows(2)
+        .all(|w| w[0].ev_loss <= w[1].ev_loss));
+    assert!(advice.removed_lines.is_empty());
+    assert_eq!(advice.simplified_ev, advice.ev);
+
+    // every bet is removed with a large tolerance, and OOP loses the value of AA
+    let advice = game.simplify_bet_sizes(100.0, 1000, 0.01).unwrap();
+    assert_eq!(advice.removed_lines.len(), 2);
+    assert!(advice.simplified_ev[0] < advice.ev[0]);
+
+    let mut action_tree = ActionTree::new(tree_config).unwrap();
+    for line in &advice.removed_lines {
+        action_tree.remove_line(line).unwrap();
+    }
+    assert_eq!(action_tree.available_actions(), [Action::Check]);
 }
 
 #[test]
@@ -915,6 +2468,7 @@ fn node_locking_partial() {
         flop: flop_from_str("2s3h4d").unwrap(),
         turn: card_from_str("6c").unwrap(),
         river: card_from_str("7c").unwrap(),
+        variant: GameVariant::Holdem,
     };
 
     let tree_config = TreeConfig {
@@ -1248,3 +2802,268 @@ fn solve_pio_preset_raked() {
     assert!((root_ev_oop - 95.57).abs() < 0.2);
     assert!((root_ev_ip - 66.98).abs() < 0.2);
 }
+
+#[test]
+fn simulate_hands() {
+    let build_game = |turn: &str| {
+        let card_config = CardConfig {
+            range: ["AA,QQ,JJ,T9s".parse().unwrap(), "KK,AKs".parse().unwrap()],
+            flop: flop_from_str("Td9d6h").unwrap(),
+            turn: card_from_str(turn).unwrap(),
+            ..Default::default()
+        };
+        let tree_config = TreeConfig {
+            initial_state: BoardState::Turn,
+            starting_pot: 100,
+            effective_stack: 100,
+            rake_rate: 0.05,
+            rake_cap: 10.0,
+            turn_bet_sizes: [("50%, a", "").try_into().unwrap(), Default::default()],
+            river_bet_sizes: [("50%", "").try_into().unwrap(), Default::default()],
+            ..Default::default()
+        };
+        let action_tree = ActionTree::new(tree_config).unwrap();
+        let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+        game.allocate_memory(false);
+        solve(&mut game, 1000, 0.1, false);
+        game
+    };
+
+    let mut game = build_game("2c");
+    game.play(0);
+    let history = game.history().to_vec();
+
+    let result = simulate(&mut game, 20000, 1).unwrap();
+    assert_eq!(game.history(), history);
+    assert_eq!(result.num_hands, 20000);
+    assert_eq!(result, simulate(&mut game, 20000, 1).unwrap());
+    assert_ne!(result, simulate(&mut game, 20000, 2).unwrap());
+
+    game.back_to_root();
+    game.cache_normalized_weights();
+    let ev = compute_current_ev(&game);
+    for (player, &ev) in ev.iter().enumerate() {
+        let (lower, upper) = result.confidence_interval[player];
+        assert!(lower < result.mean[player] && result.mean[player] < upper);
+        assert!((result.mean[player] - ev).abs() < 4.0 * result.std_error[player]);
+    }
+
+    // the same strategies played head-to-head give the same result
+    let mut other = build_game("2c");
+    let head_to_head = simulate_head_to_head(&mut game, &mut other, 20000, 1).unwrap();
+    assert_eq!(head_to_head, result);
+
+    let mut other = build_game("3c");
+    assert!(simulate_head_to_head(&mut game, &mut other, 100, 1).is_err());
+    assert!(simulate(&mut game, 0, 1).is_err());
+}
+
+#[test]
+fn review_hand() {
+    let text = "\
+PokerStars Hand #4001: Hold'em No Limit ($1/$2 USD)
+Table 'Delta' 6-max Seat #1 is the button
+Seat 1: Villain ($200 in chips)
+Seat 2: Hero ($200 in chips)
+Villain: posts small blind $1
+Hero: posts big blind $2
+*** HOLE CARDS ***
+Dealt to Hero [Qh Qc]
+Villain: raises $3 to $5
+Hero: calls $3
+*** FLOP *** [Td 9d 6h]
+Hero: checks
+Villain: bets $5
+Hero: calls $5
+*** TURN *** [Td 9d 6h] [2c]
+Hero: bets $12
+Villain: calls $12
+*** RIVER *** [Td 9d 6h 2c] [Ks]
+Hero: checks
+Villain: bets $18
+Hero: calls $18
+*** SUMMARY ***
+";
+    let history = text.parse::<HandHistory>().unwrap();
+    assert_eq!(history.hero, Some(0));
+
+    let card_config = CardConfig {
+        range: ["QQ,JJ,T9s".parse().unwrap(), "KK,AKs,88".parse().unwrap()],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        ..Default::default()
+    };
+    let bet_sizes = BetSizeOptions::try_from(("50%", "")).unwrap();
+    let tree_config = TreeConfig {
+        initial_state: BoardState::Turn,
+        starting_pot: 100,
+        effective_stack: 900,
+        turn_bet_sizes: [bet_sizes.clone(), bet_sizes.clone()],
+        river_bet_sizes: [bet_sizes.clone(), bet_sizes],
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.5, false);
+
+    game.play(0);
+    let review = game.review_hand(&history, &Default::default()).unwrap();
+    assert_eq!(game.history(), [0]);
+    assert_eq!(review.hand_id, "4001");
+    assert_eq!(review.scale, 5.0);
+
+    let river = Action::Chance(card_from_str("Ks").unwrap());
+    let lines = review
+        .decisions
+        .iter()
+        .map(|decision| (decision.line.clone(), decision.action))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        vec![
+            (vec![], Action::Bet(50)),
+            (vec![Action::Bet(50), Action::Call, river], Action::Check),
+            (
+                vec![
+                    Action::Bet(50),
+                    Action::Call,
+                    river,
+                    Action::Check,
+                    Action::Bet(100)
+                ],
+                Action::Call
+            ),
+        ]
+    );
+
+    for decision in &review.decisions {
+        assert_eq!(decision.strategy.len(), decision.actions.len());
+        assert_eq!(decision.ev.len(), decision.actions.len());
+        assert!((decision.strategy.iter().sum::<f32>() - 1.0).abs() < 1e-3);
+        assert!(decision.ev_loss >= 0.0);
+    }
+    let ev_loss = review.decisions.iter().map(|d| d.ev_loss).sum::<f32>();
+    assert_eq!(review.ev_loss, ev_loss);
+
+    // $12 into $20 is too far from the 50% bet with the strict tolerance
+    let config = HandReviewConfig {
+        bet_size_tolerance: 0.1,
+        ..Default::default()
+    };
+    assert!(game.review_hand(&history, &config).is_err());
+    assert_eq!(game.history(), [0]);
+
+    // but it can be translated into the tree
+    let config = HandReviewConfig {
+        bet_size_tolerance: 0.1,
+        translation: Some(TranslationMethod::PseudoHarmonic),
+    };
+    assert_eq!(game.review_hand(&history, &config).unwrap(), review);
+
+    let mut history = history;
+    let ace_club = card_from_str("Ac").unwrap();
+    let ace_heart = card_from_str("Ah").unwrap();
+    history.hero_cards = Some((ace_club, ace_heart));
+    assert!(game.review_hand(&history, &Default::default()).is_err());
+}
+
+#[test]
+fn translate_bet() {
+    let card_config = CardConfig {
+        range: [
+            "AA,QQ,JJ,T9s".parse().unwrap(),
+            "KK,AKs,88".parse().unwrap(),
+        ],
+        flop: flop_from_str("Td9d6h").unwrap(),
+        turn: card_from_str("2c").unwrap(),
+        river: card_from_str("Ks").unwrap(),
+        ..Default::default()
+    };
+    let tree_config = TreeConfig {
+        initial_state: BoardState::River,
+        starting_pot: 100,
+        effective_stack: 1000,
+        river_bet_sizes: [
+            ("50%, 100%, a", "").try_into().unwrap(),
+            ("", "3x, 5x").try_into().unwrap(),
+        ],
+        ..Default::default()
+    };
+    let action_tree = ActionTree::new(tree_config).unwrap();
+    let mut game = PostFlopGame::with_config(card_config, action_tree).unwrap();
+    game.allocate_memory(false);
+    solve(&mut game, 1000, 0.5, false);
+
+    // [Check, Bet(50), Bet(100), AllIn(1000)]
+    let actions = game.available_actions();
+    assert_eq!(actions.len(), 4);
+    let method = TranslationMethod::PseudoHarmonic;
+    assert_eq!(game.translate_bet(50.0, method), vec![(1, 1.0)]);
+    assert_eq!(game.translate_bet(20.0, method), vec![(1, 1.0)]);
+    assert_eq!(game.translate_bet(1000.0, method), vec![(3, 1.0)]);
+    assert_eq!(
+        game.translate_bet(200.0, TranslationMethod::Nearest),
+        vec![(2, 1.0)]
+    );
+    let mix = game.translate_bet(300.0, method);
+    assert_eq!((mix[0].0, mix[1].0), (2, 3));
+    assert!((mix[0].1 + mix[1].1 - 1.0).abs() < 1e-6);
+
+    // the pseudo-harmonic mapping is monotone
+    let probs = (51..100)
+        .map(|amount| game.translate_bet(amount as f64, method)[0].1)
+        .collect::<Vec<_>>();
+    assert!(probs.windows(2).all(|w| w[0] > w[1]));
+
+    // IP raises after Bet(50): [Fold, Call, Raise(150), Raise(250)]
+    game.play(1);
+    assert_eq!(
+        game.available_actions()[2..],
+        [Action::Raise(150), Action::Raise(250)]
+    );
+    // raise to 200 is 150 into the pot of 200 after calling, between 100 / 200 and 200 / 200
+    let (a, b, x) = (0.5, 1.0, 0.75);
+    let mix = game.translate_bet(200.0, method);
+    assert_eq!((mix[0].0, mix[1].0), (2, 3));
+    assert!((mix[0].1 as f64 - (b - x) * (1.0 + a) / ((b - a) * (1.0 + x))).abs() < 1e-6);
+
+    // interpolated response to a bet of 75
+    game.back_to_root();
+    let mix = game.translate_bet(75.0, method);
+    let (actions, strategy) = game.translated_strategy(75.0, method).unwrap();
+    assert!(game.history().is_empty());
+    assert_eq!(actions[..2], [Action::Fold, Action::Call]);
+
+    let num_hands = game.private_cards(1).len();
+    let mut expected = vec![0.0; num_hands];
+    for &(action, prob) in &mix {
+        game.play(action);
+        let child = game.strategy();
+        for (e, &s) in expected.iter_mut().zip(&child[num_hands..2 * num_hands]) {
+            *e += prob * s;
+        }
+        game.back_to_root();
+    }
+    for (&e, &s) in expected.iter().zip(&strategy[num_hands..2 * num_hands]) {
+        assert!((e - s).abs() < 1e-6);
+    }
+    for hand in 0..num_hands {
+        let sum = (0..actions.len())
+            .map(|i| strategy[i * num_hands + hand])
+            .sum::<f32>();
+        assert!((sum - 1.0).abs() < 1e-5);
+    }
+
+    // 75% pot between 50% and 100%: (1.0 - 0.75) * (1.0 + 0.5) / ((1.0 - 0.5) * (1.0 + 0.75))
+    let game = river_toy_game(1000, "50%, 100%");
+    assert_eq!(
+        game.available_actions()[1..3],
+        [Action::Bet(50), Action::Bet(100)]
+    );
+    let mix = game.translate_bet(75.0, method);
+    assert_eq!((mix[0].0, mix[1].0), (1, 2));
+    assert!((mix[0].1 - 3.0 / 7.0).abs() < 1e-6);
+    let nearest = game.translate_bet(70.0, TranslationMethod::Nearest);
+    assert_eq!(nearest, vec![(1, 1.0)]);
+}
@@ -0,0 +1,184 @@
+use super::*;
+use crate::interface::*;
+
+/// Method of translating an off-tree bet size into the actions of the game tree.
+///
+/// See [`PostFlopGame::translate_bet`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TranslationMethod {
+    /// Maps onto the nearest bet size.
+    Nearest,
+
+    /// Pseudo-harmonic mapping, which randomizes between the two neighboring bet sizes
+    /// (Ganzfried and Sandholm, 2013).
+    #[default]
+    PseudoHarmonic,
+}
+
+impl PostFlopGame {
+    /// Translates a bet or raise of `amount` that may not exist in the game tree into the bet,
+    /// raise, and all-in actions available at the current node.
+    ///
+    /// As in [`Action`], `amount` is the total amount that the current player puts in on the
+    /// current street. The returned vector contains pairs of an action index (i.e., an index of
+    /// [`available_actions`]) and its probability, in ascending order of the bet amount. The
+    /// probabilities sum up to 1.
+    ///
+    /// The bet sizes are compared relative to the pot after calling. With
+    /// [`TranslationMethod::PseudoHarmonic`], a size `x` between the neighboring sizes `a < x < b`
+    /// is mapped onto `a` with probability `(b - x) * (1 + a) / ((b - a) * (1 + x))` and onto `b`
+    /// otherwise. A size smaller (larger) than all the sizes in the tree is mapped onto the
+    /// smallest (largest) one. If `amount` matches an action in the tree, that action is returned
+    /// with probability 1.
+    ///
+    /// Returns an empty vector if the current player cannot bet or raise. Panics if the current
+    /// node is a terminal node or a chance node.
+    ///
+    /// [`available_actions`]: #method.available_actions
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use postflop_solver::*;
+    ///
+    /// // the river game with the pot of 100 and the bet sizes of 50% and 100%
+    /// let (game, _): (PostFlopGame, _) = load_data_from_file("river.flop", None).unwrap();
+    ///
+    /// // 75% pot is mapped onto 50% pot with probability 3 / 7 and onto 100% pot otherwise
+    /// let mix = game.translate_bet(75.0, TranslationMethod::PseudoHarmonic);
+    /// assert_eq!(mix.len(), 2);
+    /// ```
+    pub fn translate_bet(&self, amount: f64, method: TranslationMethod) -> Vec<(usize, f32)> {
+        if self.is_terminal_node() {
+            panic!("Terminal node is not allowed");
+        }
+
+        if self.is_chance_node() {
+            panic!("Chance node is not allowed");
+        }
+
+        let mut candidates = self
+            .available_actions()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, action)| match action {
+                Action::Bet(x) | Action::Raise(x) | Action::AllIn(x) => {
+                    Some((i, self.bet_fraction(x as f64)))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        if candidates.is_empty() {
+            return Vec::new();
+        }
+
+        candidates.sort_unstable_by(|a, b| a.1.total_cmp(&b.1));
+        let x = self.bet_fraction(amount);
+
+        // index of the smallest candidate whose size is not smaller than `x`
+        let upper = candidates.partition_point(|&(_, size)| size < x);
+        if upper == 0 {
+            return vec![(candidates[0].0, 1.0)];
+        }
+        if upper == candidates.len() {
+            return vec![(candidates[upper - 1].0, 1.0)];
+        }
+
+        let (index_a, a) = candidates[upper - 1];
+        let (index_b, b) = candidates[upper];
+        if b == x {
+            return vec![(index_b, 1.0)];
+        }
+
+        match method {
+            TranslationMethod::Nearest => {
+                if x - a <= b - x {
+                    vec![(index_a, 1.0)]
+                } else {
+                    vec![(index_b, 1.0)]
+                }
+            }
+            TranslationMethod::PseudoHarmonic => {
+                let prob_a = ((b - x) * (1.0 + a) / ((b - a) * (1.0 + x))) as f32;
+                vec![(index_a, prob_a), (index_b, 1.0 - prob_a)]
+            }
+        }
+    }
+
+    /// Returns the strategy of the opponent responding to a bet or raise of `amount` that may not
+    /// exist in the game tree, by interpolating the strategies after the translated actions.
+    ///
+    /// The bet is translated by the [`translate_bet`] method, and the strategies of the nodes
+    /// after the translated actions are mixed with the translated probabilities. The first element
+    /// of the return value is the union of the actions available at those nodes, sorted in the
+    /// same order as [`available_actions`]. The second element has the same layout as the
+    /// [`strategy`] method with respect to these actions; an action missing at some node is
+    /// treated as having zero probability there. The current node is preserved.
+    ///
+    /// Returns `None` if the current player cannot bet or raise. Panics if the memory is not yet
+    /// allocated or if the current node is a terminal node or a chance node.
+    ///
+    /// [`translate_bet`]: #method.translate_bet
+    /// [`available_actions`]: #method.available_actions
+    /// [`strategy`]: #method.strategy
+    pub fn translated_strategy(
+        &mut self,
+        amount: f64,
+        method: TranslationMethod,
+    ) -> Option<(Vec<Action>, Vec<f32>)> {
+        if self.state < State::MemoryAllocated {
+            panic!("Memory is not allocated");
+        }
+
+        let translation = self.translate_bet(amount, method);
+        if translation.is_empty() {
+            return None;
+        }
+
+        let history = self.action_history.clone();
+        let player = self.current_player() ^ 1;
+        let num_hands = self.num_private_hands(player);
+
+        let mut children = Vec::with_capacity(translation.len());
+        for &(action, prob) in &translation {
+            self.play(action);
+            children.push((self.available_actions(), self.strategy(), prob));
+            self.apply_history(&history);
+        }
+
+        let mut actions = children
+            .iter()
+            .flat_map(|(actions, _, _)| actions.iter().cloned())
+            .collect::<Vec<_>>();
+        actions.sort_unstable();
+        actions.dedup();
+
+        let mut ret = vec![0.0; actions.len() * num_hands];
+        for (child_actions, strategy, prob) in &children {
+            for (i, action) in child_actions.iter().enumerate() {
+                let index = actions.binary_search(action).unwrap();
+                let dst = &mut ret[index * num_hands..(index + 1) * num_hands];
+                let src = &strategy[i * num_hands..(i + 1) * num_hands];
+                dst.iter_mut().zip(src).for_each(|(d, &s)| *d += prob * s);
+            }
+        }
+
+        Some((actions, ret))
+    }
+
+    /// Returns the size of the bet or raise to `amount` at the current node, relative to the pot
+    /// after calling.
+    fn bet_fraction(&self, amount: f64) -> f64 {
+        let node = self.node_arena[self.node_history.last().cloned().unwrap_or(0)].lock();
+        let prev_bet_amount = match node.prev_action {
+            Action::Bet(a) | Action::Raise(a) | Action::AllIn(a) => a,
+            _ => 0,
+        };
+
+        let player = node.player();
+        let [oop_amount, ip_amount] = self.total_bet_amount;
+        let to_call = self.total_bet_amount[player ^ 1] - self.total_bet_amount[player];
+        let pot = self.tree_config.starting_pot + oop_amount + ip_amount + to_call;
+        (amount - prev_bet_amount as f64) / pot as f64
+    }
+}
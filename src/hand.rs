@@ -1,4 +1,5 @@
 use crate::hand_table::*;
+use once_cell::sync::Lazy;
 
 #[derive(Clone, Copy, Default)]
 pub(crate) struct Hand {
@@ -7,7 +8,7 @@ pub(crate) struct Hand {
 }
 
 #[inline]
-fn keep_n_msb(mut x: i32, n: i32) -> i32 {
+pub(crate) fn keep_n_msb(mut x: i32, n: i32) -> i32 {
     let mut ret = 0;
     for _ in 0..n {
         let bit = 1 << (x.leading_zeros() ^ 31);
@@ -18,18 +19,26 @@ fn keep_n_msb(mut x: i32, n: i32) -> i32 {
 }
 
 #[inline]
-fn find_straight(rankset: i32) -> i32 {
+pub(crate) fn find_straight(rankset: i32, short_deck: bool) -> i32 {
     const WHEEL: i32 = 0b1_0000_0000_1111;
+    const SHORT_DECK_WHEEL: i32 = 0b1_0000_1111_0000;
     let is_straight = rankset & (rankset << 1) & (rankset << 2) & (rankset << 3) & (rankset << 4);
     if is_straight != 0 {
         keep_n_msb(is_straight, 1)
-    } else if (rankset & WHEEL) == WHEEL {
+    } else if !short_deck && (rankset & WHEEL) == WHEEL {
         1 << 3
+    } else if short_deck && (rankset & SHORT_DECK_WHEEL) == SHORT_DECK_WHEEL {
+        // A-6-7-8-9 is encoded as the 9-high straight, which does not exist in the short deck
+        1 << 7
     } else {
         0
     }
 }
 
+/// Start indices of flushes, full houses, and four of a kinds in `HAND_TABLE`.
+static CATEGORY_OFFSETS: Lazy<[usize; 3]> =
+    Lazy::new(|| [5, 6, 7].map(|category| HAND_TABLE.partition_point(|&x| x < category << 26)));
+
 impl Hand {
     #[inline]
     pub fn new() -> Hand {
@@ -51,10 +60,31 @@ impl Hand {
 
     #[inline]
     pub fn evaluate(&self) -> u16 {
-        HAND_TABLE.binary_search(&self.evaluate_internal()).unwrap() as u16
+        HAND_TABLE
+            .binary_search(&self.evaluate_internal(false))
+            .unwrap() as u16
+    }
+
+    /// Evaluates the hand with the short-deck rules.
+    ///
+    /// The returned index is remapped so that flushes rank above full houses.
+    #[inline]
+    pub fn evaluate_short_deck(&self) -> u16 {
+        let index = HAND_TABLE
+            .binary_search(&self.evaluate_internal(true))
+            .unwrap();
+        let [flush, full_house, quads] = *CATEGORY_OFFSETS;
+        let ret = if (flush..full_house).contains(&index) {
+            index + (quads - full_house)
+        } else if (full_house..quads).contains(&index) {
+            index - (full_house - flush)
+        } else {
+            index
+        };
+        ret as u16
     }
 
-    fn evaluate_internal(&self) -> i32 {
+    fn evaluate_internal(&self, short_deck: bool) -> i32 {
         let mut rankset = 0i32;
         let mut rankset_suit = [0i32; 4];
         let mut rankset_of_count = [0i32; 5];
@@ -79,10 +109,10 @@ impl Hand {
             }
         }
 
-        let is_straight = find_straight(rankset);
+        let is_straight = find_straight(rankset, short_deck);
 
         if flush_suit >= 0 {
-            let is_straight_flush = find_straight(rankset_suit[flush_suit as usize]);
+            let is_straight_flush = find_straight(rankset_suit[flush_suit as usize], short_deck);
             if is_straight_flush != 0 {
                 // straight flush
                 (8 << 26) | is_straight_flush
@@ -129,6 +159,7 @@ impl Hand {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::range::card_from_str;
 
     #[test]
     fn test_all_hands() {
@@ -149,7 +180,7 @@ mod tests {
                                 let hand = hand.add_card(p);
                                 for q in (p + 1)..52 {
                                     let hand = hand.add_card(q);
-                                    let raw_value = hand.evaluate_internal();
+                                    let raw_value = hand.evaluate_internal(false);
                                     let index_result = HAND_TABLE.binary_search(&raw_value);
                                     assert!(index_result.is_ok());
                                     appeared[index_result.unwrap()] = true;
@@ -173,4 +204,30 @@ mod tests {
         assert_eq!(counter[1], 58627800); // one pair
         assert_eq!(counter[0], 23294460); // high card
     }
+
+    #[test]
+    fn test_short_deck() {
+        let hand = |cards: &[&str]| {
+            cards.iter().fold(Hand::new(), |hand, &card| {
+                hand.add_card(card_from_str(card).unwrap() as usize)
+            })
+        };
+
+        let flush = hand(&["Ah", "Kh", "Th", "8h", "6h", "6c", "7d"]);
+        let full_house = hand(&["As", "Ad", "Ac", "Kd", "Kc", "6c", "7d"]);
+        let quads = hand(&["6s", "6d", "6c", "6h", "Ac", "Kc", "7d"]);
+        assert!(flush.evaluate() < full_house.evaluate());
+        assert!(flush.evaluate_short_deck() > full_house.evaluate_short_deck());
+        assert!(flush.evaluate_short_deck() < quads.evaluate_short_deck());
+
+        let wheel = hand(&["As", "6d", "7c", "8h", "9c", "Kc", "Kd"]);
+        let ten_high = hand(&["Ts", "6d", "7c", "8h", "9c", "Kc", "Kd"]);
+        let trips = hand(&["As", "Ad", "Ac", "8h", "9c", "Kc", "7d"]);
+        assert!(wheel.evaluate_short_deck() > trips.evaluate_short_deck());
+        assert!(wheel.evaluate_short_deck() < ten_high.evaluate_short_deck());
+        assert!(wheel.evaluate() < trips.evaluate());
+
+        let straight_flush = hand(&["Ac", "6c", "7c", "8c", "9c", "Kd", "Kh"]);
+        assert!(straight_flush.evaluate_short_deck() > quads.evaluate_short_deck());
+    }
 }
@@ -0,0 +1,603 @@
+use crate::action_tree::*;
+use crate::card::*;
+use crate::range::*;
+use std::str::FromStr;
+
+/// An action in a hand history.
+///
+/// As in [`Action`], the amount of a bet or a raise is the total amount that the player has put in
+/// on the current street. The amounts are in the units of the hand history (e.g., dollars).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HistoryAction {
+    /// Fold action.
+    Fold,
+
+    /// Check action.
+    Check,
+
+    /// Call action.
+    Call,
+
+    /// Bet action with a specified amount.
+    Bet(f64),
+
+    /// Raise action with a specified amount.
+    Raise(f64),
+}
+
+/// A postflop action taken by a player in a hand history.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistoryEntry {
+    /// Street on which the action was taken.
+    pub street: BoardState,
+
+    /// Player taking the action (0 for OOP, 1 for IP).
+    pub player: usize,
+
+    /// Action taken.
+    pub action: HistoryAction,
+
+    /// Whether the player is all-in after the action.
+    pub is_all_in: bool,
+}
+
+/// A heads-up postflop hand parsed from a hand history.
+///
+/// The [`HandHistory`] struct implements the [`FromStr`] trait, so you can parse a hand history
+/// using `parse::<HandHistory>()`. Currently, PokerStars-style hand histories of Hold'em are
+/// supported, and exactly two players must see the flop.
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// let text = "\
+/// PokerStars Hand #1001: Hold'em No Limit ($0.50/$1.00 USD) - 2024/01/01 12:00:00 ET
+/// Table 'Alpha' 6-max Seat #3 is the button
+/// Seat 1: Alice ($100 in chips)
+/// Seat 3: Bob ($100 in chips)
+/// Seat 5: Carol ($100 in chips)
+/// Carol: posts small blind $0.50
+/// Alice: posts big blind $1
+/// *** HOLE CARDS ***
+/// Dealt to Bob [Ah Kd]
+/// Bob: raises $2 to $3
+/// Carol: folds
+/// Alice: calls $2
+/// *** FLOP *** [Qs 7h 2c]
+/// Alice: checks
+/// Bob: bets $4.50
+/// Alice: calls $4.50
+/// *** TURN *** [Qs 7h 2c] [9d]
+/// Alice: checks
+/// Bob: checks
+/// *** RIVER *** [Qs 7h 2c 9d] [3s]
+/// Alice: bets $87.50 and is all-in
+/// Bob: folds
+/// Uncalled bet ($87.50) returned to Alice
+/// Alice collected $15.50 from pot
+/// *** SUMMARY ***
+/// Total pot $15.50 | Rake $0
+/// ";
+///
+/// let history = text.parse::<HandHistory>().unwrap();
+/// assert_eq!(history.hand_id, "1001");
+/// assert_eq!(history.players, ["Alice".to_string(), "Bob".to_string()]);
+/// assert_eq!(history.hero, Some(1));
+/// assert_eq!(history.pots, vec![6.5, 15.5, 15.5]);
+/// assert_eq!(history.stacks, [97.0, 97.0]);
+/// assert_eq!(history.actions.len(), 7);
+/// assert_eq!(history.actions[1].action, HistoryAction::Bet(4.5));
+/// assert!(history.actions[5].is_all_in);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HandHistory {
+    /// Hand ID.
+    pub hand_id: String,
+
+    /// Names of the players who saw the flop (OOP, IP).
+    pub players: [String; 2],
+
+    /// Hero (0 for OOP, 1 for IP) if the hero saw the flop.
+    pub hero: Option<usize>,
+
+    /// Private cards of the hero in `(low_id, high_id)` order.
+    pub hero_cards: Option<(Card, Card)>,
+
+    /// Board cards.
+    pub board: Vec<Card>,
+
+    /// Pot at the beginning of each street (flop, turn, river) that was dealt.
+    pub pots: Vec<f64>,
+
+    /// Remaining stacks of the players (OOP, IP) at the beginning of the flop.
+    pub stacks: [f64; 2],
+
+    /// Postflop actions.
+    pub actions: Vec<HistoryEntry>,
+}
+
+/// A player seated at the table.
+struct Seat {
+    number: u32,
+    name: String,
+    stack: f64,
+    is_active: bool,
+    is_folded: bool,
+    street_amount: f64,
+    total_amount: f64,
+}
+
+impl FromStr for HandHistory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut hand_id = None;
+        let mut big_blind = None;
+        let mut button = None;
+        let mut seats = Vec::<Seat>::new();
+        let mut street = None;
+        let mut hero = None;
+        let mut hero_cards = None;
+        let mut board = Vec::new();
+        let mut pots = Vec::new();
+        let mut stacks = Vec::new();
+        let mut actions = Vec::new();
+
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if hand_id.is_none() {
+                if let Some(pos) = line.find("Hand #") {
+                    let id = &line[pos + 6..];
+                    let end = id.find(|c: char| c == ':' || c.is_whitespace());
+                    hand_id = Some(id[..end.unwrap_or(id.len())].to_string());
+                    big_blind = parse_big_blind(line);
+                }
+                continue;
+            }
+
+            if line.starts_with("*** ") {
+                let next_street = if line.starts_with("*** FLOP ***") {
+                    BoardState::Flop
+                } else if line.starts_with("*** TURN ***") {
+                    BoardState::Turn
+                } else if line.starts_with("*** RIVER ***") {
+                    BoardState::River
+                } else if line.starts_with("*** SUMMARY ***") {
+                    break;
+                } else {
+                    continue;
+                };
+
+                board = parse_bracketed_cards(line)?;
+                if board.len() != 3 + next_street as usize {
+                    return Err(format!("Unexpected number of board cards: {line}"));
+                }
+
+                if next_street == BoardState::Flop {
+                    stacks = seats.iter().map(|s| s.stack - s.total_amount).collect();
+                }
+
+                pots.push(seats.iter().map(|s| s.total_amount).sum());
+                seats.iter_mut().for_each(|s| s.street_amount = 0.0);
+                street = Some(next_street);
+                continue;
+            }
+
+            if let Some(rest) = line.strip_prefix("Table ") {
+                if let Some(pos) = rest.find("Seat #") {
+                    let number = rest[pos + 6..].split_whitespace().next().unwrap_or("");
+                    button = Some(parse_number::<u32>(number)?);
+                }
+                continue;
+            }
+
+            if street.is_none() && line.starts_with("Seat ") && line.contains(" in chips") {
+                let (number, rest) = line[5..]
+                    .split_once(": ")
+                    .ok_or_else(|| format!("Invalid seat line: {line}"))?;
+                let chips_pos = rest.find(" in chips").unwrap();
+                let paren_pos = rest[..chips_pos]
+                    .rfind(" (")
+                    .ok_or_else(|| format!("Invalid seat line: {line}"))?;
+                if !rest.contains("is sitting out") {
+                    seats.push(Seat {
+                        number: parse_number(number)?,
+                        name: rest[..paren_pos].to_string(),
+                        stack: parse_amount(&rest[paren_pos + 2..chips_pos])?,
+                        is_active: false,
+                        is_folded: false,
+                        street_amount: 0.0,
+                        total_amount: 0.0,
+                    });
+                }
+                continue;
+            }
+
+            if let Some(rest) = line.strip_prefix("Dealt to ") {
+                if let Some(pos) = rest.rfind(" [") {
+                    let cards = parse_bracketed_cards(&rest[pos..])?;
+                    if cards.len() != 2 {
+                        return Err(format!("Expected two hole cards: {line}"));
+                    }
+                    hero = seats.iter().position(|s| s.name == rest[..pos]);
+                    hero_cards = Some((cards[0].min(cards[1]), cards[0].max(cards[1])));
+                }
+                continue;
+            }
+
+            if let Some(rest) = line.strip_prefix("Uncalled bet (") {
+                let (amount, name) = rest
+                    .split_once(") returned to ")
+                    .ok_or_else(|| format!("Invalid line: {line}"))?;
+                let amount = parse_amount(amount)?;
+                if let Some(seat) = seats.iter_mut().find(|s| s.name == name) {
+                    seat.street_amount -= amount;
+                    seat.total_amount -= amount;
+                }
+                continue;
+            }
+
+            // find the player taking the action (prefer the longest name)
+            let Some(index) = seats
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| {
+                    line.starts_with(s.name.as_str()) && line[s.name.len()..].starts_with(": ")
+                })
+                .max_by_key(|(_, s)| s.name.len())
+                .map(|(i, _)| i)
+            else {
+                continue;
+            };
+
+            let seat = &mut seats[index];
+            let rest = &line[seat.name.len() + 2..];
+            let is_all_in = rest.ends_with("and is all-in");
+            let words = rest.split_whitespace().collect::<Vec<_>>();
+
+            let action = match words.as_slice() {
+                ["folds", ..] => {
+                    seat.is_folded = street.is_none();
+                    HistoryAction::Fold
+                }
+                ["checks", ..] => HistoryAction::Check,
+                ["calls", amount, ..] => {
+                    let amount = parse_amount(amount)?;
+                    seat.street_amount += amount;
+                    seat.total_amount += amount;
+                    HistoryAction::Call
+                }
+                ["bets", amount, ..] => {
+                    let amount = parse_amount(amount)?;
+                    seat.total_amount += amount - seat.street_amount;
+                    seat.street_amount = amount;
+                    HistoryAction::Bet(amount)
+                }
+                ["raises", _, "to", amount, ..] => {
+                    let amount = parse_amount(amount)?;
+                    seat.total_amount += amount - seat.street_amount;
+                    seat.street_amount = amount;
+                    HistoryAction::Raise(amount)
+                }
+                ["posts", rest @ ..] if street.is_none() => {
+                    // the amount follows the keyword, e.g., "posts big blind $1 and is all-in"
+                    let Some(pos) = rest
+                        .iter()
+                        .position(|&w| matches!(w, "blind" | "blinds" | "ante"))
+                    else {
+                        continue;
+                    };
+                    let amount = parse_amount(rest.get(pos + 1).unwrap_or(&""))?;
+                    seat.street_amount += match rest[pos] {
+                        "ante" => 0.0,
+                        // the small blind posted together with the big blind is dead
+                        "blinds" => big_blind
+                            .ok_or_else(|| format!("Big blind not found: {line}"))?
+                            .min(amount),
+                        _ => amount,
+                    };
+                    seat.total_amount += amount;
+                    seat.is_active = true;
+                    continue;
+                }
+                _ => continue,
+            };
+
+            seat.is_active = true;
+            if let Some(street) = street {
+                actions.push((street, index, action, is_all_in));
+            }
+        }
+
+        let hand_id = hand_id.ok_or_else(|| "Hand ID not found".to_string())?;
+        if street.is_none() {
+            return Err("Hand did not reach the flop".to_string());
+        }
+
+        let mut in_hand = (0..seats.len())
+            .filter(|&i| seats[i].is_active && !seats[i].is_folded)
+            .collect::<Vec<_>>();
+        if in_hand.len() != 2 {
+            return Err(format!(
+                "Expected two players on the flop, found {}",
+                in_hand.len()
+            ));
+        }
+
+        // the player closest to the left of the button acts first
+        let button = button.unwrap_or(0);
+        in_hand.sort_by_key(|&i| (seats[i].number <= button, seats[i].number));
+
+        let player_of = |index: usize| in_hand.iter().position(|&i| i == index);
+        let actions = actions
+            .into_iter()
+            .map(|(street, index, action, is_all_in)| {
+                let player = player_of(index).ok_or_else(|| {
+                    format!(
+                        "Player not on the flop took an action: {}",
+                        seats[index].name
+                    )
+                })?;
+                Ok(HistoryEntry {
+                    street,
+                    player,
+                    action,
+                    is_all_in,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Self {
+            hand_id,
+            players: [0, 1].map(|player| seats[in_hand[player]].name.clone()),
+            hero: hero.and_then(player_of),
+            hero_cards: hero_cards.filter(|_| hero.and_then(player_of).is_some()),
+            board,
+            pots,
+            stacks: [0, 1].map(|player| stacks[in_hand[player]]),
+            actions,
+        })
+    }
+}
+
+/// Parses multiple hand histories separated by their header lines.
+///
+/// Each element of the returned vector is the result of parsing one hand, in the order of
+/// appearance. Hands that did not reach a heads-up flop result in `Err`.
+pub fn parse_hand_histories(s: &str) -> Vec<Result<HandHistory, String>> {
+    let mut hands = Vec::new();
+    let mut current = String::new();
+
+    for line in s.lines() {
+        if line.contains("Hand #") && !current.trim().is_empty() {
+            hands.push(current.parse());
+            current.clear();
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+
+    if !current.trim().is_empty() {
+        hands.push(current.parse());
+    }
+
+    hands
+}
+
+/// Parses all the cards enclosed in brackets, e.g., `"*** TURN *** [Qs 7h 2c] [9d]"`.
+fn parse_bracketed_cards(s: &str) -> Result<Vec<Card>, String> {
+    let mut cards = Vec::new();
+    let mut rest = s;
+    while let Some(start) = rest.find('[') {
+        let end = rest[start..]
+            .find(']')
+            .ok_or_else(|| format!("Unclosed bracket: {s}"))?;
+        for card in rest[start + 1..start + end].split_whitespace() {
+            cards.push(card_from_str(card)?);
+        }
+        rest = &rest[start + end + 1..];
+    }
+    Ok(cards)
+}
+
+/// Parses the big blind from the stakes in a header line, e.g., `"($0.50/$1.00 USD)"`.
+fn parse_big_blind(line: &str) -> Option<f64> {
+    let stakes = &line[line.find('(')? + 1..];
+    let (_, big_blind) = stakes[..stakes.find(')')?].split_once('/')?;
+    parse_amount(big_blind.split_whitespace().next()?).ok()
+}
+
+/// Parses an amount such as `"$1,234.50"`, ignoring currency symbols and separators.
+fn parse_amount(s: &str) -> Result<f64, String> {
+    let digits = s
+        .chars()
+        .filter(|&c| c.is_ascii_digit() || c == '.')
+        .collect::<String>();
+    digits.parse().map_err(|_| format!("Invalid amount: {s}"))
+}
+
+/// Parses a number such as a seat number.
+fn parse_number<T: FromStr>(s: &str) -> Result<T, String> {
+    s.parse().map_err(|_| format!("Invalid number: {s}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HANDS: &str = "\
+PokerStars Hand #2001: Hold'em No Limit (50/100) - 2024/01/01 12:00:00 ET
+Table 'Beta' 6-max Seat #2 is the button
+Seat 1: Player One (10,000 in chips)
+Seat 2: Player Two (12,000 in chips)
+Seat 4: Three (5,000 in chips) is sitting out
+Seat 6: Four (8,000 in chips)
+Player One: posts the ante 10
+Player Two: posts the ante 10
+Four: posts the ante 10
+Four: posts small blind 50
+Player One: posts big blind 100
+*** HOLE CARDS ***
+Dealt to Four [Td Jd]
+Player Two: raises 200 to 300
+Four: calls 250
+Player One: folds
+*** FLOP *** [Ah 8d 2s]
+Four: bets 400
+Player Two: raises 800 to 1,200
+Four: raises 6,490 to 7,690 and is all-in
+Player Two: calls 6,490
+*** TURN *** [Ah 8d 2s] [3c]
+*** RIVER *** [Ah 8d 2s 3c] [Kd]
+*** SHOW DOWN ***
+Four: shows [Td Jd] (high card Ace)
+Player Two: shows [As Ks] (two pair, Aces and Kings)
+Player Two collected 15,510 from pot
+*** SUMMARY ***
+Seat 1: Player One (big blind) folded before Flop
+
+PokerStars Hand #2002: Hold'em No Limit (50/100) - 2024/01/01 12:01:00 ET
+Table 'Beta' 6-max Seat #6 is the button
+Seat 1: Player One (10,000 in chips)
+Seat 2: Player Two (8,000 in chips)
+Seat 6: Four (12,000 in chips)
+Player One: posts small blind 50
+Player Two: posts big blind 100
+*** HOLE CARDS ***
+Four: raises 200 to 300
+Player One: folds
+Player Two: folds
+Uncalled bet (200) returned to Four
+Four collected 250 from pot
+*** SUMMARY ***
+";
+
+    #[test]
+    fn test_parse_hand_histories() {
+        let hands = parse_hand_histories(HANDS);
+        assert_eq!(hands.len(), 2);
+        assert_eq!(hands[1], Err("Hand did not reach the flop".to_string()));
+
+        let hand = hands[0].as_ref().unwrap();
+        assert_eq!(hand.hand_id, "2001");
+        assert_eq!(hand.players, ["Four".to_string(), "Player Two".to_string()]);
+        assert_eq!(hand.hero, Some(0));
+        assert_eq!(
+            hand.hero_cards,
+            Some((card_from_str("Td").unwrap(), card_from_str("Jd").unwrap()))
+        );
+        assert_eq!(
+            hand.board,
+            ["Ah", "8d", "2s", "3c", "Kd"].map(|card| card_from_str(card).unwrap())
+        );
+        assert_eq!(hand.pots, vec![730.0, 16110.0, 16110.0]);
+        assert_eq!(hand.stacks, [7690.0, 11690.0]);
+        assert_eq!(
+            hand.actions,
+            vec![
+                HistoryEntry {
+                    street: BoardState::Flop,
+                    player: 0,
+                    action: HistoryAction::Bet(400.0),
+                    is_all_in: false,
+                },
+                HistoryEntry {
+                    street: BoardState::Flop,
+                    player: 1,
+                    action: HistoryAction::Raise(1200.0),
+                    is_all_in: false,
+                },
+                HistoryEntry {
+                    street: BoardState::Flop,
+                    player: 0,
+                    action: HistoryAction::Raise(7690.0),
+                    is_all_in: true,
+                },
+                HistoryEntry {
+                    street: BoardState::Flop,
+                    player: 1,
+                    action: HistoryAction::Call,
+                    is_all_in: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_hand_history_errors() {
+        let three_way = "\
+PokerStars Hand #3001: Hold'em No Limit (50/100)
+Table 'Gamma' 6-max Seat #1 is the button
+Seat 1: A (1,000 in chips)
+Seat 2: B (1,000 in chips)
+Seat 3: C (1,000 in chips)
+B: posts small blind 50
+C: posts big blind 100
+*** HOLE CARDS ***
+A: calls 100
+B: calls 50
+C: checks
+*** FLOP *** [Ah 8d 2s]
+";
+        assert_eq!(
+            three_way.parse::<HandHistory>(),
+            Err("Expected two players on the flop, found 3".to_string())
+        );
+
+        let invalid_board = "\
+PokerStars Hand #3002: Hold'em No Limit (50/100)
+Table 'Gamma' 6-max Seat #1 is the button
+Seat 1: A (1,000 in chips)
+Seat 2: B (1,000 in chips)
+A: posts small blind 50
+B: posts big blind 100
+*** HOLE CARDS ***
+A: calls 50
+B: checks
+*** FLOP *** [Ah 8d]
+";
+        assert!(invalid_board.parse::<HandHistory>().is_err());
+    }
+
+    #[test]
+    fn test_parse_blinds() {
+        let all_in_blind = "\
+PokerStars Hand #4001: Hold'em No Limit ($0.50/$1.00 USD)
+Table 'Delta' 6-max Seat #1 is the button
+Seat 1: Alice ($100 in chips)
+Seat 2: Bob ($1 in chips)
+Alice: posts small blind $0.50
+Bob: posts big blind $1 and is all-in
+*** HOLE CARDS ***
+Alice: calls $0.50
+*** FLOP *** [Ah 8d 2s]
+";
+        let hand = all_in_blind.parse::<HandHistory>().unwrap();
+        assert_eq!(hand.players, ["Bob".to_string(), "Alice".to_string()]);
+        assert_eq!(hand.pots, vec![2.0]);
+        assert_eq!(hand.stacks, [0.0, 99.0]);
+
+        // Dave's small blind is dead: only $1 counts toward the call
+        let dead_blind = "\
+PokerStars Hand #4002: Hold'em No Limit ($0.50/$1.00 USD)
+Table 'Delta' 6-max Seat #1 is the button
+Seat 1: Alice ($100 in chips)
+Seat 2: Bob ($100 in chips)
+Seat 3: Carol ($100 in chips)
+Seat 4: Dave ($100 in chips)
+Bob: posts small blind $0.50
+Carol: posts big blind $1
+Dave: posts small & big blinds $1.50
+*** HOLE CARDS ***
+Dave: raises $2 to $3
+Alice: folds
+Bob: folds
+Carol: calls $2
+*** FLOP *** [Ah 8d 2s]
+";
+        let hand = dead_blind.parse::<HandHistory>().unwrap();
+        assert_eq!(hand.players, ["Carol".to_string(), "Dave".to_string()]);
+        assert_eq!(hand.pots, vec![7.0]);
+        assert_eq!(hand.stacks, [97.0, 96.5]);
+    }
+}
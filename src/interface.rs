@@ -10,7 +10,7 @@ pub trait Game: Send + Sync {
 
     /// Returns the root node of game tree.
     #[doc(hidden)]
-    fn root(&self) -> MutexGuardLike<Self::Node>;
+    fn root(&self) -> MutexGuardLike<'_, Self::Node>;
 
     /// Returns the number of private hands of given player.
     #[doc(hidden)]
@@ -72,11 +72,24 @@ pub trait Game: Send + Sync {
         &[]
     }
 
+    /// Returns the locked aggregate frequencies of the actions and the multipliers used for
+    /// projecting the strategy onto them.
+    #[doc(hidden)]
+    fn locking_frequencies(&self, _node: &Self::Node) -> Option<(&[f32], &MutexLike<Vec<f32>>)> {
+        None
+    }
+
     /// Returns whether the compression is enabled.
     #[doc(hidden)]
     fn is_compression_enabled(&self) -> bool {
         false
     }
+
+    /// Returns whether the 8-bit quantization is enabled (implies that the compression is enabled).
+    #[doc(hidden)]
+    fn is_quantization_enabled(&self) -> bool {
+        false
+    }
 }
 
 /// The trait representing a node in game tree.
@@ -99,7 +112,7 @@ pub trait GameNode: Send + Sync {
 
     /// Returns the node after taking the given action.
     #[doc(hidden)]
-    fn play(&self, action: usize) -> MutexGuardLike<Self>;
+    fn play(&self, action: usize) -> MutexGuardLike<'_, Self>;
 
     /// Returns the strategy.
     #[doc(hidden)]
@@ -179,6 +192,18 @@ pub trait GameNode: Send + Sync {
         unreachable!()
     }
 
+    /// Returns the quantized strategy.
+    #[doc(hidden)]
+    fn strategy_quantized(&self) -> &[u8] {
+        unreachable!()
+    }
+
+    /// Returns the mutable reference to the quantized strategy.
+    #[doc(hidden)]
+    fn strategy_quantized_mut(&mut self) -> &mut [u8] {
+        unreachable!()
+    }
+
     /// Returns the compressed cumulative regrets.
     #[doc(hidden)]
     fn regrets_compressed(&self) -> &[i16] {
@@ -227,6 +252,30 @@ pub trait GameNode: Send + Sync {
         unreachable!()
     }
 
+    /// Returns IP's quantized counterfactual values.
+    #[doc(hidden)]
+    fn cfvalues_ip_quantized(&self) -> &[i8] {
+        unreachable!()
+    }
+
+    /// Returns the mutable reference to IP's quantized counterfactual values.
+    #[doc(hidden)]
+    fn cfvalues_ip_quantized_mut(&mut self) -> &mut [i8] {
+        unreachable!()
+    }
+
+    /// Returns the quantized buffer for counterfactual values.
+    #[doc(hidden)]
+    fn cfvalues_chance_quantized(&self) -> &[i8] {
+        unreachable!()
+    }
+
+    /// Returns the mutable reference to the quantized buffer for counterfactual values.
+    #[doc(hidden)]
+    fn cfvalues_chance_quantized_mut(&mut self) -> &mut [i8] {
+        unreachable!()
+    }
+
     /// Returns the scale of the compressed strategy.
     #[doc(hidden)]
     fn strategy_scale(&self) -> f32 {
@@ -22,6 +22,8 @@
 //!   When calculating summations, temporary values use 64-bit floating-point numbers.
 //!   There is also a compression option where each game node stores the values
 //!   by 16-bit integers with a single 32-bit floating-point scaling factor.
+//!   For coarse solves, the cumulative strategy and the counterfactual values used for display
+//!   can be further quantized to 8-bit integers.
 //! - **Bunching effect**: At the time of writing, this is the only implementation that can handle the bunching effect.
 //!   It supports up to four folded players (6-max game).
 //!   The implementation correctly counts the number of card combinations and does not rely on heuristics
@@ -41,13 +43,23 @@
 //!   Note that this feature assumes that, at most, only one instance of `PostFlopGame` is available
 //!   when solving in a program.
 //!   Disabled by default.
+//! - `lz4`: Uses [lz4_flex] crate to compress and decompress the game tree with LZ4.
+//!   LZ4 compresses less than zstd but decompresses much faster,
+//!   which is useful for interactive viewers.
+//!   Disabled by default.
+//! - `mmap`: Uses [memmap2] crate to back the game tree storage with memory-mapped files.
+//!   This feature is required to use `PostFlopGame::allocate_memory_on_disk`.
+//!   Disabled by default.
 //! - `rayon`: Uses [rayon] crate for parallelization.
 //!   Enabled by default.
 //! - `zstd`: Uses [zstd] crate to compress and decompress the game tree.
-//!   This feature is required to save and load the game tree with compression.
+//!   This feature is required to save and load the game tree with zstd compression,
+//!   including compression with a trained dictionary.
 //!   Disabled by default.
 //!
 //! [bincode]: https://github.com/bincode-org/bincode
+//! [lz4_flex]: https://github.com/PSeitz/lz4_flex
+//! [memmap2]: https://github.com/RazrFalcon/memmap2-rs
 //! [rayon]: https://github.com/rayon-rs/rayon
 //! [zstd]: https://github.com/gyscos/zstd-rs
 
@@ -66,9 +78,11 @@ mod bunching;
 mod card;
 mod game;
 mod hand;
+mod hand_history;
 mod hand_table;
 mod interface;
 mod mutex_like;
+mod rake;
 mod range;
 mod sliceop;
 mod solver;
@@ -82,8 +96,10 @@ pub use bet_size::*;
 pub use bunching::*;
 pub use card::*;
 pub use game::*;
+pub use hand_history::*;
 pub use interface::*;
 pub use mutex_like::*;
+pub use rake::*;
 pub use range::*;
 pub use solver::*;
 pub use utility::*;
@@ -63,7 +63,7 @@ impl<T: ?Sized> MutexLike<T> {
     /// assert_eq!(*mutex_like.lock(), 10);
     /// ```
     #[inline]
-    pub fn lock(&self) -> MutexGuardLike<T> {
+    pub fn lock(&self) -> MutexGuardLike<'_, T> {
         MutexGuardLike { mutex: self }
     }
 }
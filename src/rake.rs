@@ -0,0 +1,277 @@
+use crate::action_tree::BoardState;
+use std::fmt;
+use std::sync::Arc;
+
+#[cfg(feature = "bincode")]
+use bincode::{
+    de::Decoder,
+    enc::Encoder,
+    error::{DecodeError, EncodeError},
+    Decode, Encode,
+};
+
+/// A struct containing the information of a finished hand, which is passed to [`RakeModel`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RakeContext {
+    /// Final pot size, including the starting pot (uncalled bets are not included).
+    pub pot: f64,
+
+    /// Starting pot size.
+    pub starting_pot: f64,
+
+    /// Amount called by each player after the flop, i.e., `(pot - starting_pot) / 2`.
+    pub called_amount: f64,
+
+    /// Street on which the hand finished.
+    pub board_state: BoardState,
+
+    /// Whether the hand finished with a showdown (`false` if someone folded).
+    pub is_showdown: bool,
+}
+
+/// An enum representing the rake structure.
+///
+/// Since the game starts from the flop, the "no flop, no drop" rule is always satisfied. Uncalled
+/// bets are not included in the pot, so every model rakes only the called portion. The computed
+/// rake is clamped to [`0`, `pot`].
+///
+/// # Examples
+/// ```
+/// use postflop_solver::*;
+///
+/// // 5% rake capped at 3 big blinds (big blind = 10) when the pot is 200 or more, otherwise 1 BB
+/// let rake_model = RakeModel::PotSizeCaps {
+///     rate: 0.05,
+///     caps: vec![(0.0, 10.0), (200.0, 30.0)],
+/// };
+///
+/// let tree_config = TreeConfig {
+///     starting_pot: 60,
+///     effective_stack: 970,
+///     rake_model: Some(rake_model),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone)]
+pub enum RakeModel {
+    /// Percentage of the pot with a cap. Same as `rake_rate` and `rake_cap` of `TreeConfig`.
+    Percentage { rate: f64, cap: f64 },
+
+    /// Percentage of the chips wagered after the starting pot (postflop-only rake), i.e.,
+    /// `starting_pot` is not raked.
+    ///
+    /// This is not the "called portion" rake of some sites: since uncalled bets are returned
+    /// before the pot is raked, every model already rakes only the called portion of the pot. If
+    /// your site rakes the called portion of the whole pot (including the preflop money), use
+    /// [`Percentage`] instead; use this variant only if the preflop money is not raked.
+    ///
+    /// [`Percentage`]: RakeModel::Percentage
+    PostflopOnly { rate: f64, cap: f64 },
+
+    /// Percentage of the pot with a cap depending on the street on which the hand finished
+    /// (flop, turn, and river).
+    StreetCaps { rate: f64, caps: [f64; 3] },
+
+    /// Percentage of the pot with a cap depending on the pot size.
+    ///
+    /// Each element of `caps` is a pair of (minimum pot size, cap), and the last element whose
+    /// minimum pot size is less than or equal to the pot is applied (no rake if none).
+    PotSizeCaps { rate: f64, caps: Vec<(f64, f64)> },
+
+    /// Percentage of the pot with a cap depending on the number of players dealt in.
+    ///
+    /// Each element of `caps` is a pair of (minimum number of players, cap), and the last element
+    /// whose minimum number of players is less than or equal to `num_players` is applied (no rake
+    /// if none).
+    PlayerCountCaps {
+        rate: f64,
+        num_players: u32,
+        caps: Vec<(u32, f64)>,
+    },
+
+    /// Custom rake function (cannot be serialized).
+    Custom(Arc<dyn Fn(&RakeContext) -> f64 + Send + Sync>),
+}
+
+impl RakeModel {
+    /// Computes the rake of the finished hand.
+    #[inline]
+    pub fn rake(&self, context: &RakeContext) -> f64 {
+        let rake = match self {
+            Self::Percentage { rate, cap } => (context.pot * rate).min(*cap),
+            Self::PostflopOnly { rate, cap } => (2.0 * context.called_amount * rate).min(*cap),
+            Self::StreetCaps { rate, caps } => {
+                (context.pot * rate).min(caps[context.board_state as usize])
+            }
+            Self::PotSizeCaps { rate, caps } => caps
+                .iter()
+                .rev()
+                .find(|&&(min_pot, _)| min_pot <= context.pot)
+                .map_or(0.0, |&(_, cap)| (context.pot * rate).min(cap)),
+            Self::PlayerCountCaps {
+                rate,
+                num_players,
+                caps,
+            } => caps
+                .iter()
+                .rev()
+                .find(|&&(min_players, _)| min_players <= *num_players)
+                .map_or(0.0, |&(_, cap)| (context.pot * rate).min(cap)),
+            Self::Custom(f) => f(context),
+        };
+        rake.clamp(0.0, context.pot)
+    }
+
+    /// Checks the parameters.
+    pub(crate) fn check(&self) -> Result<(), String> {
+        let (rate, caps) = match self {
+            Self::Percentage { rate, cap } | Self::PostflopOnly { rate, cap } => {
+                (*rate, vec![*cap])
+            }
+            Self::StreetCaps { rate, caps } => (*rate, caps.to_vec()),
+            Self::PotSizeCaps { rate, caps } => (*rate, caps.iter().map(|x| x.1).collect()),
+            Self::PlayerCountCaps { rate, caps, .. } => (*rate, caps.iter().map(|x| x.1).collect()),
+            Self::Custom(_) => return Ok(()),
+        };
+
+        if !(0.0..=1.0).contains(&rate) {
+            return Err(format!("Rake rate must be between 0.0 and 1.0: {rate}"));
+        }
+
+        if let Some(cap) = caps.iter().find(|&&cap| cap < 0.0 || cap.is_nan()) {
+            return Err(format!("Rake cap must be non-negative: {cap}"));
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the rake is never charged.
+    #[inline]
+    pub(crate) fn is_zero(&self) -> bool {
+        match self {
+            Self::Percentage { rate, cap } | Self::PostflopOnly { rate, cap } => {
+                *rate == 0.0 || *cap == 0.0
+            }
+            Self::StreetCaps { rate, caps } => *rate == 0.0 || caps.iter().all(|&c| c == 0.0),
+            Self::PotSizeCaps { rate, caps } => *rate == 0.0 || caps.iter().all(|c| c.1 == 0.0),
+            Self::PlayerCountCaps { rate, caps, .. } => {
+                *rate == 0.0 || caps.iter().all(|c| c.1 == 0.0)
+            }
+            Self::Custom(_) => false,
+        }
+    }
+}
+
+impl fmt::Debug for RakeModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Percentage { rate, cap } => f
+                .debug_struct("Percentage")
+                .field("rate", rate)
+                .field("cap", cap)
+                .finish(),
+            Self::PostflopOnly { rate, cap } => f
+                .debug_struct("PostflopOnly")
+                .field("rate", rate)
+                .field("cap", cap)
+                .finish(),
+            Self::StreetCaps { rate, caps } => f
+                .debug_struct("StreetCaps")
+                .field("rate", rate)
+                .field("caps", caps)
+                .finish(),
+            Self::PotSizeCaps { rate, caps } => f
+                .debug_struct("PotSizeCaps")
+                .field("rate", rate)
+                .field("caps", caps)
+                .finish(),
+            Self::PlayerCountCaps {
+                rate,
+                num_players,
+                caps,
+            } => f
+                .debug_struct("PlayerCountCaps")
+                .field("rate", rate)
+                .field("num_players", num_players)
+                .field("caps", caps)
+                .finish(),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl Encode for RakeModel {
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        match self {
+            Self::Percentage { rate, cap } => {
+                0u8.encode(encoder)?;
+                rate.encode(encoder)?;
+                cap.encode(encoder)
+            }
+            Self::PostflopOnly { rate, cap } => {
+                1u8.encode(encoder)?;
+                rate.encode(encoder)?;
+                cap.encode(encoder)
+            }
+            Self::StreetCaps { rate, caps } => {
+                2u8.encode(encoder)?;
+                rate.encode(encoder)?;
+                caps.encode(encoder)
+            }
+            Self::PotSizeCaps { rate, caps } => {
+                3u8.encode(encoder)?;
+                rate.encode(encoder)?;
+                caps.encode(encoder)
+            }
+            Self::PlayerCountCaps {
+                rate,
+                num_players,
+                caps,
+            } => {
+                4u8.encode(encoder)?;
+                rate.encode(encoder)?;
+                num_players.encode(encoder)?;
+                caps.encode(encoder)
+            }
+            Self::Custom(_) => Err(EncodeError::Other("Custom rake model cannot be serialized")),
+        }
+    }
+}
+
+#[cfg(feature = "bincode")]
+impl Decode for RakeModel {
+    fn decode<D: Decoder>(decoder: &mut D) -> Result<Self, DecodeError> {
+        let tag = u8::decode(decoder)?;
+        let rate = f64::decode(decoder)?;
+        match tag {
+            0 => Ok(Self::Percentage {
+                rate,
+                cap: Decode::decode(decoder)?,
+            }),
+            1 => Ok(Self::PostflopOnly {
+                rate,
+                cap: Decode::decode(decoder)?,
+            }),
+            2 => Ok(Self::StreetCaps {
+                rate,
+                caps: Decode::decode(decoder)?,
+            }),
+            3 => Ok(Self::PotSizeCaps {
+                rate,
+                caps: Decode::decode(decoder)?,
+            }),
+            4 => Ok(Self::PlayerCountCaps {
+                rate,
+                num_players: Decode::decode(decoder)?,
+                caps: Decode::decode(decoder)?,
+            }),
+            _ => Err(DecodeError::OtherString(format!(
+                "Invalid rake model: {tag}"
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "bincode")]
+bincode::impl_borrow_decode!(RakeModel);
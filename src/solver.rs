@@ -245,6 +245,9 @@ fn solve_recursive<T: Game>(
 
         // node-locking
         let locking = game.locking_strategy(node);
+        if let Some((_, multipliers)) = game.locking_frequencies(node) {
+            scale_strategy(&mut strategy, &multipliers.lock(), num_actions);
+        }
         apply_locking_strategy(&mut strategy, locking);
 
         // sum up the counterfactual values
@@ -255,12 +258,23 @@ fn solve_recursive<T: Game>(
         if game.is_compression_enabled() {
             // update the cumulative strategy
             let scale = node.strategy_scale();
-            let decoder = params.gamma_t * scale / u16::MAX as f32;
-            let cum_strategy = node.strategy_compressed_mut();
-
-            strategy.iter_mut().zip(&*cum_strategy).for_each(|(x, y)| {
-                *x += (*y as f32) * decoder;
-            });
+            if game.is_quantization_enabled() {
+                let decoder = params.gamma_t * scale / u8::MAX as f32;
+                strategy
+                    .iter_mut()
+                    .zip(node.strategy_quantized())
+                    .for_each(|(x, y)| {
+                        *x += (*y as f32) * decoder;
+                    });
+            } else {
+                let decoder = params.gamma_t * scale / u16::MAX as f32;
+                strategy
+                    .iter_mut()
+                    .zip(node.strategy_compressed())
+                    .for_each(|(x, y)| {
+                        *x += (*y as f32) * decoder;
+                    });
+            }
 
             if !locking.is_empty() {
                 strategy.iter_mut().zip(locking).for_each(|(d, s)| {
@@ -270,7 +284,11 @@ fn solve_recursive<T: Game>(
                 })
             }
 
-            let new_scale = encode_unsigned_slice(cum_strategy, &strategy);
+            let new_scale = if game.is_quantization_enabled() {
+                encode_unsigned_slice_quantized(node.strategy_quantized_mut(), &strategy)
+            } else {
+                encode_unsigned_slice(node.strategy_compressed_mut(), &strategy)
+            };
             node.set_strategy_scale(new_scale);
 
             // update the cumulative regret
@@ -328,6 +346,16 @@ fn solve_recursive<T: Game>(
 
         // node-locking
         let locking = game.locking_strategy(node);
+        if let Some((frequencies, multipliers)) = game.locking_frequencies(node) {
+            let multipliers = &mut multipliers.lock();
+            project_frequencies(
+                &mut cfreach_actions,
+                cfreach,
+                frequencies,
+                locking,
+                multipliers,
+            );
+        }
         apply_locking_strategy(&mut cfreach_actions, locking);
 
         // update the reach probabilities
@@ -355,6 +383,233 @@ fn solve_recursive<T: Game>(
     }
 }
 
+/// Scales the probability of each action by the multiplier and normalizes the strategy of each
+/// hand.
+///
+/// The strategy is mixed with the uniform strategy by a small amount beforehand so that every
+/// action can be scaled up.
+#[inline]
+fn scale_strategy(strategy: &mut [f32], multipliers: &[f32], num_actions: usize) {
+    const EPSILON: f32 = 1e-3;
+    let num_hands = strategy.len() / num_actions;
+    let uniform = EPSILON / num_actions as f32;
+
+    for hand in 0..num_hands {
+        let mut sum = 0.0;
+        for (action, &multiplier) in multipliers.iter().enumerate() {
+            let prob = &mut strategy[action * num_hands + hand];
+            *prob = ((1.0 - EPSILON) * *prob + uniform) * multiplier;
+            sum += *prob;
+        }
+
+        for action in 0..num_actions {
+            strategy[action * num_hands + hand] /= sum;
+        }
+    }
+}
+
+/// Projects the strategy so that the aggregate frequencies of the actions weighted by `reach`
+/// match `frequencies`.
+///
+/// The projected strategy is `scale_strategy` applied with the multipliers `exp(u)`, where `u`
+/// minimizes the convex function
+///
+/// ```text
+/// f(u) = sum_h reach[h] * ln(sum_a p[h][a] * exp(u[a])) - sum_a target[a] * u[a]
+/// ```
+///
+/// and `p` is the strategy mixed with the uniform strategy. The gradient of `f` is the aggregate
+/// frequencies minus the target, so the minimizer matches the target exactly. It is found by
+/// Newton's method with a backtracking line search, which stops when the aggregate reach of every
+/// action is within `1e-6` of the target relative to the total reach. The multipliers are kept in
+/// the node and used as the starting point, so one or two Newton steps per iteration are usually
+/// enough.
+///
+/// The hands locked by `locking` are excluded from the projection, and their contributions are
+/// subtracted from the target. If the locked hands alone exceed the frequency of an action, the
+/// unlocked hands never take the action and the rest of the target is scaled up proportionally.
+fn project_frequencies(
+    strategy: &mut [f32],
+    reach: &[f32],
+    frequencies: &[f32],
+    locking: &[f32],
+    multipliers: &mut [f32],
+) {
+    const TOLERANCE: f64 = 1e-6;
+    const MAX_ITERATIONS: usize = 50;
+    let num_actions = frequencies.len();
+    let num_hands = reach.len();
+    let is_locked = |hand: usize| !locking.is_empty() && locking[hand].is_sign_positive();
+
+    // target of the aggregate reach of each action over the unlocked hands
+    let total = reach.iter().map(|&r| r as f64).sum::<f64>();
+    let mut target = frequencies
+        .iter()
+        .map(|&f| f as f64 * total)
+        .collect::<Vec<_>>();
+    for hand in (0..num_hands).filter(|&h| is_locked(h)) {
+        for (action, t) in target.iter_mut().enumerate() {
+            *t -= (reach[hand] * locking[action * num_hands + hand]) as f64;
+        }
+    }
+
+    let hands = (0..num_hands)
+        .filter(|&h| !is_locked(h) && reach[h] > 0.0)
+        .collect::<Vec<_>>();
+    let unlocked = hands.iter().map(|&h| reach[h] as f64).sum::<f64>();
+
+    target.iter_mut().for_each(|t| *t = t.max(0.0));
+    let target_sum = target.iter().sum::<f64>();
+    if unlocked <= 0.0 || target_sum <= 0.0 {
+        return;
+    }
+    target.iter_mut().for_each(|t| *t *= unlocked / target_sum);
+
+    // the first active action is the reference (u = 0) since `f` is invariant to adding a
+    // constant to `u`
+    let active = (0..num_actions)
+        .filter(|&a| target[a] > 0.0)
+        .collect::<Vec<_>>();
+    let num_free = active.len() - 1;
+
+    // mixed strategy of the unlocked hands
+    let mut mixed = strategy.to_vec();
+    scale_strategy(&mut mixed, &vec![1.0; num_actions], num_actions);
+    let p = |hand: usize, i: usize| mixed[active[i] * num_hands + hand] as f64;
+
+    let log = |m: f32| if m > 0.0 { (m as f64).ln() } else { 0.0 };
+    let u0 = log(multipliers[active[0]]);
+    let mut u = active
+        .iter()
+        .map(|&a| log(multipliers[a]) - u0)
+        .collect::<Vec<_>>();
+
+    // returns `f(u)` and the aggregate reach of each active action
+    let evaluate = |u: &[f64]| {
+        let mut value = 0.0;
+        let mut aggregate = vec![0.0; active.len()];
+        for &hand in &hands {
+            let r = reach[hand] as f64;
+            let z = (0..active.len())
+                .map(|i| p(hand, i) * u[i].exp())
+                .sum::<f64>();
+            value += r * z.ln();
+            for (i, x) in aggregate.iter_mut().enumerate() {
+                *x += r * p(hand, i) * u[i].exp() / z;
+            }
+        }
+        for (i, &a) in active.iter().enumerate() {
+            value -= target[a] * u[i];
+        }
+        (value, aggregate)
+    };
+
+    for _ in 0..MAX_ITERATIONS {
+        let (value, aggregate) = evaluate(&u);
+        let gradient = (1..active.len())
+            .map(|i| aggregate[i] - target[active[i]])
+            .collect::<Vec<_>>();
+        let error = (0..active.len())
+            .map(|i| (aggregate[i] - target[active[i]]).abs())
+            .fold(0.0, f64::max);
+        if error <= TOLERANCE * total {
+            break;
+        }
+
+        // Hessian with respect to the free variables
+        let mut hessian = vec![0.0; num_free * num_free];
+        for &hand in &hands {
+            let r = reach[hand] as f64;
+            let z = (0..active.len())
+                .map(|i| p(hand, i) * u[i].exp())
+                .sum::<f64>();
+            let q = (1..active.len())
+                .map(|i| p(hand, i) * u[i].exp() / z)
+                .collect::<Vec<_>>();
+            for i in 0..num_free {
+                hessian[i * num_free + i] += r * q[i];
+                for j in 0..num_free {
+                    hessian[i * num_free + j] -= r * q[i] * q[j];
+                }
+            }
+        }
+
+        let mut direction = gradient.iter().map(|&g| -g).collect::<Vec<_>>();
+        if !solve_linear_system(&mut hessian, &mut direction) {
+            break;
+        }
+
+        // backtracking line search (Armijo condition)
+        let slope = gradient
+            .iter()
+            .zip(&direction)
+            .map(|(g, d)| g * d)
+            .sum::<f64>();
+        let mut step = 1.0;
+        let mut candidate = u.clone();
+        while step > 1e-10 {
+            for i in 0..num_free {
+                candidate[i + 1] = u[i + 1] + step * direction[i];
+            }
+            if evaluate(&candidate).0 <= value + 1e-4 * step * slope {
+                break;
+            }
+            step *= 0.5;
+        }
+        u = candidate;
+    }
+
+    // avoid overflow and underflow
+    let max_u = u.iter().fold(f64::MIN, |m, &x| m.max(x));
+    multipliers.iter_mut().for_each(|m| *m = 0.0);
+    for (i, &a) in active.iter().enumerate() {
+        multipliers[a] = ((u[i] - max_u).exp() as f32).max(f32::MIN_POSITIVE);
+    }
+
+    scale_strategy(strategy, multipliers, num_actions);
+}
+
+/// Solves the linear system `matrix * x = rhs` by Gaussian elimination with partial pivoting and
+/// stores `x` in `rhs`. Returns `false` if the matrix is singular.
+fn solve_linear_system(matrix: &mut [f64], rhs: &mut [f64]) -> bool {
+    let n = rhs.len();
+
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| {
+                matrix[i * n + col]
+                    .abs()
+                    .total_cmp(&matrix[j * n + col].abs())
+            })
+            .unwrap();
+        if matrix[pivot * n + col].abs() < 1e-300 {
+            return false;
+        }
+
+        for k in 0..n {
+            matrix.swap(col * n + k, pivot * n + k);
+        }
+        rhs.swap(col, pivot);
+
+        for row in col + 1..n {
+            let factor = matrix[row * n + col] / matrix[col * n + col];
+            for k in col..n {
+                matrix[row * n + k] -= factor * matrix[col * n + k];
+            }
+            rhs[row] -= factor * rhs[col];
+        }
+    }
+
+    for col in (0..n).rev() {
+        let sum = (col + 1..n)
+            .map(|k| matrix[col * n + k] * rhs[k])
+            .sum::<f64>();
+        rhs[col] = (rhs[col] - sum) / matrix[col * n + col];
+    }
+
+    true
+}
+
 /// Computes the strategy by regret-matching algorithm.
 #[cfg(feature = "custom-alloc")]
 #[inline]
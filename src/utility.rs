@@ -227,6 +227,48 @@ pub(crate) fn encode_unsigned_slice(dst: &mut [u16], slice: &[f32]) -> f32 {
     scale
 }
 
+/// Encodes the `f32` slice to the `i8` slice, and returns the scale.
+#[inline]
+pub(crate) fn encode_signed_slice_quantized(dst: &mut [i8], slice: &[f32]) -> f32 {
+    let scale = slice_absolute_max(slice);
+    let scale_nonzero = if scale == 0.0 { 1.0 } else { scale };
+    let encoder = i8::MAX as f32 / scale_nonzero;
+    dst.iter_mut()
+        .zip(slice)
+        .for_each(|(d, s)| *d = unsafe { (s * encoder).round().to_int_unchecked::<i32>() as i8 });
+    scale
+}
+
+/// Encodes the `f32` slice to the `u8` slice with stochastic rounding, and returns the scale.
+///
+/// The cumulative strategy is re-encoded every iteration, so rounding to the nearest value would
+/// discard every increment smaller than half a step. Rounding up with the probability of the
+/// fractional part keeps the expected value unchanged. The rounding noise is derived from the
+/// values and their positions, so the results are reproducible.
+#[inline]
+pub(crate) fn encode_unsigned_slice_quantized(dst: &mut [u8], slice: &[f32]) -> f32 {
+    let scale = slice_nonnegative_max(slice);
+    let scale_nonzero = if scale == 0.0 { 1.0 } else { scale };
+    let encoder = u8::MAX as f32 / scale_nonzero;
+    dst.iter_mut()
+        .zip(slice)
+        .enumerate()
+        .for_each(|(i, (d, s))| {
+            // splitmix32-style hash to a uniform value in [0, 1)
+            let mut h = s.to_bits() ^ (i as u32).wrapping_mul(0x9e3779b9);
+            h = (h ^ (h >> 16)).wrapping_mul(0x7feb352d);
+            h = (h ^ (h >> 15)).wrapping_mul(0x846ca68b);
+            h ^= h >> 16;
+            let noise = (h >> 8) as f32 * (1.0 / (1 << 24) as f32);
+            *d = unsafe {
+                (s * encoder + noise)
+                    .to_int_unchecked::<i32>()
+                    .min(u8::MAX as i32) as u8
+            }
+        });
+    scale
+}
+
 /// Applies the given swap to the given slice.
 #[inline]
 pub(crate) fn apply_swap<T>(slice: &mut [T], swap_list: &[(u16, u16)]) {
@@ -441,7 +483,11 @@ fn compute_cfvalue_recursive<T: Game>(
         // save the counterfactual values
         if save_cfvalues && node.cfvalue_storage_player() == Some(player) {
             let result = unsafe { &*(result as *const _ as *const [f32]) };
-            if game.is_compression_enabled() {
+            if game.is_quantization_enabled() {
+                let cfv_scale =
+                    encode_signed_slice_quantized(node.cfvalues_chance_quantized_mut(), result);
+                node.set_cfvalue_chance_scale(cfv_scale);
+            } else if game.is_compression_enabled() {
                 let cfv_scale = encode_signed_slice(node.cfvalues_chance_compressed_mut(), result);
                 node.set_cfvalue_chance_scale(cfv_scale);
             } else {
@@ -465,13 +511,17 @@ fn compute_cfvalue_recursive<T: Game>(
 
         // obtain the strategy
         #[cfg(feature = "custom-alloc")]
-        let mut strategy = if game.is_compression_enabled() {
+        let mut strategy = if game.is_quantization_enabled() {
+            normalized_strategy_compressed_custom_alloc(node.strategy_quantized(), num_actions)
+        } else if game.is_compression_enabled() {
             normalized_strategy_compressed_custom_alloc(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy_custom_alloc(node.strategy(), num_actions)
         };
         #[cfg(not(feature = "custom-alloc"))]
-        let mut strategy = if game.is_compression_enabled() {
+        let mut strategy = if game.is_quantization_enabled() {
+            normalized_strategy_compressed(node.strategy_quantized(), num_actions)
+        } else if game.is_compression_enabled() {
             normalized_strategy_compressed(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy(node.strategy(), num_actions)
@@ -510,13 +560,17 @@ fn compute_cfvalue_recursive<T: Game>(
     } else {
         // obtain the strategy
         #[cfg(feature = "custom-alloc")]
-        let mut cfreach_actions = if game.is_compression_enabled() {
+        let mut cfreach_actions = if game.is_quantization_enabled() {
+            normalized_strategy_compressed_custom_alloc(node.strategy_quantized(), num_actions)
+        } else if game.is_compression_enabled() {
             normalized_strategy_compressed_custom_alloc(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy_custom_alloc(node.strategy(), num_actions)
         };
         #[cfg(not(feature = "custom-alloc"))]
-        let mut cfreach_actions = if game.is_compression_enabled() {
+        let mut cfreach_actions = if game.is_quantization_enabled() {
+            normalized_strategy_compressed(node.strategy_quantized(), num_actions)
+        } else if game.is_compression_enabled() {
             normalized_strategy_compressed(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy(node.strategy(), num_actions)
@@ -553,7 +607,10 @@ fn compute_cfvalue_recursive<T: Game>(
     // save the counterfactual values for IP
     if save_cfvalues && node.has_cfvalues_ip() && player == 1 {
         let result = unsafe { &*(result as *const _ as *const [f32]) };
-        if game.is_compression_enabled() {
+        if game.is_quantization_enabled() {
+            let cfv_scale = encode_signed_slice_quantized(node.cfvalues_ip_quantized_mut(), result);
+            node.set_cfvalue_ip_scale(cfv_scale);
+        } else if game.is_compression_enabled() {
             let cfv_scale = encode_signed_slice(node.cfvalues_ip_compressed_mut(), result);
             node.set_cfvalue_ip_scale(cfv_scale);
         } else {
@@ -563,7 +620,7 @@ fn compute_cfvalue_recursive<T: Game>(
 }
 
 /// The recursive helper function for computing the counterfactual values of best response.
-fn compute_best_cfv_recursive<T: Game>(
+pub(crate) fn compute_best_cfv_recursive<T: Game>(
     result: &mut [MaybeUninit<f32>],
     game: &T,
     node: &T::Node,
@@ -667,7 +724,28 @@ fn compute_best_cfv_recursive<T: Game>(
         let mut cfv_actions = cfv_actions.lock();
         unsafe { cfv_actions.set_len(num_actions * num_hands) };
 
-        if locking.is_empty() {
+        if game.locking_frequencies(node).is_some() {
+            // when the frequencies are locked, the strategy is treated as fixed
+            #[cfg(feature = "custom-alloc")]
+            let mut strategy = if game.is_quantization_enabled() {
+                normalized_strategy_compressed_custom_alloc(node.strategy_quantized(), num_actions)
+            } else if game.is_compression_enabled() {
+                normalized_strategy_compressed_custom_alloc(node.strategy_compressed(), num_actions)
+            } else {
+                normalized_strategy_custom_alloc(node.strategy(), num_actions)
+            };
+            #[cfg(not(feature = "custom-alloc"))]
+            let mut strategy = if game.is_quantization_enabled() {
+                normalized_strategy_compressed(node.strategy_quantized(), num_actions)
+            } else if game.is_compression_enabled() {
+                normalized_strategy_compressed(node.strategy_compressed(), num_actions)
+            } else {
+                normalized_strategy(node.strategy(), num_actions)
+            };
+
+            apply_locking_strategy(&mut strategy, locking);
+            fma_slices_uninit(result, &strategy, &cfv_actions);
+        } else if locking.is_empty() {
             // compute element-wise maximum (take the best response)
             max_slices_uninit(result, &cfv_actions);
         } else {
@@ -679,13 +757,17 @@ fn compute_best_cfv_recursive<T: Game>(
     else {
         // obtain the strategy
         #[cfg(feature = "custom-alloc")]
-        let mut cfreach_actions = if game.is_compression_enabled() {
+        let mut cfreach_actions = if game.is_quantization_enabled() {
+            normalized_strategy_compressed_custom_alloc(node.strategy_quantized(), num_actions)
+        } else if game.is_compression_enabled() {
             normalized_strategy_compressed_custom_alloc(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy_custom_alloc(node.strategy(), num_actions)
         };
         #[cfg(not(feature = "custom-alloc"))]
-        let mut cfreach_actions = if game.is_compression_enabled() {
+        let mut cfreach_actions = if game.is_quantization_enabled() {
+            normalized_strategy_compressed(node.strategy_quantized(), num_actions)
+        } else if game.is_compression_enabled() {
             normalized_strategy_compressed(node.strategy_compressed(), num_actions)
         } else {
             normalized_strategy(node.strategy(), num_actions)
@@ -769,15 +851,15 @@ pub(crate) fn normalized_strategy(strategy: &[f32], num_actions: usize) -> Vec<f
 
 #[cfg(feature = "custom-alloc")]
 #[inline]
-pub(crate) fn normalized_strategy_compressed_custom_alloc(
-    strategy: &[u16],
+pub(crate) fn normalized_strategy_compressed_custom_alloc<T: Copy + Into<f32>>(
+    strategy: &[T],
     num_actions: usize,
 ) -> Vec<f32, StackAlloc> {
     let mut normalized = Vec::with_capacity_in(strategy.len(), StackAlloc);
     let uninit = normalized.spare_capacity_mut();
 
     uninit.iter_mut().zip(strategy).for_each(|(n, s)| {
-        n.write(*s as f32);
+        n.write((*s).into());
     });
     unsafe { normalized.set_len(strategy.len()) };
 
@@ -795,12 +877,15 @@ pub(crate) fn normalized_strategy_compressed_custom_alloc(
 }
 
 #[inline]
-pub(crate) fn normalized_strategy_compressed(strategy: &[u16], num_actions: usize) -> Vec<f32> {
+pub(crate) fn normalized_strategy_compressed<T: Copy + Into<f32>>(
+    strategy: &[T],
+    num_actions: usize,
+) -> Vec<f32> {
     let mut normalized = Vec::with_capacity(strategy.len());
     let uninit = normalized.spare_capacity_mut();
 
     uninit.iter_mut().zip(strategy).for_each(|(n, s)| {
-        n.write(*s as f32);
+        n.write((*s).into());
     });
     unsafe { normalized.set_len(strategy.len()) };
 